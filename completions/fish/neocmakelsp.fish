@@ -30,6 +30,7 @@ complete -c neocmakelsp -n "__fish_neocmakelsp_needs_command" -f -a "stdio" -d '
 complete -c neocmakelsp -n "__fish_neocmakelsp_needs_command" -f -a "tcp" -d 'run with tcp'
 complete -c neocmakelsp -n "__fish_neocmakelsp_needs_command" -f -a "search" -d 'search the packages'
 complete -c neocmakelsp -n "__fish_neocmakelsp_needs_command" -f -a "format" -d 'Format the file'
+complete -c neocmakelsp -n "__fish_neocmakelsp_needs_command" -f -a "lint" -d 'Lint the files'
 complete -c neocmakelsp -n "__fish_neocmakelsp_needs_command" -f -a "tree" -d 'show the file tree'
 complete -c neocmakelsp -n "__fish_neocmakelsp_needs_command" -f -a "gen-completions" -d 'generate the completion'
 complete -c neocmakelsp -n "__fish_neocmakelsp_needs_command" -f -a "help" -d 'Print this message or the help of the given subcommand(s)'
@@ -38,15 +39,24 @@ complete -c neocmakelsp -n "__fish_neocmakelsp_using_subcommand tcp" -l port -r
 complete -c neocmakelsp -n "__fish_neocmakelsp_using_subcommand tcp" -s h -l help -d 'Print help'
 complete -c neocmakelsp -n "__fish_neocmakelsp_using_subcommand search" -s j
 complete -c neocmakelsp -n "__fish_neocmakelsp_using_subcommand search" -s h -l help -d 'Print help'
+complete -c neocmakelsp -n "__fish_neocmakelsp_using_subcommand format" -l stdin-filepath -d 'the path of the content from stdin, used to find the config' -r -F
 complete -c neocmakelsp -n "__fish_neocmakelsp_using_subcommand format" -s o -l override
+complete -c neocmakelsp -n "__fish_neocmakelsp_using_subcommand format" -l check -d 'list the files which are not formatted, and exit with 1 if there is any'
+complete -c neocmakelsp -n "__fish_neocmakelsp_using_subcommand format" -l diff -d 'print the unified diff of the files which are not formatted'
 complete -c neocmakelsp -n "__fish_neocmakelsp_using_subcommand format" -s h -l help -d 'Print help'
+complete -c neocmakelsp -n "__fish_neocmakelsp_using_subcommand lint" -l output-format -d 'how the diagnostics are printed' -r -f -a "human\t''
+json\t''
+sarif\t''"
+complete -c neocmakelsp -n "__fish_neocmakelsp_using_subcommand lint" -l build-dir -d 'the build directory with CMakeCache.txt, to check the missing packages' -r -F
+complete -c neocmakelsp -n "__fish_neocmakelsp_using_subcommand lint" -s h -l help -d 'Print help'
 complete -c neocmakelsp -n "__fish_neocmakelsp_using_subcommand tree" -s j
 complete -c neocmakelsp -n "__fish_neocmakelsp_using_subcommand tree" -s h -l help -d 'Print help'
 complete -c neocmakelsp -n "__fish_neocmakelsp_using_subcommand gen-completions" -s h -l help -d 'Print help'
-complete -c neocmakelsp -n "__fish_neocmakelsp_using_subcommand help; and not __fish_seen_subcommand_from stdio tcp search format tree gen-completions help" -f -a "stdio" -d 'run with stdio'
-complete -c neocmakelsp -n "__fish_neocmakelsp_using_subcommand help; and not __fish_seen_subcommand_from stdio tcp search format tree gen-completions help" -f -a "tcp" -d 'run with tcp'
-complete -c neocmakelsp -n "__fish_neocmakelsp_using_subcommand help; and not __fish_seen_subcommand_from stdio tcp search format tree gen-completions help" -f -a "search" -d 'search the packages'
-complete -c neocmakelsp -n "__fish_neocmakelsp_using_subcommand help; and not __fish_seen_subcommand_from stdio tcp search format tree gen-completions help" -f -a "format" -d 'Format the file'
-complete -c neocmakelsp -n "__fish_neocmakelsp_using_subcommand help; and not __fish_seen_subcommand_from stdio tcp search format tree gen-completions help" -f -a "tree" -d 'show the file tree'
-complete -c neocmakelsp -n "__fish_neocmakelsp_using_subcommand help; and not __fish_seen_subcommand_from stdio tcp search format tree gen-completions help" -f -a "gen-completions" -d 'generate the completion'
-complete -c neocmakelsp -n "__fish_neocmakelsp_using_subcommand help; and not __fish_seen_subcommand_from stdio tcp search format tree gen-completions help" -f -a "help" -d 'Print this message or the help of the given subcommand(s)'
+complete -c neocmakelsp -n "__fish_neocmakelsp_using_subcommand help; and not __fish_seen_subcommand_from stdio tcp search format lint tree gen-completions help" -f -a "stdio" -d 'run with stdio'
+complete -c neocmakelsp -n "__fish_neocmakelsp_using_subcommand help; and not __fish_seen_subcommand_from stdio tcp search format lint tree gen-completions help" -f -a "tcp" -d 'run with tcp'
+complete -c neocmakelsp -n "__fish_neocmakelsp_using_subcommand help; and not __fish_seen_subcommand_from stdio tcp search format lint tree gen-completions help" -f -a "search" -d 'search the packages'
+complete -c neocmakelsp -n "__fish_neocmakelsp_using_subcommand help; and not __fish_seen_subcommand_from stdio tcp search format lint tree gen-completions help" -f -a "format" -d 'Format the file'
+complete -c neocmakelsp -n "__fish_neocmakelsp_using_subcommand help; and not __fish_seen_subcommand_from stdio tcp search format lint tree gen-completions help" -f -a "lint" -d 'Lint the files'
+complete -c neocmakelsp -n "__fish_neocmakelsp_using_subcommand help; and not __fish_seen_subcommand_from stdio tcp search format lint tree gen-completions help" -f -a "tree" -d 'show the file tree'
+complete -c neocmakelsp -n "__fish_neocmakelsp_using_subcommand help; and not __fish_seen_subcommand_from stdio tcp search format lint tree gen-completions help" -f -a "gen-completions" -d 'generate the completion'
+complete -c neocmakelsp -n "__fish_neocmakelsp_using_subcommand help; and not __fish_seen_subcommand_from stdio tcp search format lint tree gen-completions help" -f -a "help" -d 'Print this message or the help of the given subcommand(s)'
@@ -1,4 +1,3 @@
-use crate::consts::TREESITTER_CMAKE_LANGUAGE;
 /// Get the tree of ast
 use crate::utils::treehelper::ToPosition;
 use crate::CMakeNodeKinds;
@@ -13,16 +12,17 @@ const COMMAND_KEYWORDS: [&str; 5] = [
     "target_link_libraries",
     "target_include_directories",
 ];
-pub async fn getast(client: &Client, context: &str) -> Option<DocumentSymbolResponse> {
+pub async fn getast(
+    client: &Client,
+    context: &str,
+    tree: &tree_sitter::Tree,
+) -> Option<DocumentSymbolResponse> {
     let line = context.lines().count();
     if line > 10000 {
         client
             .log_message(MessageType::INFO, "use simple ast")
             .await;
     }
-    let mut parse = tree_sitter::Parser::new();
-    parse.set_language(&TREESITTER_CMAKE_LANGUAGE).unwrap();
-    let tree = parse.parse(context, None)?;
     getsubast(tree.root_node(), &context.lines().collect(), line > 10000)
         .map(DocumentSymbolResponse::Nested)
 }
@@ -237,6 +237,7 @@ fn getsubast(
 #[cfg(test)]
 mod ast_test {
     use super::*;
+    use crate::consts::TREESITTER_CMAKE_LANGUAGE;
     #[test]
     fn test_ast_1() {
         let context = include_str!("../assert/ast_test/bast_test.cmake");
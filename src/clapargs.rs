@@ -1,10 +1,17 @@
 use std::path::PathBuf;
 
-use clap::{arg, Parser};
+use clap::{arg, Parser, ValueEnum};
 use clap_complete::Shell;
 
 const LSP_VERSION: &str = env!("CARGO_PKG_VERSION");
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum LintOutputFormat {
+    Human,
+    Json,
+    Sarif,
+}
+
 #[derive(Debug, Parser, PartialEq, Eq)]
 #[command(
     name = "neocmakelsp",
@@ -34,6 +41,26 @@ pub enum NeocmakeCli {
         format_paths: Vec<String>,
         #[arg(value_name = "override", long = "override", short = 'o')]
         hasoverride: bool,
+        /// list the files which are not formatted, and exit with 1 if there is any
+        #[arg(long = "check", conflicts_with = "hasoverride")]
+        check: bool,
+        /// print the unified diff of the files which are not formatted
+        #[arg(long = "diff", conflicts_with = "hasoverride")]
+        diff: bool,
+        /// the path of the content from stdin, used to find the config
+        #[arg(long = "stdin-filepath", value_name = "path")]
+        stdin_filepath: Option<PathBuf>,
+    },
+    #[command(long_flag = "lint", short_flag = 'L', about = "Lint the files")]
+    Lint {
+        #[arg(required = true)]
+        lint_paths: Vec<String>,
+        /// how the diagnostics are printed
+        #[arg(long = "output-format", value_enum, default_value_t = LintOutputFormat::Human)]
+        output_format: LintOutputFormat,
+        /// the build directory with CMakeCache.txt, to check the missing packages
+        #[arg(long = "build-dir", value_name = "path")]
+        build_dir: Option<PathBuf>,
     },
     #[command(long_flag = "tree", short_flag = 'T', about = "show the file tree")]
     Tree {
@@ -61,6 +88,9 @@ fn test_claps() {
     if let NeocmakeCli::Format {
         format_paths,
         hasoverride: true,
+        check: false,
+        diff: false,
+        stdin_filepath: None,
     } = cli
     {
         assert_eq!(format_paths, vec!["a".to_string(), "b".to_string()]);
@@ -68,6 +98,68 @@ fn test_claps() {
         panic!("test format failed");
     }
 
+    let mut args = NeocmakeCli::command().get_matches_from(vec![
+        "neocmakelsp",
+        "format",
+        "--check",
+        "--diff",
+        "a",
+    ]);
+
+    let cli = NeocmakeCli::from_arg_matches_mut(&mut args).unwrap();
+    assert_eq!(
+        cli,
+        NeocmakeCli::Format {
+            format_paths: vec!["a".to_string()],
+            hasoverride: false,
+            check: true,
+            diff: true,
+            stdin_filepath: None,
+        }
+    );
+
+    let mut args = NeocmakeCli::command().get_matches_from(vec![
+        "neocmakelsp",
+        "format",
+        "--stdin-filepath",
+        "cmake/utils.cmake",
+        "-",
+    ]);
+
+    let cli = NeocmakeCli::from_arg_matches_mut(&mut args).unwrap();
+    assert_eq!(
+        cli,
+        NeocmakeCli::Format {
+            format_paths: vec!["-".to_string()],
+            hasoverride: false,
+            check: false,
+            diff: false,
+            stdin_filepath: Some(PathBuf::from("cmake/utils.cmake")),
+        }
+    );
+
+    assert!(NeocmakeCli::command()
+        .try_get_matches_from(vec!["neocmakelsp", "format", "-o", "--check", "a"])
+        .is_err());
+
+    let mut args = NeocmakeCli::command().get_matches_from(vec![
+        "neocmakelsp",
+        "lint",
+        "--output-format",
+        "sarif",
+        "a",
+    ]);
+
+    let cli = NeocmakeCli::from_arg_matches_mut(&mut args).unwrap();
+    assert_eq!(
+        cli,
+        NeocmakeCli::Lint {
+            lint_paths: vec!["a".to_string()],
+            output_format: LintOutputFormat::Sarif,
+            build_dir: None,
+        }
+    );
+
     let mut args =
         NeocmakeCli::command().get_matches_from(vec!["neocmakelsp", "search", "-j", "dde"]);
 
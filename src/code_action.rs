@@ -0,0 +1,140 @@
+/// Quick fixes for the diagnostics, the fix is stored in the data of the diagnostic
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+use tower_lsp::lsp_types::{
+    CodeAction, CodeActionKind, CodeActionOrCommand, CreateFile, CreateFileOptions, Diagnostic,
+    DocumentChangeOperation, DocumentChanges, Range, ResourceOp, TextEdit, Url, WorkspaceEdit,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum QuickFix {
+    /// change the case of the command name
+    ChangeCase { new_name: String },
+    /// create the missing file of include or add_subdirectory
+    CreateFile { path: PathBuf },
+    /// remove the argument and the space around it
+    RemoveArgument { range: Range },
+    /// remove the spaces at the end of the line
+    RemoveTrailingWhitespace,
+}
+
+impl QuickFix {
+    fn title(&self) -> String {
+        match self {
+            QuickFix::ChangeCase { new_name } => format!("Change command to {new_name}"),
+            QuickFix::CreateFile { path } => format!("Create {}", path.display()),
+            QuickFix::RemoveArgument { .. } => "Remove empty argument".to_string(),
+            QuickFix::RemoveTrailingWhitespace => "Remove trailing whitespace".to_string(),
+        }
+    }
+
+    fn edit(&self, uri: &Url, diagnostic: &Diagnostic) -> Option<WorkspaceEdit> {
+        let text_edit = |range: Range, new_text: &str| WorkspaceEdit {
+            changes: Some(HashMap::from([(
+                uri.clone(),
+                vec![TextEdit {
+                    range,
+                    new_text: new_text.to_string(),
+                }],
+            )])),
+            ..Default::default()
+        };
+        match self {
+            QuickFix::ChangeCase { new_name } => Some(text_edit(diagnostic.range, new_name)),
+            QuickFix::RemoveArgument { range } => Some(text_edit(*range, "")),
+            QuickFix::RemoveTrailingWhitespace => Some(text_edit(diagnostic.range, "")),
+            QuickFix::CreateFile { path } => Some(WorkspaceEdit {
+                document_changes: Some(DocumentChanges::Operations(vec![
+                    DocumentChangeOperation::Op(ResourceOp::Create(CreateFile {
+                        uri: Url::from_file_path(path).ok()?,
+                        options: Some(CreateFileOptions {
+                            overwrite: Some(false),
+                            ignore_if_exists: Some(true),
+                        }),
+                        annotation_id: None,
+                    })),
+                ])),
+                ..Default::default()
+            }),
+        }
+    }
+}
+
+/// get the quick fixes of the diagnostics in the request
+pub fn get_code_actions(uri: &Url, diagnostics: &[Diagnostic]) -> Option<Vec<CodeActionOrCommand>> {
+    let actions: Vec<CodeActionOrCommand> = diagnostics
+        .iter()
+        .filter_map(|diagnostic| {
+            let fix: QuickFix = serde_json::from_value(diagnostic.data.clone()?).ok()?;
+            Some(CodeActionOrCommand::CodeAction(CodeAction {
+                title: fix.title(),
+                kind: Some(CodeActionKind::QUICKFIX),
+                diagnostics: Some(vec![diagnostic.clone()]),
+                edit: Some(fix.edit(uri, diagnostic)?),
+                is_preferred: Some(true),
+                ..Default::default()
+            }))
+        })
+        .collect();
+    if actions.is_empty() {
+        None
+    } else {
+        Some(actions)
+    }
+}
+
+#[cfg(test)]
+mod code_action_test {
+    use super::*;
+    use tower_lsp::lsp_types::Position;
+
+    #[test]
+    fn tst_code_actions() {
+        let uri = Url::from_file_path("/tmp/code_action/CMakeLists.txt").unwrap();
+        let range = Range {
+            start: Position {
+                line: 0,
+                character: 0,
+            },
+            end: Position {
+                line: 0,
+                character: 7,
+            },
+        };
+        let diagnostics = vec![
+            Diagnostic {
+                range,
+                message: "suggested to use upcase".to_string(),
+                data: Some(
+                    serde_json::to_value(QuickFix::ChangeCase {
+                        new_name: "MESSAGE".to_string(),
+                    })
+                    .unwrap(),
+                ),
+                ..Default::default()
+            },
+            Diagnostic {
+                range,
+                message: "Grammar error".to_string(),
+                ..Default::default()
+            },
+        ];
+        let actions = get_code_actions(&uri, &diagnostics).unwrap();
+        assert_eq!(actions.len(), 1);
+        let CodeActionOrCommand::CodeAction(action) = &actions[0] else {
+            panic!("should be code action");
+        };
+        assert_eq!(action.title, "Change command to MESSAGE");
+        let changes = action.edit.as_ref().unwrap().changes.as_ref().unwrap();
+        assert_eq!(
+            changes.get(&uri).unwrap(),
+            &vec![TextEdit {
+                range,
+                new_text: "MESSAGE".to_string(),
+            }]
+        );
+    }
+}
@@ -3,22 +3,25 @@ mod findpackage;
 mod includescanner;
 use crate::consts::TREESITTER_CMAKE_LANGUAGE;
 use crate::fileapi;
-use crate::languageserver::BUFFERS_CACHE;
+use crate::genex;
 use crate::scansubs::TREE_MAP;
 use crate::utils::treehelper::{get_pos_type, PositionType, ToPoint};
 use crate::utils::{
     gen_module_pattern, include_is_module, remove_quotation_and_replace_placeholders,
     LineCommentTmp, CACHE_CMAKE_PACKAGES_WITHKEYS,
 };
-use buildin::{BUILDIN_COMMAND, BUILDIN_MODULE, BUILDIN_VARIABLE};
+use buildin::BUILDIN_MODULE;
+pub use buildin::{
+    buildin_command_keywords, buildin_variable_positions, is_keyword, CommandSignature,
+    BUILDIN_COMMAND, BUILDIN_COMMAND_SIGNATURES, BUILDIN_SIGNATURE, BUILDIN_VARIABLE,
+};
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tokio::fs;
 use tokio::sync::Mutex;
 use tower_lsp::lsp_types::{
-    self, CompletionItem, CompletionItemKind, CompletionResponse, Documentation, MessageType,
-    Position,
+    CompletionItem, CompletionItemKind, CompletionResponse, Documentation, MessageType, Position,
 };
 
 use crate::CMakeNodeKinds;
@@ -85,12 +88,6 @@ pub async fn get_cached_completion<P: AsRef<Path>>(path: P) -> Vec<CompletionIte
         if let Some(data) = complete_cache.get(parent) {
             completions.append(&mut data.clone());
         } else if let Ok(context) = fs::read_to_string(parent).await {
-            let mut buffer_cache = BUFFERS_CACHE.lock().await;
-            buffer_cache.insert(
-                lsp_types::Url::from_file_path(parent).unwrap(),
-                context.clone(),
-            );
-            drop(buffer_cache);
             drop(complete_cache);
             completions.append(&mut update_cache(parent, context.as_str()).await);
             path.clone_from(parent);
@@ -105,18 +102,28 @@ pub async fn get_cached_completion<P: AsRef<Path>>(path: P) -> Vec<CompletionIte
 /// get the complete messages
 pub async fn getcomplete(
     source: &str,
+    tree: &tree_sitter::Tree,
     location: Position,
     client: &tower_lsp::Client,
     local_path: &str,
     find_cmake_in_package: bool,
 ) -> Option<CompletionResponse> {
-    let mut parse = tree_sitter::Parser::new();
-    parse.set_language(&TREESITTER_CMAKE_LANGUAGE).unwrap();
-    let thetree = parse.parse(source, None);
-    let tree = thetree.unwrap();
     let mut complete: Vec<CompletionItem> = vec![];
 
     let current_point = location.to_point();
+    if let Some(line) = source
+        .lines()
+        .nth(current_point.row)
+        .and_then(|line| line.get(..current_point.column))
+    {
+        if genex::completion_prefix(line).is_some() {
+            return Some(CompletionResponse::Array(genex::completion_items()));
+        }
+        // NOTE: "<" is a trigger character only for the generator expressions
+        if line.ends_with('<') {
+            return None;
+        }
+    }
     let postype = get_pos_type(current_point, tree.root_node(), source);
     match postype {
         PositionType::VarOrFun | PositionType::TargetLink | PositionType::TargetInclude => {
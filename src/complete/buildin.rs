@@ -1,11 +1,18 @@
 /// buildin Commands and vars
+mod signature;
+
 use anyhow::Result;
+#[cfg(not(test))]
 use std::process::Command;
 use std::sync::LazyLock;
-use std::{collections::HashMap, iter::zip};
+use std::{
+    collections::{HashMap, HashSet},
+    iter::zip,
+};
 use tower_lsp::lsp_types::{CompletionItem, CompletionItemKind, Documentation, InsertTextFormat};
 
 use crate::languageserver::client_support_snippet;
+pub use signature::{is_keyword, CommandSignature};
 
 fn shorter_var(arg: &str) -> String {
     let mut shorter = arg.to_string();
@@ -90,20 +97,52 @@ fn tst_convert_to_lsp_snippet() {
     assert_eq!(snippet_result, snippet_target);
 }
 
-fn gen_buildin_commands(raw_info: &str) -> Result<Vec<CompletionItem>> {
-    let re = regex::Regex::new(r"[a-zA-z]+\n-+").unwrap();
-    let keys: Vec<_> = re
+fn signature_regex(key: &str) -> regex::Regex {
+    let s = format!(r"\n\s+(?P<signature>{key}\([^)]*\))");
+    regex::Regex::new(s.as_str()).unwrap()
+}
+
+/// split the output of `cmake --help-commands` into the name and the doc of every command
+fn split_command_docs(raw_info: &str) -> Vec<(&str, &str)> {
+    let re = regex::Regex::new(r"[a-zA-Z_]+\n-+").unwrap();
+    let keys = re
         .find_iter(raw_info)
-        .map(|message| {
-            let temp: Vec<&str> = message.as_str().split('\n').collect();
-            temp[0]
-        })
-        .collect();
-    let contents: Vec<_> = re.split(raw_info).collect();
-    let contents = &contents[1..].to_vec();
+        .map(|title| title.as_str().split('\n').next().unwrap_or_default());
+    keys.zip(re.split(raw_info).skip(1)).collect()
+}
+
+/// get all the signatures of the command in the doc, like `add_library(<name> ...)`
+/// NOTE: examples in the doc are also matched, they do not have placeholders, so skip them
+fn gen_buildin_signatures(docs: &[(&str, &str)]) -> HashMap<String, Vec<String>> {
+    let mut signatures = HashMap::new();
+    for (key, content) in docs {
+        let r_match_signature = signature_regex(key);
+        let mut key_signatures: Vec<String> = vec![];
+        for m in r_match_signature.captures_iter(content) {
+            let signature = m
+                .name("signature")
+                .unwrap()
+                .as_str()
+                .split_whitespace()
+                .collect::<Vec<&str>>()
+                .join(" ");
+            if signature.contains("$<") || !(signature.contains('<') || signature.contains('[')) {
+                continue;
+            }
+            if !key_signatures.contains(&signature) {
+                key_signatures.push(signature);
+            }
+        }
+        if !key_signatures.is_empty() {
+            signatures.insert(key.to_lowercase(), key_signatures);
+        }
+    }
+    signatures
+}
 
+fn gen_buildin_commands(docs: &[(&str, &str)]) -> Result<Vec<CompletionItem>> {
     let mut completes = HashMap::new();
-    for (key, content) in keys.iter().zip(contents) {
+    for (key, content) in docs {
         let small_key = key.to_lowercase();
         let big_key = key.to_uppercase();
         completes.insert(small_key, content.to_string());
@@ -129,8 +168,7 @@ fn gen_buildin_commands(raw_info: &str) -> Result<Vec<CompletionItem>> {
             let mut insert_text_format = InsertTextFormat::PLAIN_TEXT;
             let mut insert_text = akey.to_string();
             let mut detail = "Function".to_string();
-            let s = format!(r"\n\s+(?P<signature>{}\([^)]*\))", akey);
-            let r_match_signature = regex::Regex::new(s.as_str()).unwrap();
+            let r_match_signature = signature_regex(akey);
 
             // snippets only work for lower case for now...
             if client_support_snippet
@@ -205,32 +243,95 @@ fn gen_buildin_modules(raw_info: &str) -> Result<Vec<CompletionItem>> {
         .collect())
 }
 
+#[cfg(not(test))]
+fn cmake_help(argument: &str) -> std::io::Result<String> {
+    let output = Command::new("cmake").arg(argument).output()?;
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// the saved output is used in the tests, so they do not depend on the installed cmake
+#[cfg(test)]
+fn cmake_help(argument: &str) -> std::io::Result<String> {
+    let output = match argument {
+        "--help-commands" => include_str!("../../assert/cmake_help_commands.txt"),
+        "--help-variables" => include_str!("../../assert/cmake_help_variables.txt"),
+        _ => include_str!("../../assert/cmake_help_modules.txt"),
+    };
+    Ok(output.to_string())
+}
+
+/// the output of `cmake --help-commands`, cmake is only run once for all the command tables
+static COMMANDS_HELP: LazyLock<std::io::Result<String>> =
+    LazyLock::new(|| cmake_help("--help-commands"));
+
+/// the name and the doc of every buildin command
+static COMMAND_DOCS: LazyLock<Vec<(&'static str, &'static str)>> =
+    LazyLock::new(|| match &*COMMANDS_HELP {
+        Ok(help) => split_command_docs(help),
+        Err(_) => vec![],
+    });
+
 /// CMake build in commands
 pub static BUILDIN_COMMAND: LazyLock<Result<Vec<CompletionItem>>> = LazyLock::new(|| {
-    let output = Command::new("cmake")
-        .arg("--help-commands")
-        .output()?
-        .stdout;
-    let temp = String::from_utf8_lossy(&output);
-    gen_buildin_commands(&temp)
+    if let Err(error) = &*COMMANDS_HELP {
+        return Err(anyhow::Error::msg(error.to_string()));
+    }
+    gen_buildin_commands(&COMMAND_DOCS)
 });
 
+/// CMake build in command signatures, the key is lowercase
+pub static BUILDIN_SIGNATURE: LazyLock<HashMap<String, Vec<String>>> =
+    LazyLock::new(|| gen_buildin_signatures(&COMMAND_DOCS));
+
+/// the structured signatures of the buildin commands, the key is lowercase
+pub static BUILDIN_COMMAND_SIGNATURES: LazyLock<HashMap<String, Vec<CommandSignature>>> =
+    LazyLock::new(|| gen_command_signatures(&BUILDIN_SIGNATURE));
+
+/// the keywords of the buildin command, such as PUBLIC of target_link_libraries
+pub fn buildin_command_keywords(command: &str) -> HashSet<&'static str> {
+    BUILDIN_COMMAND_SIGNATURES
+        .get(&command.to_lowercase())
+        .map(|signatures| {
+            signatures
+                .iter()
+                .flat_map(CommandSignature::keywords)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// the indexes of the arguments which are the names of variables, such as `<out-var>`
+pub fn buildin_variable_positions(command: &str, arguments: &[Option<&str>]) -> Vec<usize> {
+    BUILDIN_COMMAND_SIGNATURES
+        .get(&command.to_lowercase())
+        .into_iter()
+        .flatten()
+        .flat_map(|signature| signature.variable_positions(arguments))
+        .collect()
+}
+
+fn gen_command_signatures(
+    signatures: &HashMap<String, Vec<String>>,
+) -> HashMap<String, Vec<CommandSignature>> {
+    signatures
+        .iter()
+        .map(|(command, signatures)| {
+            let signatures = signatures
+                .iter()
+                .filter_map(|signature| CommandSignature::parse(signature))
+                .collect();
+            (command.clone(), signatures)
+        })
+        .collect()
+}
+
 /// cmake buildin vars
-pub static BUILDIN_VARIABLE: LazyLock<Result<Vec<CompletionItem>>> = LazyLock::new(|| {
-    let output = Command::new("cmake")
-        .arg("--help-variables")
-        .output()?
-        .stdout;
-    let temp = String::from_utf8_lossy(&output);
-    gen_buildin_variables(&temp)
-});
+pub static BUILDIN_VARIABLE: LazyLock<Result<Vec<CompletionItem>>> =
+    LazyLock::new(|| gen_buildin_variables(&cmake_help("--help-variables")?));
 
 /// Cmake buildin modules
-pub static BUILDIN_MODULE: LazyLock<Result<Vec<CompletionItem>>> = LazyLock::new(|| {
-    let output = Command::new("cmake").arg("--help-modules").output()?.stdout;
-    let temp = String::from_utf8_lossy(&output);
-    gen_buildin_modules(&temp)
-});
+pub static BUILDIN_MODULE: LazyLock<Result<Vec<CompletionItem>>> =
+    LazyLock::new(|| gen_buildin_modules(&cmake_help("--help-modules")?));
 
 #[cfg(test)]
 mod tests {
@@ -238,7 +339,9 @@ mod tests {
 
     use crate::complete::buildin::{gen_buildin_modules, gen_buildin_variables};
 
-    use super::gen_buildin_commands;
+    use super::{
+        gen_buildin_commands, gen_buildin_signatures, gen_command_signatures, split_command_docs,
+    };
     #[test]
     fn tst_regex() {
         let re = regex::Regex::new(r"-+").unwrap();
@@ -257,11 +360,38 @@ mod tests {
         // NOTE: In case the command fails, ignore test
         let output = include_str!("../../assert/cmake_help_commands.txt");
 
-        let output = gen_buildin_commands(&output);
+        let output = gen_buildin_commands(&split_command_docs(output));
 
         assert!(output.is_ok());
     }
 
+    #[test]
+    fn tst_cmake_command_signatures() {
+        let output = include_str!("../../assert/cmake_help_commands.txt");
+
+        let docs = split_command_docs(output);
+        assert!(docs.iter().any(|(key, _)| *key == "add_custom_target"));
+        let signatures = gen_buildin_signatures(&docs);
+
+        let add_library = signatures.get("add_library").unwrap();
+        assert!(add_library
+            .contains(&"add_library(<name> [<type>] [EXCLUDE_FROM_ALL] <sources>...)".to_string()));
+        assert!(!add_library.contains(&"add_library(myLib out.c)".to_string()));
+        let install = signatures.get("install").unwrap();
+        assert_eq!(install[0], "install(TARGETS <target>... [...])");
+        assert!(install.len() > 1);
+
+        let command_signatures = gen_command_signatures(&signatures);
+        let target_link_libraries = command_signatures.get("target_link_libraries").unwrap();
+        assert!(target_link_libraries
+            .iter()
+            .any(|signature| signature.keywords().contains("PUBLIC")));
+        let file = command_signatures.get("file").unwrap();
+        assert!(file
+            .iter()
+            .any(|signature| signature.modes() == vec!["READ"] && signature.min_arguments() == 3));
+    }
+
     #[test]
     fn tst_cmake_variables_buildin() {
         // NOTE: In case the command fails, ignore test
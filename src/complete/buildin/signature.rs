@@ -0,0 +1,594 @@
+/// the structured signatures of the buildin commands, parsed from the signatures in the doc
+use std::collections::HashSet;
+
+/// one item of the signature, such as `<target>`, `EXCLUDE_FROM_ALL` or `[COMPONENT <component>]`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SignatureItem {
+    /// the flag, such as `EXCLUDE_FROM_ALL`, or the start of a section, such as `DESTINATION <dir>`
+    Keyword(String),
+    /// the value given by the user, such as `<target>`
+    Positional(String),
+    /// one of the items, such as `<PRIVATE|PUBLIC|INTERFACE>` or `{WRITE | APPEND}`
+    Choice(Vec<SignatureItem>),
+    /// the items in `[]`, which can be omitted
+    Optional(Vec<SignatureItem>),
+    /// the item followed by `...`, which can be repeated
+    Repeated(Box<SignatureItem>),
+    /// the bare `...`, any arguments
+    Any,
+}
+
+/// the algorithms of `file(<HASH> ...)` and `string(<HASH> ...)`
+const HASH_ALGORITHMS: &[&str] = &[
+    "MD5", "SHA1", "SHA224", "SHA256", "SHA384", "SHA512", "SHA3_224", "SHA3_256", "SHA3_384",
+    "SHA3_512",
+];
+
+/// the placeholders whose values are keywords listed in the doc, not in the signature
+const KEYWORD_PLACEHOLDERS: &[(&str, &str, &[&str])] = &[
+    (
+        "message",
+        "mode",
+        &[
+            "FATAL_ERROR",
+            "SEND_ERROR",
+            "WARNING",
+            "AUTHOR_WARNING",
+            "DEPRECATION",
+            "NOTICE",
+            "STATUS",
+            "VERBOSE",
+            "DEBUG",
+            "TRACE",
+        ],
+    ),
+    (
+        "message",
+        "checkState",
+        &["CHECK_START", "CHECK_PASS", "CHECK_FAIL"],
+    ),
+    ("file", "HASH", HASH_ALGORITHMS),
+    ("string", "HASH", HASH_ALGORITHMS),
+];
+
+/// the placeholder whose value is the name of a variable, such as `<out-var>` or `<list>`
+fn is_variable_placeholder(placeholder: &str) -> bool {
+    let placeholder = placeholder.to_ascii_lowercase();
+    matches!(
+        placeholder.as_str(),
+        "list" | "lists" | "result" | "varname" | "var-name"
+    ) || placeholder.ends_with("var")
+        || placeholder.ends_with("variable")
+}
+
+/// the word like `PUBLIC` or `EXCLUDE_FROM_ALL`, which is not given by the user
+pub fn is_keyword(word: &str) -> bool {
+    word.starts_with(|c: char| c.is_ascii_uppercase())
+        && word
+            .chars()
+            .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_')
+}
+
+impl SignatureItem {
+    fn from_word(command: &str, word: &str) -> Self {
+        if word == "..." {
+            return Self::Any;
+        }
+        if let Some(item) = word.strip_suffix("...") {
+            return Self::Repeated(Box::new(Self::from_word(command, item)));
+        }
+        if let Some(inner) = word
+            .strip_prefix('<')
+            .and_then(|word| word.strip_suffix('>'))
+            .filter(|inner| !inner.contains(['<', '>']))
+        {
+            let choices: Vec<&str> = inner.split('|').map(str::trim).collect();
+            if choices.len() > 1 && choices.iter().all(|choice| is_keyword(choice)) {
+                return Self::Choice(
+                    choices
+                        .iter()
+                        .map(|choice| Self::Keyword(choice.to_string()))
+                        .collect(),
+                );
+            }
+            if let Some((_, _, values)) = KEYWORD_PLACEHOLDERS
+                .iter()
+                .find(|(name, placeholder, _)| *name == command && *placeholder == inner)
+            {
+                return Self::Choice(
+                    values
+                        .iter()
+                        .map(|value| Self::Keyword(value.to_string()))
+                        .collect(),
+                );
+            }
+            return Self::Positional(inner.to_string());
+        }
+        if is_keyword(word) {
+            return Self::Keyword(word.to_string());
+        }
+        Self::Positional(word.to_string())
+    }
+
+    /// the least count of the arguments, the repeated ones can be empty, like the sources of add_library
+    fn min_arguments(&self) -> usize {
+        match self {
+            Self::Keyword(_) | Self::Positional(_) => 1,
+            Self::Choice(items) => items.iter().map(Self::min_arguments).min().unwrap_or(0),
+            Self::Optional(_) | Self::Repeated(_) | Self::Any => 0,
+        }
+    }
+
+    fn collect_keywords<'a>(&'a self, keywords: &mut HashSet<&'a str>) {
+        match self {
+            Self::Keyword(keyword) => {
+                keywords.insert(keyword.as_str());
+            }
+            Self::Choice(items) | Self::Optional(items) => {
+                for item in items {
+                    item.collect_keywords(keywords);
+                }
+            }
+            Self::Repeated(item) => item.collect_keywords(keywords),
+            Self::Positional(_) | Self::Any => {}
+        }
+    }
+}
+
+struct SignatureParser<'a> {
+    command: &'a str,
+    chars: std::iter::Peekable<std::str::CharIndices<'a>>,
+    input: &'a str,
+}
+
+impl SignatureParser<'_> {
+    /// read one word, the brackets inside it are kept, such as `ENV{<variable>}`
+    fn read_word(&mut self, start: usize) -> Option<&str> {
+        let mut depth = 0;
+        let mut in_quote = false;
+        let mut end = self.input.len();
+        while let Some(&(index, c)) = self.chars.peek() {
+            match c {
+                '"' => in_quote = !in_quote,
+                _ if in_quote => {}
+                '<' | '{' => depth += 1,
+                '>' | '}' if depth > 0 => depth -= 1,
+                _ if depth > 0 => {}
+                '[' | ']' | '|' | ')' | '}' => {
+                    end = index;
+                    break;
+                }
+                _ if c.is_whitespace() => {
+                    end = index;
+                    break;
+                }
+                _ => {}
+            }
+            self.chars.next();
+        }
+        if depth != 0 || in_quote {
+            return None;
+        }
+        Some(&self.input[start..end])
+    }
+
+    /// parse the items until the closing char, None if the brackets are not balanced
+    fn parse_items(&mut self, closing: Option<char>) -> Option<Vec<SignatureItem>> {
+        let mut items: Vec<SignatureItem> = vec![];
+        let mut is_choice = false;
+        loop {
+            let Some(&(index, c)) = self.chars.peek() else {
+                return closing.is_none().then_some(items);
+            };
+            let item = match c {
+                _ if c.is_whitespace() => {
+                    self.chars.next();
+                    continue;
+                }
+                '|' => {
+                    self.chars.next();
+                    is_choice = !items.is_empty();
+                    continue;
+                }
+                ']' | '}' | ')' => {
+                    self.chars.next();
+                    return (closing == Some(c)).then_some(items);
+                }
+                '[' => {
+                    self.chars.next();
+                    let item = SignatureItem::Optional(self.parse_items(Some(']'))?);
+                    if self.rest_starts_with("...") {
+                        self.skip(3);
+                        SignatureItem::Repeated(Box::new(item))
+                    } else {
+                        item
+                    }
+                }
+                '{' | '(' => {
+                    self.chars.next();
+                    let closing = if c == '{' { '}' } else { ')' };
+                    let mut group = self.parse_items(Some(closing))?;
+                    if group.len() != 1 {
+                        // NOTE: the group of several items is the same as the items
+                        items.append(&mut group);
+                        continue;
+                    }
+                    group.remove(0)
+                }
+                _ => {
+                    let command = self.command;
+                    SignatureItem::from_word(command, self.read_word(index)?)
+                }
+            };
+            match items.last_mut() {
+                Some(SignatureItem::Choice(choices)) if is_choice => choices.push(item),
+                Some(last) if is_choice => {
+                    let previous = std::mem::replace(last, SignatureItem::Any);
+                    *last = SignatureItem::Choice(vec![previous, item]);
+                }
+                _ => items.push(item),
+            }
+            is_choice = false;
+        }
+    }
+
+    fn rest_starts_with(&mut self, pattern: &str) -> bool {
+        self.chars
+            .peek()
+            .is_some_and(|&(index, _)| self.input[index..].starts_with(pattern))
+    }
+
+    fn skip(&mut self, count: usize) {
+        for _ in 0..count {
+            self.chars.next();
+        }
+    }
+}
+
+/// the state of the automaton built from the items, the arguments are matched one by one
+enum MatchState {
+    /// consume the argument equal to the keyword
+    Keyword(String, usize),
+    /// consume any argument, true if it is the name of a variable
+    Argument(bool, usize),
+    /// go to both states without consuming the argument
+    Split(usize, usize),
+    End,
+}
+
+#[derive(Default)]
+struct Matcher {
+    states: Vec<MatchState>,
+}
+
+impl Matcher {
+    fn push(&mut self, state: MatchState) -> usize {
+        self.states.push(state);
+        self.states.len() - 1
+    }
+
+    /// the repeated items can be empty, the same as in min_arguments
+    fn push_loop(&mut self, next: usize, body: impl FnOnce(&mut Self, usize) -> usize) -> usize {
+        let split = self.push(MatchState::Split(next, next));
+        let start = body(self, split);
+        self.states[split] = MatchState::Split(start, next);
+        split
+    }
+
+    fn compile_items(&mut self, items: &[SignatureItem], next: usize) -> usize {
+        items
+            .iter()
+            .rev()
+            .fold(next, |next, item| self.compile(item, next))
+    }
+
+    fn compile(&mut self, item: &SignatureItem, next: usize) -> usize {
+        match item {
+            SignatureItem::Keyword(keyword) => {
+                self.push(MatchState::Keyword(keyword.clone(), next))
+            }
+            SignatureItem::Positional(placeholder) => self.push(MatchState::Argument(
+                is_variable_placeholder(placeholder),
+                next,
+            )),
+            SignatureItem::Choice(items) => {
+                let mut starts = items.iter().map(|item| self.compile(item, next));
+                let first = starts.next().unwrap_or(next);
+                let starts: Vec<usize> = starts.collect();
+                starts.into_iter().fold(first, |start, other| {
+                    self.push(MatchState::Split(start, other))
+                })
+            }
+            SignatureItem::Optional(items) => {
+                let start = self.compile_items(items, next);
+                self.push(MatchState::Split(start, next))
+            }
+            SignatureItem::Repeated(item) => {
+                self.push_loop(next, |matcher, split| matcher.compile(item, split))
+            }
+            SignatureItem::Any => self.push_loop(next, |matcher, split| {
+                matcher.push(MatchState::Argument(false, split))
+            }),
+        }
+    }
+
+    /// the states which consume an argument or end, reached from the state without consuming
+    fn closure(&self, state: usize, output: &mut HashSet<usize>, visited: &mut HashSet<usize>) {
+        if !visited.insert(state) {
+            return;
+        }
+        match self.states[state] {
+            MatchState::Split(first, second) => {
+                self.closure(first, output, visited);
+                self.closure(second, output, visited);
+            }
+            _ => {
+                output.insert(state);
+            }
+        }
+    }
+
+    fn closure_of(&self, state: usize) -> HashSet<usize> {
+        let mut output = HashSet::new();
+        self.closure(state, &mut output, &mut HashSet::new());
+        output
+    }
+
+    /// the next state if the state consumes the argument
+    fn step(&self, state: usize, argument: Option<&str>, ignore_case: bool) -> Option<usize> {
+        match &self.states[state] {
+            MatchState::Keyword(keyword, next) => argument
+                .is_some_and(|argument| {
+                    argument == keyword || ignore_case && argument.eq_ignore_ascii_case(keyword)
+                })
+                .then_some(*next),
+            MatchState::Argument(_, next) => Some(*next),
+            MatchState::Split(..) | MatchState::End => None,
+        }
+    }
+}
+
+/// the signature of the command, such as `add_library(<name> [<type>] [EXCLUDE_FROM_ALL] <sources>...)`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommandSignature {
+    pub items: Vec<SignatureItem>,
+}
+
+impl CommandSignature {
+    /// None if the signature is cut off, the regex in the doc stops at the first ")"
+    pub fn parse(signature: &str) -> Option<Self> {
+        let start = signature.find('(')?;
+        let inner = signature[start + 1..].strip_suffix(')')?;
+        let mut parser = SignatureParser {
+            command: signature[..start].trim(),
+            chars: inner.char_indices().peekable(),
+            input: inner,
+        };
+        Some(Self {
+            items: parser.parse_items(None)?,
+        })
+    }
+
+    pub fn min_arguments(&self) -> usize {
+        self.items.iter().map(SignatureItem::min_arguments).sum()
+    }
+
+    pub fn keywords(&self) -> HashSet<&str> {
+        let mut keywords = HashSet::new();
+        for item in &self.items {
+            item.collect_keywords(&mut keywords);
+        }
+        keywords
+    }
+
+    /// the first keywords, which select the signature, such as `READ` of `file(READ ...)`
+    pub fn modes(&self) -> Vec<&str> {
+        match self.items.first() {
+            Some(SignatureItem::Keyword(keyword)) => vec![keyword.as_str()],
+            Some(SignatureItem::Choice(choices)) => choices
+                .iter()
+                .filter_map(|choice| match choice {
+                    SignatureItem::Keyword(keyword) => Some(keyword.as_str()),
+                    _ => None,
+                })
+                .collect(),
+            _ => vec![],
+        }
+    }
+
+    /// the first argument is a name given by the user, such as the target
+    pub fn starts_with_positional(&self) -> bool {
+        matches!(self.items.first(), Some(SignatureItem::Positional(_)))
+    }
+
+    /// the indexes of the arguments which can be matched as keywords,
+    /// None is given for the argument which cannot be a keyword, such as the quoted one.
+    /// Nothing is returned if the arguments do not match the signature
+    pub fn keyword_positions(&self, arguments: &[Option<&str>], ignore_case: bool) -> Vec<usize> {
+        self.positions(arguments, ignore_case, |state| {
+            matches!(state, MatchState::Keyword(..))
+        })
+    }
+
+    /// the indexes of the arguments which can be matched as the names of variables,
+    /// such as `<variable>` of `set` or `<out-var>` of `string(REGEX MATCH ...)`
+    pub fn variable_positions(&self, arguments: &[Option<&str>]) -> Vec<usize> {
+        self.positions(arguments, false, |state| {
+            matches!(state, MatchState::Argument(true, _))
+        })
+    }
+
+    /// the indexes of the arguments consumed by the states, on any way to match all the arguments
+    fn positions(
+        &self,
+        arguments: &[Option<&str>],
+        ignore_case: bool,
+        is_wanted: impl Fn(&MatchState) -> bool,
+    ) -> Vec<usize> {
+        let mut matcher = Matcher::default();
+        let end = matcher.push(MatchState::End);
+        let start = matcher.compile_items(&self.items, end);
+
+        // the states from which the rest arguments can be matched to the end
+        let mut alive: Vec<HashSet<usize>> = vec![HashSet::new(); arguments.len() + 1];
+        alive[arguments.len()].insert(end);
+        for (index, argument) in arguments.iter().enumerate().rev() {
+            let (current, rest) = alive.split_at_mut(index + 1);
+            current[index] = (0..matcher.states.len())
+                .filter(|&state| {
+                    matcher
+                        .step(state, *argument, ignore_case)
+                        .is_some_and(|next| !matcher.closure_of(next).is_disjoint(&rest[0]))
+                })
+                .collect();
+        }
+
+        let mut positions = vec![];
+        let mut current: HashSet<usize> = matcher.closure_of(start);
+        for (index, argument) in arguments.iter().enumerate() {
+            current.retain(|state| alive[index].contains(state));
+            let mut next_states = HashSet::new();
+            for &state in &current {
+                if is_wanted(&matcher.states[state]) && !positions.contains(&index) {
+                    positions.push(index);
+                }
+                if let Some(next) = matcher.step(state, *argument, ignore_case) {
+                    next_states.extend(matcher.closure_of(next));
+                }
+            }
+            current = next_states;
+        }
+        positions
+    }
+}
+
+#[cfg(test)]
+mod signature_test {
+    use super::*;
+
+    fn keyword(word: &str) -> SignatureItem {
+        SignatureItem::Keyword(word.to_string())
+    }
+
+    fn positional(word: &str) -> SignatureItem {
+        SignatureItem::Positional(word.to_string())
+    }
+
+    #[test]
+    fn tst_parse_signature() {
+        let signature = CommandSignature::parse(
+            "target_link_libraries(<target> <PRIVATE|PUBLIC|INTERFACE> <item>... [<PRIVATE|PUBLIC|INTERFACE> <item>...]...)",
+        )
+        .unwrap();
+        let scope = SignatureItem::Choice(vec![
+            keyword("PRIVATE"),
+            keyword("PUBLIC"),
+            keyword("INTERFACE"),
+        ]);
+        let items = SignatureItem::Repeated(Box::new(positional("item")));
+        assert_eq!(
+            signature.items,
+            vec![
+                positional("target"),
+                scope.clone(),
+                items.clone(),
+                SignatureItem::Repeated(Box::new(SignatureItem::Optional(vec![scope, items]))),
+            ]
+        );
+        assert_eq!(signature.min_arguments(), 2);
+        assert!(signature.starts_with_positional());
+
+        let signature =
+            CommandSignature::parse("file({WRITE | APPEND} <filename> <content>...)").unwrap();
+        assert_eq!(signature.modes(), vec!["WRITE", "APPEND"]);
+        assert_eq!(signature.min_arguments(), 2);
+
+        let signature = CommandSignature::parse(
+            "add_custom_command(TARGET <target> PRE_BUILD | PRE_LINK | POST_BUILD COMMAND command1 [ARGS] [args1...])",
+        )
+        .unwrap();
+        assert_eq!(
+            signature.items[2],
+            SignatureItem::Choice(vec![
+                keyword("PRE_BUILD"),
+                keyword("PRE_LINK"),
+                keyword("POST_BUILD")
+            ])
+        );
+        assert_eq!(signature.min_arguments(), 5);
+        assert!(signature.keywords().contains("ARGS"));
+
+        let signature = CommandSignature::parse("set(ENV{<variable>} [<value>])").unwrap();
+        assert_eq!(
+            signature.items,
+            vec![
+                positional("ENV{<variable>}"),
+                SignatureItem::Optional(vec![positional("value")]),
+            ]
+        );
+        let signature = CommandSignature::parse(r#"message([<mode>] "message text" ...)"#).unwrap();
+        assert!(signature.keywords().contains("STATUS"));
+        assert_eq!(signature.min_arguments(), 1);
+
+        let signature = CommandSignature::parse(
+            "target_link_libraries(<target> <PRIVATE|PUBLIC|INTERFACE> <item>... [<PRIVATE|PUBLIC|INTERFACE> <item>...]...)",
+        )
+        .unwrap();
+        assert_eq!(
+            signature.keyword_positions(
+                &[
+                    Some("public"),
+                    Some("public"),
+                    Some("a"),
+                    Some("private"),
+                    None,
+                    Some("b"),
+                ],
+                true
+            ),
+            vec![1, 3]
+        );
+        assert_eq!(
+            signature.keyword_positions(&[Some("lib"), Some("PUBLIC"), Some("private")], false),
+            vec![1]
+        );
+        assert_eq!(
+            signature.keyword_positions(&[Some("foo"), None, Some("a")], true),
+            Vec::<usize>::new()
+        );
+        let signature =
+            CommandSignature::parse("set(<variable> <value>... [PARENT_SCOPE])").unwrap();
+        assert_eq!(
+            signature.keyword_positions(
+                &[Some("sources"), Some("a.cpp"), Some("parent_scope")],
+                true
+            ),
+            vec![2]
+        );
+        assert_eq!(
+            signature.variable_positions(&[Some("sources"), Some("a.cpp")]),
+            vec![0]
+        );
+        let signature = CommandSignature::parse(
+            "string(REGEX REPLACE <regular_expression> <replacement_expression> <output_variable> <input> [<input>...])",
+        )
+        .unwrap();
+        assert_eq!(
+            signature.variable_positions(&[
+                Some("REGEX"),
+                Some("REPLACE"),
+                None,
+                None,
+                Some("out"),
+                None
+            ]),
+            vec![4]
+        );
+
+        // cut off by the regex
+        assert_eq!(
+            CommandSignature::parse("list(TRANSFORM <list> (APPEND|PREPEND)"),
+            None
+        );
+    }
+}
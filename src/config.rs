@@ -1,12 +1,52 @@
+use std::collections::HashMap;
 use std::io::Read;
+use std::path::Path;
 
 use serde::Deserialize;
 use std::sync::LazyLock;
+use tower_lsp::lsp_types::DiagnosticSeverity;
+
+use crate::gammar::rules;
+
+/// the level of the lint rule, set in the `[rules]` of `.neocmakelint.toml`
+#[derive(Deserialize, PartialEq, Eq, Debug, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum RuleLevel {
+    Off,
+    Error,
+    Warning,
+    #[serde(alias = "info")]
+    Information,
+    Hint,
+}
+
+impl RuleLevel {
+    pub fn severity(self) -> Option<DiagnosticSeverity> {
+        match self {
+            RuleLevel::Off => None,
+            RuleLevel::Error => Some(DiagnosticSeverity::ERROR),
+            RuleLevel::Warning => Some(DiagnosticSeverity::WARNING),
+            RuleLevel::Information => Some(DiagnosticSeverity::INFORMATION),
+            RuleLevel::Hint => Some(DiagnosticSeverity::HINT),
+        }
+    }
+}
 
 #[derive(Deserialize, PartialEq, Eq, Debug)]
+#[serde(default)]
 pub struct CMakeLintConfig {
     pub command_upcase: String,
     pub enable_external_cmake_lint: bool,
+    /// use the max_line_length of .editorconfig, or 80 if it is not set
+    pub max_line_length: Option<usize>,
+    /// the max count of the named arguments of the function or macro
+    pub max_arguments: usize,
+    /// the regex which the whole function name should match
+    pub function_name_pattern: String,
+    /// check the modern cmake rules, such as no include_directories
+    pub enable_modern_cmake_lint: bool,
+    /// the rule id and its level
+    pub rules: HashMap<String, RuleLevel>,
 }
 
 pub struct LintSuggestion {
@@ -53,6 +93,11 @@ impl Default for CMakeLintConfig {
         Self {
             command_upcase: "ignore".to_string(),
             enable_external_cmake_lint: true,
+            max_line_length: None,
+            max_arguments: 5,
+            function_name_pattern: "[0-9a-z_]+".to_string(),
+            enable_modern_cmake_lint: false,
+            rules: HashMap::new(),
         }
     }
 }
@@ -69,22 +114,185 @@ pub static CMAKE_LINT_CONFIG: LazyLock<CMakeLintConfig> = LazyLock::new(|| {
         return CMakeLintConfig::default();
     }
 
-    if let Ok(config) = toml::from_str::<CMakeLintConfig>(&buf) {
-        return config;
-    };
-    CMakeLintConfig::default()
+    match toml::from_str::<CMakeLintConfig>(&buf) {
+        Ok(config) => {
+            for id in config.rules.keys() {
+                if rules::find_rule(id).is_none() {
+                    tracing::warn!("Unknown lint rule in .neocmakelint.toml: {id}");
+                }
+            }
+            config
+        }
+        Err(e) => {
+            tracing::warn!("Cannot parse .neocmakelint.toml: {e}");
+            CMakeLintConfig::default()
+        }
+    }
 });
 
 pub static CMAKE_LINT: LazyLock<LintSuggestion> =
     LazyLock::new(|| CMAKE_LINT_CONFIG.command_upcase.clone().into());
 
+/// the project config file, found in the workspace root
+pub const NEOCMAKE_CONFIG_FILE: &str = ".neocmakelsp.toml";
+
+#[derive(Deserialize, PartialEq, Eq, Debug, Clone, Copy, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum CaseStyle {
+    Lower,
+    Upper,
+    #[default]
+    Keep,
+}
+
+impl CaseStyle {
+    pub fn apply(&self, text: &str) -> String {
+        match self {
+            CaseStyle::Lower => text.to_lowercase(),
+            CaseStyle::Upper => text.to_uppercase(),
+            CaseStyle::Keep => text.to_string(),
+        }
+    }
+}
+
+/// the keywords are case sensitive in cmake, `public` is an argument but `PUBLIC` is a keyword,
+/// so they can only be changed to upper case, the lower case would change the meaning
+#[derive(Deserialize, PartialEq, Eq, Debug, Clone, Copy, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum KeywordCase {
+    Upper,
+    #[default]
+    Keep,
+}
+
+impl KeywordCase {
+    pub fn apply(&self, text: &str) -> String {
+        match self {
+            KeywordCase::Upper => text.to_uppercase(),
+            KeywordCase::Keep => text.to_string(),
+        }
+    }
+}
+
+/// where to put the ")" of the wrapped commands
+#[derive(Deserialize, PartialEq, Eq, Debug, Clone, Copy, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ClosingParen {
+    /// after the last argument
+    #[default]
+    SameLine,
+    /// in a new line with the indent of the command
+    NewLine,
+}
+
+#[derive(Deserialize, PartialEq, Eq, Debug, Clone, Copy, Default)]
+#[serde(default)]
+pub struct FormatStyleConfig {
+    pub command_case: CaseStyle,
+    pub keyword_case: KeywordCase,
+    /// keep it as it is if not set
+    pub space_before_paren: Option<bool>,
+    pub max_blank_lines: Option<usize>,
+    pub line_width: Option<usize>,
+    pub closing_paren: ClosingParen,
+}
+
+#[derive(Deserialize, PartialEq, Eq, Debug, Clone, Default)]
+#[serde(default)]
+pub struct NeocmakeConfig {
+    pub format: FormatStyleConfig,
+}
+
+/// read the config in the directory, use the default one if it does not exist or is invalid
+pub fn read_neocmake_config<P: AsRef<Path>>(dir: P) -> NeocmakeConfig {
+    let Ok(buf) = std::fs::read_to_string(dir.as_ref().join(NEOCMAKE_CONFIG_FILE)) else {
+        return NeocmakeConfig::default();
+    };
+    match toml::from_str::<NeocmakeConfig>(&buf) {
+        Ok(config) => config,
+        Err(e) => {
+            tracing::warn!("Cannot parse {NEOCMAKE_CONFIG_FILE}: {e}");
+            NeocmakeConfig::default()
+        }
+    }
+}
+
+/// read the config of the nearest directory which contains the config file, from the file upward
+pub fn find_neocmake_config<P: AsRef<Path>>(file_path: P) -> NeocmakeConfig {
+    let file_path = file_path.as_ref();
+    let file_path = std::path::absolute(file_path).unwrap_or(file_path.to_path_buf());
+    file_path
+        .ancestors()
+        .skip(1)
+        .find(|dir| dir.join(NEOCMAKE_CONFIG_FILE).is_file())
+        .map(read_neocmake_config)
+        .unwrap_or_default()
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::config::CMAKE_LINT_CONFIG;
+    use std::collections::HashMap;
+
+    use crate::config::{
+        CMakeLintConfig, CaseStyle, ClosingParen, FormatStyleConfig, KeywordCase, NeocmakeConfig,
+        RuleLevel, CMAKE_LINT_CONFIG,
+    };
 
     #[test]
     fn tst_lint_config() {
         assert_eq!((*CMAKE_LINT_CONFIG).command_upcase, "ignore");
         assert_eq!((*CMAKE_LINT_CONFIG).enable_external_cmake_lint, true);
     }
+
+    #[test]
+    fn tst_lint_rules_config() {
+        let config: CMakeLintConfig = toml::from_str(
+            r#"
+[rules]
+command-case = "warning"
+missing-file = "off"
+cmake-lint = "info"
+"#,
+        )
+        .unwrap();
+        assert_eq!(config.command_upcase, "ignore");
+        assert_eq!(
+            config.rules,
+            HashMap::from([
+                ("command-case".to_string(), RuleLevel::Warning),
+                ("missing-file".to_string(), RuleLevel::Off),
+                ("cmake-lint".to_string(), RuleLevel::Information),
+            ])
+        );
+    }
+
+    #[test]
+    fn tst_neocmake_config() {
+        let config: NeocmakeConfig = toml::from_str(
+            r#"
+[format]
+command_case = "lower"
+keyword_case = "upper"
+space_before_paren = false
+max_blank_lines = 1
+line_width = 100
+closing_paren = "new_line"
+"#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.format,
+            FormatStyleConfig {
+                command_case: CaseStyle::Lower,
+                keyword_case: KeywordCase::Upper,
+                space_before_paren: Some(false),
+                max_blank_lines: Some(1),
+                line_width: Some(100),
+                closing_paren: ClosingParen::NewLine,
+            }
+        );
+        let config: NeocmakeConfig = toml::from_str("").unwrap();
+        assert_eq!(config.format, FormatStyleConfig::default());
+        assert!(toml::from_str::<NeocmakeConfig>("[format]\nkeyword_case = \"lower\"").is_err());
+    }
 }
@@ -2,3 +2,6 @@ use std::sync::LazyLock;
 
 pub static TREESITTER_CMAKE_LANGUAGE: LazyLock<tree_sitter::Language> =
     LazyLock::new(tree_sitter_cmake::language);
+
+/// the commands whose first argument is the name of a target
+pub const TARGET_DEFINE_COMMANDS: &[&str] = &["add_library", "add_executable", "add_custom_target"];
@@ -0,0 +1,220 @@
+/// Opened documents, keep the text, version and the last syntax tree, so reparse can be incremental
+use std::collections::HashMap;
+use std::sync::{Arc, LazyLock};
+
+use tokio::sync::Mutex;
+use tower_lsp::lsp_types::{Position, TextDocumentContentChangeEvent, Url};
+use tree_sitter::{InputEdit, Point, Tree};
+
+use crate::consts::TREESITTER_CMAKE_LANGUAGE;
+
+pub type DocumentKV = HashMap<Url, Document>;
+
+pub static DOCUMENTS_CACHE: LazyLock<Arc<Mutex<DocumentKV>>> =
+    LazyLock::new(|| Arc::new(Mutex::new(HashMap::new())));
+
+#[derive(Debug, Clone)]
+pub struct Document {
+    pub text: String,
+    pub version: i32,
+    pub tree: Tree,
+}
+
+/// parse the source, the tree of the opened document should be got by [`get_tree`]
+pub fn parse(source: &str, old_tree: Option<&Tree>) -> Option<Tree> {
+    let mut parse = tree_sitter::Parser::new();
+    parse.set_language(&TREESITTER_CMAKE_LANGUAGE).unwrap();
+    parse.parse(source, old_tree)
+}
+
+// NOTE: the character of lsp position is counted by utf16, but tree-sitter use bytes
+pub fn position_to_byte(text: &str, position: Position) -> usize {
+    let mut offset = 0;
+    for (row, line) in text.split_inclusive('\n').enumerate() {
+        if row == position.line as usize {
+            let mut character = 0;
+            for (index, c) in line.char_indices() {
+                if character >= position.character as usize || c == '\r' || c == '\n' {
+                    return offset + index;
+                }
+                character += c.len_utf16();
+            }
+            return offset + line.len();
+        }
+        offset += line.len();
+    }
+    text.len()
+}
+
+fn byte_to_point(text: &str, offset: usize) -> Point {
+    let before = &text[..offset];
+    let row = before.matches('\n').count();
+    let column = match before.rfind('\n') {
+        Some(index) => offset - index - 1,
+        None => offset,
+    };
+    Point { row, column }
+}
+
+impl Document {
+    pub fn new(text: String, version: i32) -> Option<Self> {
+        let tree = parse(&text, None)?;
+        Some(Self {
+            text,
+            version,
+            tree,
+        })
+    }
+
+    fn apply_change(&mut self, change: TextDocumentContentChangeEvent) {
+        let Some(range) = change.range else {
+            self.text = change.text;
+            return;
+        };
+        let start_byte = position_to_byte(&self.text, range.start);
+        let old_end_byte = position_to_byte(&self.text, range.end).max(start_byte);
+        let start_position = byte_to_point(&self.text, start_byte);
+        let old_end_position = byte_to_point(&self.text, old_end_byte);
+        self.text
+            .replace_range(start_byte..old_end_byte, &change.text);
+        let new_end_byte = start_byte + change.text.len();
+        let new_end_position = byte_to_point(&self.text, new_end_byte);
+        self.tree.edit(&InputEdit {
+            start_byte,
+            old_end_byte,
+            new_end_byte,
+            start_position,
+            old_end_position,
+            new_end_position,
+        });
+    }
+
+    /// apply the changes in order, and reparse with the edited tree
+    pub fn apply_changes(&mut self, changes: Vec<TextDocumentContentChangeEvent>, version: i32) {
+        let mut full_changed = false;
+        for change in changes {
+            full_changed |= change.range.is_none();
+            self.apply_change(change);
+        }
+        let old_tree = if full_changed { None } else { Some(&self.tree) };
+        if let Some(tree) = parse(&self.text, old_tree) {
+            self.tree = tree;
+        }
+        self.version = version;
+    }
+}
+
+/// the text of the opened document
+pub async fn get_text(uri: &Url) -> Option<String> {
+    let documents = DOCUMENTS_CACHE.lock().await;
+    documents.get(uri).map(|document| document.text.clone())
+}
+
+/// the text and the tree of the opened document
+pub async fn get_document(uri: &Url) -> Option<(String, Tree)> {
+    let documents = DOCUMENTS_CACHE.lock().await;
+    documents
+        .get(uri)
+        .map(|document| (document.text.clone(), document.tree.clone()))
+}
+
+/// get the tree of the source, reuse the tree of the opened document if the text is the same
+pub async fn get_tree(uri: &Url, source: &str) -> Option<Tree> {
+    let documents = DOCUMENTS_CACHE.lock().await;
+    if let Some(document) = documents.get(uri) {
+        if document.text == source {
+            return Some(document.tree.clone());
+        }
+    }
+    drop(documents);
+    parse(source, None)
+}
+
+#[cfg(test)]
+mod document_test {
+    use super::*;
+    use tower_lsp::lsp_types::Range;
+
+    fn change(start: (u32, u32), end: (u32, u32), text: &str) -> TextDocumentContentChangeEvent {
+        TextDocumentContentChangeEvent {
+            range: Some(Range {
+                start: Position {
+                    line: start.0,
+                    character: start.1,
+                },
+                end: Position {
+                    line: end.0,
+                    character: end.1,
+                },
+            }),
+            range_length: None,
+            text: text.to_string(),
+        }
+    }
+
+    #[test]
+    fn tst_position_to_byte() {
+        let text = "set(A \"中文\")\r\nmessage(${A})\n";
+        assert_eq!(
+            position_to_byte(
+                text,
+                Position {
+                    line: 0,
+                    character: 9
+                }
+            ),
+            13
+        );
+        assert_eq!(
+            position_to_byte(
+                text,
+                Position {
+                    line: 0,
+                    character: 100
+                }
+            ),
+            15
+        );
+        assert_eq!(
+            position_to_byte(
+                text,
+                Position {
+                    line: 1,
+                    character: 8
+                }
+            ),
+            25
+        );
+        assert_eq!(
+            position_to_byte(
+                text,
+                Position {
+                    line: 3,
+                    character: 0
+                }
+            ),
+            text.len()
+        );
+        assert_eq!(byte_to_point(text, 25), Point { row: 1, column: 8 });
+    }
+
+    #[test]
+    fn tst_incremental_change() {
+        let mut document = Document::new("set(A 1)\nmessage(${A})\n".to_string(), 0).unwrap();
+        document.apply_changes(
+            vec![
+                change((0, 4), (0, 5), "ABCD"),
+                change((1, 10), (1, 11), "ABCD"),
+                change((2, 0), (2, 0), "if(ABCD)\nendif()\n"),
+            ],
+            1,
+        );
+        let text = "set(ABCD 1)\nmessage(${ABCD})\nif(ABCD)\nendif()\n";
+        assert_eq!(document.text, text);
+        assert_eq!(document.version, 1);
+        assert_eq!(
+            document.tree.root_node().to_sexp(),
+            parse(text, None).unwrap().root_node().to_sexp()
+        );
+    }
+}
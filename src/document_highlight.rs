@@ -0,0 +1,99 @@
+/// Highlight the variable, function or target under the cursor in the current file
+use std::path::Path;
+
+use tower_lsp::lsp_types::{DocumentHighlight, DocumentHighlightKind, Position};
+use tree_sitter::Node;
+
+use crate::references::{scan_tree_references, ReferenceSymbol};
+use crate::utils::treehelper::{get_point_string, get_pos_type, PositionType, ToPoint};
+
+/// get all the places where the symbol under the location appears in the current file
+pub fn get_document_highlights<P: AsRef<Path>>(
+    location: Position,
+    root: Node,
+    source: &str,
+    local_path: P,
+) -> Option<Vec<DocumentHighlight>> {
+    let point = location.to_point();
+    if get_pos_type(point, root, source) == PositionType::Comment {
+        return None;
+    }
+    let name = get_point_string(point, root, &source.lines().collect())?;
+    let info = scan_tree_references(root, source, local_path);
+    let current_unit = info
+        .units
+        .iter()
+        .find(|unit| unit.name == name && unit.contains(location))?;
+
+    let mut symbol = current_unit.kind.symbol();
+    // NOTE: a bare argument is a target only when the target is defined in this file
+    if symbol == ReferenceSymbol::Target && !info.has_target_definition(name) {
+        symbol = ReferenceSymbol::Variable;
+    }
+
+    let highlights: Vec<DocumentHighlight> = info
+        .units
+        .iter()
+        .filter(|unit| unit.is_match(name, symbol))
+        .map(|unit| DocumentHighlight {
+            range: unit.range,
+            kind: Some(if unit.kind.is_definition() {
+                DocumentHighlightKind::WRITE
+            } else {
+                DocumentHighlightKind::READ
+            }),
+        })
+        .collect();
+    if highlights.is_empty() {
+        None
+    } else {
+        Some(highlights)
+    }
+}
+
+#[cfg(test)]
+mod document_highlight_test {
+    use super::*;
+    use crate::consts::TREESITTER_CMAKE_LANGUAGE;
+
+    #[test]
+    fn tst_document_highlights() {
+        let source = r#"set(ABCD 1234)
+if(ABCD)
+  message(STATUS "${ABCD}_DIR")
+endif()
+function(my_helper)
+endfunction()
+MY_HELPER()
+"#;
+        let mut parse = tree_sitter::Parser::new();
+        parse.set_language(&TREESITTER_CMAKE_LANGUAGE).unwrap();
+        let tree = parse.parse(source, None).unwrap();
+        let highlights = |line, character| -> Vec<(u32, DocumentHighlightKind)> {
+            get_document_highlights(
+                Position { line, character },
+                tree.root_node(),
+                source,
+                "/tmp/CMakeLists.txt",
+            )
+            .unwrap()
+            .into_iter()
+            .map(|highlight| (highlight.range.start.line, highlight.kind.unwrap()))
+            .collect()
+        };
+        let abcd = vec![
+            (0, DocumentHighlightKind::WRITE),
+            (1, DocumentHighlightKind::READ),
+            (2, DocumentHighlightKind::READ),
+        ];
+        assert_eq!(highlights(1, 4), abcd);
+        assert_eq!(highlights(2, 21), abcd);
+        assert_eq!(
+            highlights(6, 2),
+            vec![
+                (4, DocumentHighlightKind::WRITE),
+                (6, DocumentHighlightKind::READ),
+            ]
+        );
+    }
+}
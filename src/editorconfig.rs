@@ -0,0 +1,475 @@
+/// Read the .editorconfig files from the directory of the file up to the root one
+use std::path::Path;
+
+use ini::{Ini, ParseOption};
+
+pub const EDITORCONFIG_FILE: &str = ".editorconfig";
+
+/// the properties for the file, None if they are not set
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct EditorConfigProperties {
+    pub use_space: Option<bool>,
+    pub indent_size: Option<u32>,
+    pub tab_width: Option<u32>,
+    pub insert_final_newline: Option<bool>,
+    pub trim_trailing_whitespace: Option<bool>,
+    pub max_line_length: Option<usize>,
+}
+
+impl EditorConfigProperties {
+    /// the count of the indent chars, it is 1 when using tab
+    pub fn indent_unit(&self) -> Option<u32> {
+        match self.use_space {
+            Some(false) => Some(1),
+            _ => self.indent_size.or(self.tab_width),
+        }
+    }
+
+    fn set(&mut self, key: &str, value: &str) {
+        let value = value.trim().to_lowercase();
+        // NOTE: unset removes the value set by the former sections, the invalid values are ignored
+        if value == "unset" {
+            self.unset(key);
+            return;
+        }
+        let to_bool = || match value.as_str() {
+            "true" => Some(true),
+            "false" => Some(false),
+            _ => None,
+        };
+        let to_size = || value.parse::<u32>().ok().filter(|size| *size > 0);
+        match key.trim().to_lowercase().as_str() {
+            "indent_style" if value == "space" => self.use_space = Some(true),
+            "indent_style" if value == "tab" => self.use_space = Some(false),
+            "indent_size" if value == "tab" => self.indent_size = None,
+            "indent_size" => self.indent_size = to_size().or(self.indent_size),
+            "tab_width" => self.tab_width = to_size().or(self.tab_width),
+            "insert_final_newline" => {
+                self.insert_final_newline = to_bool().or(self.insert_final_newline)
+            }
+            "trim_trailing_whitespace" => {
+                self.trim_trailing_whitespace = to_bool().or(self.trim_trailing_whitespace)
+            }
+            "max_line_length" if value == "off" => self.max_line_length = None,
+            "max_line_length" => self.max_line_length = value.parse().ok().or(self.max_line_length),
+            _ => {}
+        }
+    }
+
+    fn unset(&mut self, key: &str) {
+        match key.trim().to_lowercase().as_str() {
+            "indent_style" => self.use_space = None,
+            "indent_size" => self.indent_size = None,
+            "tab_width" => self.tab_width = None,
+            "insert_final_newline" => self.insert_final_newline = None,
+            "trim_trailing_whitespace" => self.trim_trailing_whitespace = None,
+            "max_line_length" => self.max_line_length = None,
+            _ => {}
+        }
+    }
+}
+
+/// the settings used by the format cli, the default values are used if they are not set
+#[derive(Debug, PartialEq, Eq)]
+pub struct EditConfigSetting {
+    pub use_space: bool,
+    pub indent_size: u32,
+    pub insert_final_newline: bool,
+    pub trim_trailing_whitespace: bool,
+    pub max_line_length: Option<usize>,
+}
+
+impl Default for EditConfigSetting {
+    fn default() -> Self {
+        Self {
+            use_space: true,
+            indent_size: 2,
+            insert_final_newline: false,
+            trim_trailing_whitespace: false,
+            max_line_length: None,
+        }
+    }
+}
+
+impl From<EditorConfigProperties> for EditConfigSetting {
+    fn from(properties: EditorConfigProperties) -> Self {
+        let default = Self::default();
+        Self {
+            use_space: properties.use_space.unwrap_or(default.use_space),
+            indent_size: properties.indent_unit().unwrap_or(default.indent_size),
+            insert_final_newline: properties
+                .insert_final_newline
+                .unwrap_or(default.insert_final_newline),
+            trim_trailing_whitespace: properties
+                .trim_trailing_whitespace
+                .unwrap_or(default.trim_trailing_whitespace),
+            max_line_length: properties.max_line_length,
+        }
+    }
+}
+
+// the content between the bracket which starts at the index, the nested ones are skipped
+fn find_closing(chars: &[char], index: usize, open: char, close: char) -> Option<usize> {
+    let mut depth = 0;
+    let mut current = index;
+    while current < chars.len() {
+        match chars[current] {
+            '\\' => current += 1,
+            c if c == open => depth += 1,
+            c if c == close => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(current);
+                }
+            }
+            _ => {}
+        }
+        current += 1;
+    }
+    None
+}
+
+// {1..3} is the numbers between them
+fn number_range(content: &str) -> Option<String> {
+    let (start, end) = content.split_once("..")?;
+    let start: i64 = start.parse().ok()?;
+    let end: i64 = end.parse().ok()?;
+    let (start, end) = (start.min(end), start.max(end));
+    // NOTE: the large range is not expanded
+    if end - start > 1000 {
+        return Some(r"-?\d+".to_string());
+    }
+    let numbers: Vec<String> = (start..=end).map(|number| number.to_string()).collect();
+    Some(format!("(?:{})", numbers.join("|")))
+}
+
+fn glob_to_regex(chars: &[char]) -> String {
+    let mut regex = String::new();
+    let mut index = 0;
+    while index < chars.len() {
+        match chars[index] {
+            '*' if chars.get(index + 1) == Some(&'*') => {
+                regex.push_str(".*");
+                index += 1;
+            }
+            '*' => regex.push_str("[^/]*"),
+            '?' => regex.push_str("[^/]"),
+            '\\' if index + 1 < chars.len() => {
+                index += 1;
+                regex.push_str(&regex::escape(&chars[index].to_string()));
+            }
+            '[' => match chars[index + 1..].iter().position(|c| *c == ']') {
+                Some(offset) if offset > 0 => {
+                    let content: String = chars[index + 1..index + 1 + offset].iter().collect();
+                    let (negative, content) = match content.strip_prefix('!') {
+                        Some(content) => (true, content.to_string()),
+                        None => (false, content),
+                    };
+                    let content = content.replace('\\', r"\\").replace('[', r"\[");
+                    if negative {
+                        regex.push_str(&format!("[^/{content}]"));
+                    } else {
+                        regex.push_str(&format!("[{content}]"));
+                    }
+                    index += offset + 1;
+                }
+                _ => regex.push_str(r"\["),
+            },
+            '{' => match find_closing(chars, index, '{', '}') {
+                Some(end) => {
+                    let content: String = chars[index + 1..end].iter().collect();
+                    if let Some(numbers) = number_range(&content) {
+                        regex.push_str(&numbers);
+                    } else {
+                        // split by the commas which are not in the nested braces
+                        let mut alternatives = vec![];
+                        let mut depth = 0;
+                        let mut start = index + 1;
+                        for current in index + 1..end {
+                            match chars[current] {
+                                '{' => depth += 1,
+                                '}' => depth -= 1,
+                                ',' if depth == 0 => {
+                                    alternatives.push(glob_to_regex(&chars[start..current]));
+                                    start = current + 1;
+                                }
+                                _ => {}
+                            }
+                        }
+                        alternatives.push(glob_to_regex(&chars[start..end]));
+                        regex.push_str(&format!("(?:{})", alternatives.join("|")));
+                    }
+                    index = end;
+                }
+                None => regex.push_str(r"\{"),
+            },
+            c => regex.push_str(&regex::escape(&c.to_string())),
+        }
+        index += 1;
+    }
+    regex
+}
+
+/// if the section matches the path relative to the directory of the .editorconfig
+fn section_matches(section: &str, relative_path: &str) -> bool {
+    let chars: Vec<char> = section.chars().collect();
+    let pattern = if section.contains('/') {
+        let chars = chars.strip_prefix(&['/']).unwrap_or(&chars);
+        format!("^{}$", glob_to_regex(chars))
+    } else {
+        // the section without "/" matches the files in all the sub directories
+        format!("^(?:.*/)?{}$", glob_to_regex(&chars))
+    };
+    regex::Regex::new(&pattern).is_ok_and(|regex| regex.is_match(relative_path))
+}
+
+fn load_editorconfig(editorconfig_path: &Path) -> Option<Ini> {
+    match Ini::load_from_file_opt(
+        editorconfig_path,
+        ParseOption {
+            enabled_quote: false,
+            enabled_escape: false,
+        },
+    ) {
+        Ok(conf) => Some(conf),
+        Err(e) => {
+            tracing::warn!("Cannot read {}: {e}", editorconfig_path.display());
+            None
+        }
+    }
+}
+
+// root = true is in the preamble
+fn is_root(conf: &Ini) -> bool {
+    conf.general_section()
+        .iter()
+        .any(|(key, value)| key.eq_ignore_ascii_case("root") && value.trim() == "true")
+}
+
+// apply the sections matching the file in order, the later ones override the former ones
+fn apply_editorconfig(
+    conf: &Ini,
+    dir: &Path,
+    file_path: &Path,
+    properties: &mut EditorConfigProperties,
+) {
+    let Ok(relative_path) = file_path.strip_prefix(dir) else {
+        return;
+    };
+    let relative_path: Vec<String> = relative_path
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy().to_string())
+        .collect();
+    let relative_path = relative_path.join("/");
+    for (section, section_properties) in conf.iter() {
+        let Some(section) = section else {
+            continue;
+        };
+        if !section_matches(section, &relative_path) {
+            continue;
+        }
+        for (key, value) in section_properties.iter() {
+            properties.set(key, value);
+        }
+    }
+}
+
+/// get the properties of the file, the nearer .editorconfig overrides the further ones
+pub fn get_properties<P: AsRef<Path>>(file_path: P) -> EditorConfigProperties {
+    let file_path = file_path.as_ref();
+    let file_path = std::path::absolute(file_path).unwrap_or(file_path.to_path_buf());
+    let mut editorconfigs: Vec<(&Path, Ini)> = vec![];
+    for dir in file_path.ancestors().skip(1) {
+        let editorconfig_path = dir.join(EDITORCONFIG_FILE);
+        if !editorconfig_path.is_file() {
+            continue;
+        }
+        let Some(conf) = load_editorconfig(&editorconfig_path) else {
+            continue;
+        };
+        let root = is_root(&conf);
+        editorconfigs.push((dir, conf));
+        if root {
+            break;
+        }
+    }
+    let mut properties = EditorConfigProperties::default();
+    for (dir, conf) in editorconfigs.iter().rev() {
+        apply_editorconfig(conf, dir, &file_path, &mut properties);
+    }
+    properties
+}
+
+/// get the settings of the file for the format cli
+pub fn editconfig_setting<P: AsRef<Path>>(file_path: P) -> EditConfigSetting {
+    get_properties(file_path).into()
+}
+
+#[cfg(test)]
+mod editorconfig_test {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn tst_section_matches() {
+        assert!(section_matches("*", "CMakeLists.txt"));
+        assert!(section_matches("*", "src/CMakeLists.txt"));
+        assert!(section_matches("*.cmake", "cmake/utils.cmake"));
+        assert!(section_matches("*.{cmake}", "utils.cmake"));
+        assert!(section_matches(
+            "{CMakeLists.txt,*.cmake}",
+            "src/CMakeLists.txt"
+        ));
+        assert!(section_matches("{CMakeLists.txt,*.cmake}", "utils.cmake"));
+        assert!(!section_matches("{CMakeLists.txt,*.cmake}", "main.lua"));
+        assert!(section_matches("/cmake/*.cmake", "cmake/utils.cmake"));
+        assert!(!section_matches("/cmake/*.cmake", "src/cmake/utils.cmake"));
+        assert!(section_matches(
+            "src/**/CMakeLists.txt",
+            "src/a/b/CMakeLists.txt"
+        ));
+        assert!(section_matches("file[0-9].cmake", "file1.cmake"));
+        assert!(!section_matches("file[!0-9].cmake", "file1.cmake"));
+        assert!(section_matches("file{1..3}.cmake", "file2.cmake"));
+        assert!(!section_matches("file{1..3}.cmake", "file4.cmake"));
+    }
+
+    #[test]
+    fn tst_editconfig_tab() {
+        let dir = tempdir().unwrap();
+        let content = r#"
+root = true
+
+[*.{cmake}]
+indent_style = tab
+indent_size = 2
+
+[CMakeLists.txt]
+indent_style = tab
+indent_size = 2
+
+[*.{lua}]
+indent_style = space
+indent_size = 4
+"#;
+        fs::write(dir.path().join(EDITORCONFIG_FILE), content).unwrap();
+
+        let setting = EditConfigSetting {
+            use_space: false,
+            indent_size: 1,
+            insert_final_newline: false,
+            trim_trailing_whitespace: false,
+            max_line_length: None,
+        };
+        assert_eq!(
+            editconfig_setting(dir.path().join("CMakeLists.txt")),
+            setting
+        );
+        assert_eq!(
+            editconfig_setting(dir.path().join("cmake/utils.cmake")),
+            setting
+        );
+    }
+
+    #[test]
+    fn tst_editconfig_space() {
+        let dir = tempdir().unwrap();
+        let content = r#"
+root = true
+
+[CMakeLists.txt]
+indent_style = space
+indent_size = 2
+
+[*.{lua}]
+indent_style = space
+indent_size = 4
+"#;
+        fs::write(dir.path().join(EDITORCONFIG_FILE), content).unwrap();
+
+        assert_eq!(
+            editconfig_setting(dir.path().join("CMakeLists.txt")),
+            EditConfigSetting {
+                use_space: true,
+                indent_size: 2,
+                insert_final_newline: false,
+                trim_trailing_whitespace: false,
+                max_line_length: None
+            }
+        )
+    }
+
+    #[test]
+    fn tst_editconfig_lastline() {
+        let dir = tempdir().unwrap();
+        let content = r#"
+root = true
+
+[CMakeLists.txt]
+indent_style = space
+indent_size = 2
+insert_final_newline = true
+max_line_length = 80
+
+[*.{lua}]
+indent_style = space
+indent_size = 4
+"#;
+        fs::write(dir.path().join(EDITORCONFIG_FILE), content).unwrap();
+
+        assert_eq!(
+            editconfig_setting(dir.path().join("CMakeLists.txt")),
+            EditConfigSetting {
+                use_space: true,
+                indent_size: 2,
+                insert_final_newline: true,
+                trim_trailing_whitespace: false,
+                max_line_length: Some(80)
+            }
+        )
+    }
+
+    #[test]
+    fn tst_editconfig_nested() {
+        let dir = tempdir().unwrap();
+        let root_content = r#"
+root = true
+
+[*]
+indent_style = space
+indent_size = 4
+trim_trailing_whitespace = true
+max_line_length = 100
+"#;
+        let sub_content = r#"
+[{CMakeLists.txt,*.cmake}]
+indent_size = 2
+max_line_length = off
+"#;
+        fs::create_dir(dir.path().join("sub")).unwrap();
+        fs::write(dir.path().join(EDITORCONFIG_FILE), root_content).unwrap();
+        fs::write(dir.path().join("sub").join(EDITORCONFIG_FILE), sub_content).unwrap();
+
+        assert_eq!(
+            editconfig_setting(dir.path().join("sub/CMakeLists.txt")),
+            EditConfigSetting {
+                use_space: true,
+                indent_size: 2,
+                insert_final_newline: false,
+                trim_trailing_whitespace: true,
+                max_line_length: None
+            }
+        );
+        assert_eq!(
+            editconfig_setting(dir.path().join("CMakeLists.txt")),
+            EditConfigSetting {
+                use_space: true,
+                indent_size: 4,
+                insert_final_newline: false,
+                trim_trailing_whitespace: true,
+                max_line_length: Some(100)
+            }
+        );
+    }
+}
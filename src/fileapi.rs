@@ -20,6 +20,28 @@ pub fn update_cache_data<P: AsRef<Path>>(cache_file: P) -> Option<Cache> {
     set_cache_data(cache)
 }
 
+/// read the cache-v2 json in the reply directory of the build directory
+pub fn update_cache_data_in_build_dir<P: AsRef<Path>>(build_dir: P) -> Option<Cache> {
+    let reply_dir = build_dir
+        .as_ref()
+        .join(".cmake")
+        .join("api")
+        .join("v1")
+        .join("reply");
+    let cache_file = std::fs::read_dir(reply_dir)
+        .ok()?
+        .flatten()
+        .map(|entry| entry.path())
+        .find(|file_path| {
+            file_path.is_file()
+                && file_path.file_name().is_some_and(|file_name| {
+                    let file_name = file_name.to_string_lossy();
+                    file_name.starts_with("cache-v2") && file_name.ends_with(".json")
+                })
+        })?;
+    update_cache_data(cache_file)
+}
+
 pub fn get_cache_data() -> Option<Cache> {
     let data = CACHE_DATA.lock().ok()?;
     data.clone()
@@ -0,0 +1,193 @@
+/// Provide folding ranges for blocks, comments and regions
+use std::sync::LazyLock;
+
+use tower_lsp::lsp_types::{FoldingRange, FoldingRangeKind};
+use tree_sitter::Node;
+
+use crate::CMakeNodeKinds;
+
+const FOLDING_BLOCKS: &[&str] = &[
+    CMakeNodeKinds::IF_CONDITION,
+    CMakeNodeKinds::FOREACH_LOOP,
+    CMakeNodeKinds::WHILE_LOOP,
+    CMakeNodeKinds::FUNCTION_DEF,
+    CMakeNodeKinds::MACRO_DEF,
+    CMakeNodeKinds::BLOCK_DEF,
+];
+
+static REGION_START_REGEX: LazyLock<regex::Regex> =
+    LazyLock::new(|| regex::Regex::new(r"^#\s*region\b").unwrap());
+
+static REGION_END_REGEX: LazyLock<regex::Regex> =
+    LazyLock::new(|| regex::Regex::new(r"^#\s*endregion\b").unwrap());
+
+fn folding_range(start_line: usize, end_line: usize, kind: FoldingRangeKind) -> FoldingRange {
+    FoldingRange {
+        start_line: start_line as u32,
+        end_line: end_line as u32,
+        kind: Some(kind),
+        ..Default::default()
+    }
+}
+
+/// the line comments which take the whole line
+struct CommentLine<'a> {
+    row: usize,
+    text: &'a str,
+}
+
+fn get_sub_folding_ranges<'a>(
+    input: Node,
+    source: &[&'a str],
+    ranges: &mut Vec<FoldingRange>,
+    comments: &mut Vec<CommentLine<'a>>,
+) {
+    let mut course = input.walk();
+    for child in input.children(&mut course) {
+        let start_row = child.start_position().row;
+        let end_row = child.end_position().row;
+        match child.kind() {
+            kind if FOLDING_BLOCKS.contains(&kind) => {
+                // NOTE: every branch such as if, elseif and else is folded till the next command
+                let mut child_course = child.walk();
+                let commands: Vec<Node> = child
+                    .children(&mut child_course)
+                    .filter(|command| command.kind() != CMakeNodeKinds::BODY)
+                    .collect();
+                for window in commands.windows(2) {
+                    let start_line = window[0].start_position().row;
+                    let next_line = window[1].start_position().row;
+                    if next_line > start_line + 1 {
+                        ranges.push(folding_range(
+                            start_line,
+                            next_line - 1,
+                            FoldingRangeKind::Region,
+                        ));
+                    }
+                }
+                get_sub_folding_ranges(child, source, ranges, comments);
+            }
+            CMakeNodeKinds::BODY => get_sub_folding_ranges(child, source, ranges, comments),
+            CMakeNodeKinds::BRACKET_COMMENT if end_row > start_row => {
+                ranges.push(folding_range(start_row, end_row, FoldingRangeKind::Comment));
+            }
+            CMakeNodeKinds::LINE_COMMENT => {
+                let Some(line) = source.get(start_row) else {
+                    continue;
+                };
+                let column = child.start_position().column;
+                if !line[..column].trim().is_empty() {
+                    continue;
+                }
+                comments.push(CommentLine {
+                    row: start_row,
+                    text: line[column..].trim_end(),
+                });
+            }
+            _ => {}
+        }
+    }
+}
+
+fn push_comment_run(run: Option<(usize, usize)>, ranges: &mut Vec<FoldingRange>) {
+    if let Some((start_row, end_row)) = run {
+        if end_row > start_row {
+            ranges.push(folding_range(start_row, end_row, FoldingRangeKind::Comment));
+        }
+    }
+}
+
+fn get_comment_folding_ranges(comments: &[CommentLine], ranges: &mut Vec<FoldingRange>) {
+    let mut regions: Vec<usize> = vec![];
+    let mut run: Option<(usize, usize)> = None;
+    for comment in comments {
+        if REGION_START_REGEX.is_match(comment.text) {
+            regions.push(comment.row);
+        } else if REGION_END_REGEX.is_match(comment.text) {
+            if let Some(start_row) = regions.pop() {
+                ranges.push(folding_range(
+                    start_row,
+                    comment.row,
+                    FoldingRangeKind::Region,
+                ));
+            }
+        } else {
+            run = match run {
+                Some((start_row, end_row)) if end_row + 1 == comment.row => {
+                    Some((start_row, comment.row))
+                }
+                _ => {
+                    push_comment_run(run, ranges);
+                    Some((comment.row, comment.row))
+                }
+            };
+            continue;
+        }
+        // region markers break the comment runs
+        push_comment_run(run.take(), ranges);
+    }
+    push_comment_run(run, ranges);
+}
+
+/// get all the folding ranges of the document
+pub fn get_folding_ranges(root: Node, source: &str) -> Option<Vec<FoldingRange>> {
+    let source: Vec<&str> = source.lines().collect();
+    let mut ranges = vec![];
+    let mut comments = vec![];
+    get_sub_folding_ranges(root, &source, &mut ranges, &mut comments);
+    get_comment_folding_ranges(&comments, &mut ranges);
+    if ranges.is_empty() {
+        return None;
+    }
+    ranges.sort_by_key(|range| (range.start_line, range.end_line));
+    Some(ranges)
+}
+
+#[cfg(test)]
+mod folding_range_test {
+    use super::*;
+    use crate::consts::TREESITTER_CMAKE_LANGUAGE;
+
+    #[test]
+    fn tst_folding_ranges() {
+        let source = r#"# region dependencies
+# the first comment
+# the second comment
+find_package(Qt6 REQUIRED)
+# endregion
+if(WIN32)
+  message(STATUS "win32")
+  message(STATUS "win32")
+elseif(APPLE)
+  message(STATUS "apple")
+endif()
+function(abcd)
+  message(STATUS "abcd") # not a comment run
+  # it is one line
+endfunction()
+#[[
+bracket comment
+]]
+"#;
+        let mut parse = tree_sitter::Parser::new();
+        parse.set_language(&TREESITTER_CMAKE_LANGUAGE).unwrap();
+        let tree = parse.parse(source, None).unwrap();
+        let ranges: Vec<(u32, u32, FoldingRangeKind)> =
+            get_folding_ranges(tree.root_node(), source)
+                .unwrap()
+                .into_iter()
+                .map(|range| (range.start_line, range.end_line, range.kind.unwrap()))
+                .collect();
+        assert_eq!(
+            ranges,
+            vec![
+                (0, 4, FoldingRangeKind::Region),
+                (1, 2, FoldingRangeKind::Comment),
+                (5, 7, FoldingRangeKind::Region),
+                (8, 9, FoldingRangeKind::Region),
+                (11, 13, FoldingRangeKind::Region),
+                (15, 17, FoldingRangeKind::Comment),
+            ]
+        );
+    }
+}
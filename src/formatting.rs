@@ -1,10 +1,65 @@
-use lsp_types::{MessageType, Position, TextEdit};
+use std::collections::HashMap;
+
+use lsp_types::{MessageType, Position, Range, TextEdit};
 use tower_lsp::lsp_types;
+use tree_sitter::{Node, Tree};
 
-use crate::{consts::TREESITTER_CMAKE_LANGUAGE, utils::treehelper::is_comment, CMakeNodeKinds};
+use crate::complete::{CommandSignature, BUILDIN_COMMAND_SIGNATURES};
+use crate::config::{ClosingParen, FormatStyleConfig, KeywordCase};
+use crate::document;
+use crate::utils::treehelper::{command_arguments, is_comment, node_text};
+use crate::CMakeNodeKinds;
 
 const CLOSURE: &[&str] = &["function_def", "macro_def", "if_condition", "foreach_loop"];
 
+/// how the content is formatted
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FormatStyle {
+    pub indent_size: u32,
+    pub use_space: bool,
+    /// remove the spaces at the end of the lines in bracket comments too
+    pub trim_trailing_whitespace: bool,
+    pub config: FormatStyleConfig,
+    /// the signatures to find the keywords in the arguments, the buildin ones are used if not set
+    pub command_signatures: Option<&'static HashMap<String, Vec<CommandSignature>>>,
+}
+
+impl FormatStyle {
+    /// the line width in the config file is used first
+    pub fn new(
+        indent_size: u32,
+        use_space: bool,
+        line_width: Option<usize>,
+        config: FormatStyleConfig,
+    ) -> Self {
+        Self {
+            indent_size,
+            use_space,
+            trim_trailing_whitespace: false,
+            config: FormatStyleConfig {
+                line_width: config.line_width.or(line_width),
+                ..config
+            },
+            command_signatures: None,
+        }
+    }
+
+    /// the signatures of the command, the name is case insensitive
+    fn signatures(&self, name: &str) -> Option<&'static Vec<CommandSignature>> {
+        self.command_signatures
+            .unwrap_or(&BUILDIN_COMMAND_SIGNATURES)
+            .get(&name.to_lowercase())
+    }
+
+    fn indent(&self, depth: u32) -> String {
+        let mut indent = String::new();
+        for _ in 0..depth {
+            indent.push_str(&get_space(self.indent_size, self.use_space));
+        }
+        indent
+    }
+}
+
 fn strip_trailing_newline(input: &str) -> &str {
     input
         .strip_suffix("\r\n")
@@ -57,65 +112,472 @@ fn get_space(spacelen: u32, use_space: bool) -> String {
     space
 }
 
+// the formatted document, the top level nodes with errors are kept as they are
+// None if the whole source cannot be parsed
+// NOTE: only the line endings are normalized, so the rows and columns in the tree of the source still match
+fn format_source(
+    source: &str,
+    tree: &Tree,
+    style: &FormatStyle,
+    insert_final_newline: bool,
+) -> Option<String> {
+    let source = strip_trailing_newline_document(source);
+    if tree.root_node().is_error() {
+        return None;
+    }
+    let (mut new_text, endline) =
+        format_content(tree.root_node(), &source.lines().collect(), style, 0, 0, 0);
+    let line_count = source.lines().count();
+    push_newlines(
+        &mut new_text,
+        line_count.max(endline) - endline,
+        style,
+        false,
+    );
+
+    if insert_final_newline && new_text.chars().last().is_some_and(|c| c != '\n') {
+        new_text.push('\n');
+    }
+    Some(new_text)
+}
+
 // use crate::utils::treehelper::point_to_position;
 pub async fn getformat(
     source: &str,
+    tree: &Tree,
     client: &tower_lsp::Client,
-    spacelen: u32,
-    use_space: bool,
+    style: &FormatStyle,
     insert_final_newline: bool,
 ) -> Option<Vec<TextEdit>> {
-    let source = strip_trailing_newline_document(source);
-    let mut parse = tree_sitter::Parser::new();
-    parse.set_language(&TREESITTER_CMAKE_LANGUAGE).unwrap();
-    let tree = parse.parse(source.as_str(), None).unwrap();
-
-    if tree.root_node().has_error() {
+    let Some(new_text) = format_source(source, tree, style, insert_final_newline) else {
         client
             .log_message(MessageType::WARNING, "Error source")
             .await;
         return None;
+    };
+    Some(get_diff_edits(source, &new_text))
+}
+
+// NOTE: the diff of the large changes costs too much, so they are replaced as one hunk
+const MAX_DIFF_CELLS: usize = 4_000_000;
+
+fn line_position(lines: &[&str], index: usize) -> Position {
+    if index < lines.len() || lines.last().is_none_or(|line| line.ends_with('\n')) {
+        return Position {
+            line: index as u32,
+            character: 0,
+        };
+    }
+    // the last line without a newline
+    Position {
+        line: index as u32 - 1,
+        character: lines[index - 1].encode_utf16().count() as u32,
+    }
+}
+
+// the pairs of the same lines in the two lists, by longest common subsequence
+fn common_lines(origin: &[&str], new: &[&str]) -> Vec<(usize, usize)> {
+    if origin.len() * new.len() > MAX_DIFF_CELLS {
+        return vec![];
+    }
+    let width = new.len() + 1;
+    let mut lengths = vec![0u32; (origin.len() + 1) * width];
+    for i in (0..origin.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lengths[i * width + j] = if origin[i] == new[j] {
+                lengths[(i + 1) * width + j + 1] + 1
+            } else {
+                lengths[(i + 1) * width + j].max(lengths[i * width + j + 1])
+            };
+        }
+    }
+    let mut pairs = vec![];
+    let (mut i, mut j) = (0, 0);
+    while i < origin.len() && j < new.len() {
+        if origin[i] == new[j] {
+            pairs.push((i, j));
+            i += 1;
+            j += 1;
+        } else if lengths[(i + 1) * width + j] >= lengths[i * width + j + 1] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    pairs
+}
+
+/// the changed lines, by (origin start, origin end, new start, new end)
+struct DiffHunk {
+    origin_start: usize,
+    origin_end: usize,
+    new_start: usize,
+    new_end: usize,
+}
+
+fn get_diff_hunks(origin_lines: &[&str], new_lines: &[&str]) -> Vec<DiffHunk> {
+    let prefix = origin_lines
+        .iter()
+        .zip(new_lines.iter())
+        .take_while(|(origin, new)| origin == new)
+        .count();
+    let suffix = origin_lines[prefix..]
+        .iter()
+        .rev()
+        .zip(new_lines[prefix..].iter().rev())
+        .take_while(|(origin, new)| origin == new)
+        .count();
+    let origin_end = origin_lines.len() - suffix;
+    let new_end = new_lines.len() - suffix;
+
+    let mut pairs: Vec<(usize, usize)> = common_lines(
+        &origin_lines[prefix..origin_end],
+        &new_lines[prefix..new_end],
+    )
+    .into_iter()
+    .map(|(i, j)| (i + prefix, j + prefix))
+    .collect();
+    pairs.push((origin_end, new_end));
+
+    let mut hunks = vec![];
+    let (mut origin_index, mut new_index) = (prefix, prefix);
+    for (origin_same, new_same) in pairs {
+        if origin_same > origin_index || new_same > new_index {
+            hunks.push(DiffHunk {
+                origin_start: origin_index,
+                origin_end: origin_same,
+                new_start: new_index,
+                new_end: new_same,
+            });
+        }
+        origin_index = origin_same + 1;
+        new_index = new_same + 1;
+    }
+    hunks
+}
+
+fn hunk_edit(origin_lines: &[&str], new_lines: &[&str], hunk: DiffHunk) -> TextEdit {
+    TextEdit {
+        range: lsp_types::Range {
+            start: line_position(origin_lines, hunk.origin_start),
+            end: line_position(origin_lines, hunk.origin_end),
+        },
+        new_text: new_lines[hunk.new_start..hunk.new_end].concat(),
+    }
+}
+
+// the formatted text uses "\n", it is changed back to "\r\n" if the origin uses it,
+// so the lines which are not changed by the formatter are still the same
+fn with_line_ending(origin: &str, new_text: &str) -> String {
+    if origin.contains("\r\n") {
+        new_text.replace('\n', "\r\n")
+    } else {
+        new_text.to_string()
+    }
+}
+
+/// only replace the changed lines
+fn get_diff_edits(origin: &str, new_text: &str) -> Vec<TextEdit> {
+    let new_text = with_line_ending(origin, new_text);
+    let origin_lines: Vec<&str> = origin.split_inclusive('\n').collect();
+    let new_lines: Vec<&str> = new_text.split_inclusive('\n').collect();
+    get_diff_hunks(&origin_lines, &new_lines)
+        .into_iter()
+        .map(|hunk| hunk_edit(&origin_lines, &new_lines, hunk))
+        .collect()
+}
+
+const DIFF_CONTEXT: usize = 3;
+
+fn push_diff_line(diff: &mut String, prefix: char, line: &str) {
+    diff.push(prefix);
+    diff.push_str(line);
+    if !line.ends_with('\n') {
+        diff.push_str("\n\\ No newline at end of file\n");
+    }
+}
+
+/// the unified diff between the origin and the formatted content, None if nothing changed
+pub fn get_unified_diff(path: &str, origin: &str, new_text: &str) -> Option<String> {
+    let origin_lines: Vec<&str> = origin.split_inclusive('\n').collect();
+    let new_lines: Vec<&str> = new_text.split_inclusive('\n').collect();
+    let hunks = get_diff_hunks(&origin_lines, &new_lines);
+    if hunks.is_empty() {
+        return None;
+    }
+    // the hunks close to each other share the context
+    let mut groups: Vec<Vec<DiffHunk>> = vec![];
+    for hunk in hunks {
+        match groups.last_mut() {
+            Some(group)
+                if group.last().is_some_and(|last| {
+                    hunk.origin_start - last.origin_end <= DIFF_CONTEXT * 2
+                }) =>
+            {
+                group.push(hunk)
+            }
+            _ => groups.push(vec![hunk]),
+        }
+    }
+
+    let mut diff = format!("--- a/{path}\n+++ b/{path}\n");
+    for group in groups {
+        let first = &group[0];
+        let last = &group[group.len() - 1];
+        let origin_start = first.origin_start.saturating_sub(DIFF_CONTEXT);
+        let new_start = first.new_start - (first.origin_start - origin_start);
+        let origin_end = (last.origin_end + DIFF_CONTEXT).min(origin_lines.len());
+        let new_end = last.new_end + (origin_end - last.origin_end);
+        // NOTE: the start is 0 when the range is empty
+        let header_start = |start: usize, end: usize| if end > start { start + 1 } else { start };
+        diff.push_str(&format!(
+            "@@ -{},{} +{},{} @@\n",
+            header_start(origin_start, origin_end),
+            origin_end - origin_start,
+            header_start(new_start, new_end),
+            new_end - new_start
+        ));
+        let mut origin_index = origin_start;
+        for hunk in group.iter() {
+            for line in &origin_lines[origin_index..hunk.origin_start] {
+                push_diff_line(&mut diff, ' ', line);
+            }
+            for line in &origin_lines[hunk.origin_start..hunk.origin_end] {
+                push_diff_line(&mut diff, '-', line);
+            }
+            for line in &new_lines[hunk.new_start..hunk.new_end] {
+                push_diff_line(&mut diff, '+', line);
+            }
+            origin_index = hunk.origin_end;
+        }
+        for line in &origin_lines[origin_index..origin_end] {
+            push_diff_line(&mut diff, ' ', line);
+        }
     }
-    let (mut new_text, endline) = format_content(
+    Some(diff)
+}
+
+/// format the selected commands, the indent is the same as formatting the whole document
+pub async fn get_range_format(
+    source: &str,
+    tree: &Tree,
+    client: &tower_lsp::Client,
+    range: Range,
+    style: &FormatStyle,
+) -> Option<Vec<TextEdit>> {
+    let edits = range_format(source, tree, range, style);
+    if edits.is_none() {
+        client
+            .log_message(MessageType::WARNING, "Error source")
+            .await;
+    }
+    edits
+}
+
+fn range_format(
+    source: &str,
+    tree: &Tree,
+    range: Range,
+    style: &FormatStyle,
+) -> Option<Vec<TextEdit>> {
+    let new_text = with_line_ending(source, &format_source(source, tree, style, false)?);
+    let (start_row, end_row) = expand_range(
         tree.root_node(),
-        &source.lines().collect(),
-        spacelen,
-        use_space,
-        0,
-        0,
-        0,
+        range.start.line as usize,
+        range.end.line as usize,
     );
-    for _ in endline..source.lines().count() {
-        new_text.push('\n');
+    let origin_lines: Vec<&str> = source.split_inclusive('\n').collect();
+    let new_lines: Vec<&str> = new_text.split_inclusive('\n').collect();
+    // NOTE: only the changes of the lines in the commands are kept
+    Some(
+        get_diff_hunks(&origin_lines, &new_lines)
+            .into_iter()
+            .filter_map(|hunk| clip_hunk(hunk, start_row, end_row + 1, &origin_lines, &new_lines))
+            .map(|hunk| hunk_edit(&origin_lines, &new_lines, hunk))
+            .collect(),
+    )
+}
+
+// the formatter only changes the whitespace and the case, so the count of the other chars is kept
+fn content_len(lines: &[&str]) -> usize {
+    lines
+        .iter()
+        .map(|line| line.chars().filter(|c| !c.is_whitespace()).count())
+        .sum()
+}
+
+// keep the part of the hunk in the rows [start_row, end_row)
+// the rows are the bounds of the commands, so the new lines are split where the content before them ends
+fn clip_hunk(
+    hunk: DiffHunk,
+    start_row: usize,
+    end_row: usize,
+    origin_lines: &[&str],
+    new_lines: &[&str],
+) -> Option<DiffHunk> {
+    if hunk.origin_start == hunk.origin_end {
+        return (start_row < hunk.origin_start && hunk.origin_start < end_row).then_some(hunk);
+    }
+    let origin_start = hunk.origin_start.max(start_row);
+    let origin_end = hunk.origin_end.min(end_row);
+    if origin_start >= origin_end {
+        return None;
     }
+    let before = content_len(&origin_lines[hunk.origin_start..origin_start]);
+    let inside = content_len(&origin_lines[origin_start..origin_end]);
+    let mut new_start = hunk.new_start;
+    if origin_start > hunk.origin_start {
+        // the blank lines between are left as they are
+        while new_start < hunk.new_end
+            && content_len(&new_lines[hunk.new_start..=new_start]) <= before
+        {
+            new_start += 1;
+        }
+    }
+    let mut new_end = hunk.new_end;
+    if origin_end < hunk.origin_end {
+        new_end = new_start;
+        while new_end < hunk.new_end
+            && content_len(&new_lines[hunk.new_start..new_end]) < before + inside
+        {
+            new_end += 1;
+        }
+    }
+    Some(DiffHunk {
+        origin_start,
+        origin_end,
+        new_start,
+        new_end,
+    })
+}
 
-    if insert_final_newline && new_text.chars().last().is_some_and(|c| c != '\n') {
-        new_text.push('\n');
+// expand the range to the whole commands in it
+fn expand_range(input: Node, start_row: usize, end_row: usize) -> (usize, usize) {
+    let (mut start_row, mut end_row) = (start_row, end_row);
+    let mut course = input.walk();
+    for child in input.children(&mut course) {
+        let child_start = child.start_position().row;
+        let child_end = child.end_position().row;
+        if child_end < start_row || child_start > end_row {
+            continue;
+        }
+        if CLOSURE.contains(&child.kind()) || child.kind() == CMakeNodeKinds::BODY {
+            (start_row, end_row) = expand_range(child, start_row, end_row);
+            continue;
+        }
+        start_row = start_row.min(child_start);
+        end_row = end_row.max(child_end);
     }
+    (start_row, end_row)
+}
 
-    let len_count = new_text.lines().count();
-    let len_origin = source.lines().count();
-    let len = std::cmp::max(len_count, len_origin);
+// the count of the bodies the row is in
+fn get_indent_depth(input: Node, row: usize) -> u32 {
+    let mut course = input.walk();
+    for child in input.children(&mut course) {
+        if child.start_position().row > row || child.end_position().row < row {
+            continue;
+        }
+        if child.kind() == CMakeNodeKinds::BODY {
+            return get_indent_depth(child, row);
+        }
+        if !CLOSURE.contains(&child.kind()) {
+            continue;
+        }
+        let mut child_course = child.walk();
+        let commands: Vec<Node> = child
+            .children(&mut child_course)
+            .filter(|command| command.kind() != CMakeNodeKinds::BODY)
+            .collect();
+        let in_body = commands.windows(2).any(|window| {
+            window[0].end_position().row < row && window[1].start_position().row > row
+        });
+        if in_body {
+            return 1 + get_indent_depth(child, row);
+        }
+        return 0;
+    }
+    0
+}
+
+// the command which is not a block, like message() or endif()
+fn get_statement(input: Node, row: usize, column: usize) -> Option<Node> {
+    let point = tree_sitter::Point { row, column };
+    let mut node = input.descendant_for_point_range(point, point)?;
+    loop {
+        if node.kind() == CMakeNodeKinds::BODY || CLOSURE.contains(&node.kind()) {
+            return None;
+        }
+        let parent = node.parent()?;
+        if parent == input
+            || parent.kind() == CMakeNodeKinds::BODY
+            || CLOSURE.contains(&parent.kind())
+        {
+            return Some(node);
+        }
+        node = parent;
+    }
+}
+
+/// reindent the command after typing ")", or the new line after typing a newline
+pub fn get_on_type_format(
+    source: &str,
+    tree: &tree_sitter::Tree,
+    location: Position,
+    ch: &str,
+    spacelen: u32,
+    use_space: bool,
+) -> Option<Vec<TextEdit>> {
+    let root = tree.root_node();
+    let row = match ch {
+        ")" => {
+            let column = (location.character as usize).checked_sub(1)?;
+            let statement = get_statement(root, location.line as usize, column)?;
+            if statement.kind() == CMakeNodeKinds::LINE_COMMENT
+                || statement.kind() == CMakeNodeKinds::BRACKET_COMMENT
+            {
+                return None;
+            }
+            statement.start_position().row
+        }
+        "\n" => {
+            let row = location.line as usize;
+            // the new line is inside the arguments of a command
+            if get_statement(root, row, location.character as usize)
+                .is_some_and(|statement| statement.start_position().row < row)
+            {
+                return None;
+            }
+            row
+        }
+        _ => return None,
+    };
+    let line = source.lines().nth(row).unwrap_or("");
+    let old_indent = line.len() - line.trim_start().len();
+    let new_indent = FormatStyle::new(spacelen, use_space, None, FormatStyleConfig::default())
+        .indent(get_indent_depth(root, row));
+    if line[..old_indent] == new_indent {
+        return None;
+    }
     Some(vec![TextEdit {
-        range: lsp_types::Range {
+        range: Range {
             start: Position {
-                line: 0,
+                line: row as u32,
                 character: 0,
             },
             end: Position {
-                line: len as u32,
-                character: 0,
+                line: row as u32,
+                character: old_indent as u32,
             },
         },
-        new_text,
+        new_text: new_indent,
     }])
 }
 
 fn format_content(
     input: tree_sitter::Node,
     newsource: &Vec<&str>,
-    spacelen: u32,
-    use_space: bool,
+    style: &FormatStyle,
     appendtab: u32,
     endline: usize,
     lastendline: usize,
@@ -141,50 +603,64 @@ fn format_content(
             continue;
         }
 
+        let at_start = input.parent().is_none() && new_text.is_empty();
         if child.kind() == CMakeNodeKinds::BRACKET_COMMENT {
-            for _ in endline..start_row {
-                new_text.push('\n');
-            }
+            push_newlines(&mut new_text, start_row - endline, style, at_start);
             endline = end_position.row;
             lastendline = end_position.row;
             for comment in newsource.iter().take(endline + 1).skip(start_row) {
-                new_text.push_str(comment);
+                if style.trim_trailing_whitespace {
+                    new_text.push_str(comment.trim_end());
+                } else {
+                    new_text.push_str(comment);
+                }
                 new_text.push('\n');
             }
             new_text.pop();
             continue;
         }
 
-        for _ in endline..start_row {
-            new_text.push('\n');
+        if input.parent().is_none() && child.has_error() {
+            // NOTE: the node ends at the start of the next line if it takes the newline
+            let end_row = if end_position.column == 0 && end_row > start_row {
+                end_row - 1
+            } else {
+                end_row
+            };
+            // the line is written already if the node starts after another node
+            let first_row = if start_row == endline && !new_text.is_empty() {
+                start_row + 1
+            } else {
+                start_row
+            };
+            if first_row <= end_row {
+                push_newlines(&mut new_text, first_row - endline, style, at_start);
+                for line in newsource.iter().take(end_row + 1).skip(first_row) {
+                    new_text.push_str(line);
+                    new_text.push('\n');
+                }
+                new_text.pop();
+            }
+            endline = end_row.max(endline);
+            lastendline = endline;
+            isfirstunit = false;
+            continue;
         }
 
+        push_newlines(&mut new_text, start_row - endline, style, at_start);
+
         endline = start_position.row;
         if CLOSURE.contains(&child.kind()) {
-            let (text, newend) = format_content(
-                child,
-                newsource,
-                spacelen,
-                use_space,
-                appendtab,
-                endline,
-                lastendline,
-            );
+            let (text, newend) =
+                format_content(child, newsource, style, appendtab, endline, lastendline);
             endline = newend;
             lastendline = newend;
             new_text.push_str(&text);
             continue;
         }
         if child.kind() == CMakeNodeKinds::BODY {
-            let (text, newend) = format_content(
-                child,
-                newsource,
-                spacelen,
-                use_space,
-                appendtab + 1,
-                endline,
-                lastendline,
-            );
+            let (text, newend) =
+                format_content(child, newsource, style, appendtab + 1, endline, lastendline);
             new_text.push_str(&text);
             endline = newend;
             continue;
@@ -193,6 +669,15 @@ fn format_content(
         endline = end_position.row;
         lastendline = end_position.row;
 
+        let token_edits = get_token_edits(child, newsource, style);
+        if let Some(text) = style.config.line_width.and_then(|line_width| {
+            wrap_command(child, newsource, style, &token_edits, appendtab, line_width)
+        }) {
+            new_text.push_str(&text);
+            isfirstunit = false;
+            continue;
+        }
+
         for (index, currentline) in newsource
             .iter()
             .take(end_row + 1)
@@ -200,17 +685,15 @@ fn format_content(
             .enumerate()
         {
             let currentline = pre_format(currentline, start_row + index, input);
+            // NOTE: the tokens are before the comment, so the columns are not moved by pre_format
+            let currentline = apply_token_edits(&currentline, start_row + index, &token_edits);
             let currentline = currentline.trim_end();
             let trimapter = currentline.trim_start();
             let spacesize = currentline.len() - trimapter.len();
             let mut newline = if index != 0 {
-                get_space(spacesize as u32, use_space)
+                get_space(spacesize as u32, style.use_space)
             } else {
-                let mut firstline = String::new();
-                for _ in 0..appendtab {
-                    firstline.push_str(&get_space(spacelen, use_space));
-                }
-                firstline
+                style.indent(appendtab)
             };
 
             let startsource = currentline
@@ -232,37 +715,258 @@ fn format_content(
     (new_text, endline)
 }
 
-pub fn get_format_cli(
-    source: &str,
-    indent_size: u32,
-    use_space: bool,
-    insert_final_newline: bool,
+// the newlines before the node, the blank lines are limited by the style
+fn push_newlines(new_text: &mut String, count: usize, style: &FormatStyle, at_start: bool) {
+    let count = match style.config.max_blank_lines {
+        // there is no line before the first node
+        Some(max_blank_lines) if at_start => count.min(max_blank_lines),
+        Some(max_blank_lines) => count.min(max_blank_lines + 1),
+        None => count,
+    };
+    for _ in 0..count {
+        new_text.push('\n');
+    }
+}
+
+/// the replacement of the text in one line
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct TokenEdit {
+    row: usize,
+    start: usize,
+    end: usize,
+    new_text: String,
+}
+
+// the changes of the command name, the space before "(" and the keywords
+fn get_token_edits(statement: Node, source: &[&str], style: &FormatStyle) -> Vec<TokenEdit> {
+    let mut edits = vec![];
+    if !statement.kind().ends_with("_command") {
+        return edits;
+    }
+    let Some(name) = statement.child(0) else {
+        return edits;
+    };
+    let mut push_edit =
+        |node_start: tree_sitter::Point, end: usize, old: &str, new_text: String| {
+            if old != new_text {
+                edits.push(TokenEdit {
+                    row: node_start.row,
+                    start: node_start.column,
+                    end,
+                    new_text,
+                });
+            }
+        };
+    if let Some(text) = node_text(source, &name) {
+        push_edit(
+            name.start_position(),
+            name.end_position().column,
+            text,
+            style.config.command_case.apply(text),
+        );
+    }
+    if let (Some(space_before_paren), Some(paren)) =
+        (style.config.space_before_paren, statement.child(1))
+    {
+        let gap_start = name.end_position();
+        let gap_end = paren.start_position();
+        if paren.kind() == "(" && gap_start.row == gap_end.row {
+            let gap = &source[gap_start.row][gap_start.column..gap_end.column];
+            let new_gap = if space_before_paren { " " } else { "" };
+            push_edit(gap_start, gap_end.column, gap, new_gap.to_string());
+        }
+    }
+    if style.config.keyword_case == KeywordCase::Keep {
+        return edits;
+    }
+    let Some(signatures) = node_text(source, &name).and_then(|name| style.signatures(name)) else {
+        return edits;
+    };
+    // NOTE: the quoted arguments and the ones with variables are never keywords
+    let arguments: Vec<(Option<Node>, Option<&str>)> = command_arguments(statement)
+        .into_iter()
+        .map(|argument| {
+            let unquoted = argument.child(0).filter(|node| {
+                node.kind() == CMakeNodeKinds::UNQUOTED_ARGUMENT && node.child_count() == 0
+            });
+            let text = unquoted.and_then(|unquoted| node_text(source, &unquoted));
+            (unquoted, text)
+        })
+        .collect();
+    let texts: Vec<Option<&str>> = arguments.iter().map(|(_, text)| *text).collect();
+    let mut positions: Vec<usize> = signatures
+        .iter()
+        .flat_map(|signature| signature.keyword_positions(&texts, true))
+        .collect();
+    positions.sort_unstable();
+    positions.dedup();
+    // NOTE: the first argument is never changed, it is the name given by the user in most commands
+    for index in positions.into_iter().filter(|index| *index > 0) {
+        let (Some(unquoted), Some(text)) = arguments[index] else {
+            continue;
+        };
+        push_edit(
+            unquoted.start_position(),
+            unquoted.end_position().column,
+            text,
+            style.config.keyword_case.apply(text),
+        );
+    }
+    edits
+}
+
+fn apply_token_edits(line: &str, row: usize, edits: &[TokenEdit]) -> String {
+    let mut line = line.to_string();
+    // from right to left, so the columns of the left ones are not changed
+    for edit in edits.iter().rev().filter(|edit| edit.row == row) {
+        line.replace_range(edit.start..edit.end, &edit.new_text);
+    }
+    line
+}
+
+// the text of the node after the edits
+fn edited_text(source: &[&str], node: &Node, edits: &[TokenEdit]) -> Option<String> {
+    let start = node.start_position();
+    let end = node.end_position();
+    if let Some(edit) = edits
+        .iter()
+        .find(|edit| edit.row == start.row && edit.start == start.column && edit.end == end.column)
+    {
+        return Some(edit.new_text.clone());
+    }
+    node_text(source, node).map(|text| text.to_string())
+}
+
+/// wrap the command which is longer than line width, None if it should be kept as it is
+fn wrap_command(
+    command: Node,
+    source: &[&str],
+    style: &FormatStyle,
+    edits: &[TokenEdit],
+    appendtab: u32,
+    line_width: usize,
 ) -> Option<String> {
-    let source = strip_trailing_newline_document(source);
-    let mut parse = tree_sitter::Parser::new();
-    parse.set_language(&TREESITTER_CMAKE_LANGUAGE).unwrap();
-    let tree = parse.parse(&source, None).unwrap();
-    let input = tree.root_node();
-    if input.has_error() {
+    if command.kind() != CMakeNodeKinds::NORMAL_COMMAND {
         return None;
     }
-    let (mut new_text, endline) = format_content(
-        tree.root_node(),
-        &source.lines().collect(),
-        indent_size,
-        use_space,
-        0,
-        0,
-        0,
-    );
-    for _ in endline..source.lines().count() {
-        new_text.push('\n');
+    let start = command.start_position();
+    let end = command.end_position();
+    // NOTE: the comment after the command is in the same line, it should be kept
+    if !source.get(start.row)?[..start.column].trim().is_empty()
+        || !source.get(end.row)?[end.column..].trim().is_empty()
+    {
+        return None;
+    }
+    let indent = style.indent(appendtab);
+    let width = |line: &str| line.chars().count();
+    let fits = (start.row..=end.row).all(|row| {
+        let line = apply_token_edits(source[row], row, edits);
+        let indent_width = if row == start.row {
+            width(&indent)
+        } else {
+            width(&line) - width(line.trim_start())
+        };
+        indent_width + width(line.trim()) <= line_width
+    });
+    if fits {
+        return None;
     }
 
-    if insert_final_newline && new_text.chars().last().is_some_and(|c| c != '\n') {
-        new_text.push('\n');
+    let name_node = command.child(0)?;
+    let name = edited_text(source, &name_node, edits)?;
+    let paren = command.child(1)?;
+    let gap = if name_node.end_position().row == paren.start_position().row {
+        edits
+            .iter()
+            .find(|edit| {
+                edit.row == paren.start_position().row
+                    && edit.start == name_node.end_position().column
+            })
+            .map(|edit| edit.new_text.as_str())
+            .unwrap_or(
+                &source[paren.start_position().row]
+                    [name_node.end_position().column..paren.start_position().column],
+            )
+    } else {
+        ""
+    };
+    let argument_list = command
+        .child(2)
+        .filter(|node| node.kind() == CMakeNodeKinds::ARGUMENT_LIST)?;
+    let mut course = argument_list.walk();
+    let mut arguments = vec![];
+    // NOTE: the quoted arguments and the ones with variables are never keywords
+    let mut unquoted = vec![];
+    for argument in argument_list.children(&mut course) {
+        // comments and the arguments in multi lines cannot be moved
+        if argument.kind() != CMakeNodeKinds::ARGUMENT {
+            return None;
+        }
+        arguments.push(edited_text(source, &argument, edits)?);
+        unquoted.push(argument.child(0).is_some_and(|node| {
+            node.kind() == CMakeNodeKinds::UNQUOTED_ARGUMENT && node.child_count() == 0
+        }));
     }
-    Some(new_text)
+
+    // horizontal
+    let horizontal = format!("{indent}{name}{gap}({})", arguments.join(" "));
+    if width(&horizontal) <= line_width {
+        return Some(horizontal);
+    }
+
+    // one keyword group per line
+    let argument_indent = style.indent(appendtab + 1);
+    let texts: Vec<Option<&str>> = arguments
+        .iter()
+        .zip(&unquoted)
+        .map(|(argument, unquoted)| unquoted.then_some(argument.as_str()))
+        .collect();
+    let keyword_positions: Vec<usize> = style
+        .signatures(&name)
+        .into_iter()
+        .flatten()
+        .flat_map(|signature| signature.keyword_positions(&texts, false))
+        .collect();
+    let mut groups: Vec<Vec<&str>> = vec![];
+    for (index, argument) in arguments.iter().enumerate() {
+        match groups.last_mut() {
+            Some(group) if !keyword_positions.contains(&index) => group.push(argument),
+            _ => groups.push(vec![argument]),
+        }
+    }
+    let closing_paren = match style.config.closing_paren {
+        ClosingParen::SameLine => ")".to_string(),
+        ClosingParen::NewLine => format!("\n{indent})"),
+    };
+    let wrap =
+        |lines: Vec<String>| format!("{indent}{name}{gap}(\n{}{closing_paren}", lines.join("\n"));
+    let grouped = wrap(
+        groups
+            .iter()
+            .map(|group| format!("{argument_indent}{}", group.join(" ")))
+            .collect(),
+    );
+    if grouped.lines().all(|line| width(line) <= line_width) {
+        return Some(grouped);
+    }
+
+    // one argument per line
+    Some(wrap(
+        arguments
+            .iter()
+            .map(|argument| format!("{argument_indent}{argument}"))
+            .collect(),
+    ))
+}
+
+/// format the content for the cli
+pub fn get_format_cli(
+    source: &str,
+    style: &FormatStyle,
+    insert_final_newline: bool,
+) -> Option<String> {
+    let tree = document::parse(source, None)?;
+    format_source(source, &tree, style, insert_final_newline)
 }
 
 #[test]
@@ -281,8 +985,9 @@ fn strip_newline_works() {
 fn tst_format_function() {
     let source = include_str!("../assert/function/formatbefore.cmake");
     let sourceafter = include_str!("../assert/function/formatafter.cmake");
-    let formatstr = get_format_cli(source, 1, false, false).unwrap();
-    let formatstr_with_lastline = get_format_cli(source, 1, false, true).unwrap();
+    let style = FormatStyle::new(1, false, None, FormatStyleConfig::default());
+    let formatstr = get_format_cli(source, &style, false).unwrap();
+    let formatstr_with_lastline = get_format_cli(source, &style, true).unwrap();
     assert_eq!(formatstr.as_str(), sourceafter);
     assert_eq!(formatstr_with_lastline.as_str(), sourceafter);
 }
@@ -292,8 +997,9 @@ fn tst_format_function() {
 fn tst_format_base() {
     let source = include_str!("../assert/base/formatbefore.cmake");
     let sourceafter = include_str!("../assert/base/formatafter.cmake");
-    let formatstr = get_format_cli(source, 1, false, false).unwrap();
-    let formatstr_with_lastline = get_format_cli(source, 1, false, true).unwrap();
+    let style = FormatStyle::new(1, false, None, FormatStyleConfig::default());
+    let formatstr = get_format_cli(source, &style, false).unwrap();
+    let formatstr_with_lastline = get_format_cli(source, &style, true).unwrap();
     assert_eq!(formatstr.as_str(), sourceafter);
     assert_eq!(formatstr_with_lastline.as_str(), sourceafter);
 }
@@ -303,8 +1009,327 @@ fn tst_format_base() {
 fn tst_format_lastline() {
     let source = include_str!("../assert/lastline/before.cmake");
     let sourceafter = include_str!("../assert/lastline/after.cmake");
-    let formatstr = get_format_cli(source, 4, true, false).unwrap();
-    let formatstr_with_lastline = get_format_cli(source, 4, true, true).unwrap();
+    let style = FormatStyle::new(4, true, None, FormatStyleConfig::default());
+    let formatstr = get_format_cli(source, &style, false).unwrap();
+    let formatstr_with_lastline = get_format_cli(source, &style, true).unwrap();
     assert_eq!(formatstr.as_str(), sourceafter);
     assert_eq!(formatstr_with_lastline.as_str(), sourceafter);
 }
+
+#[test]
+fn tst_range_format() {
+    let source = "if(A)\nmessage(STATUS\n    \"a\")\n  set(B   C)\nendif()\n";
+    let range = Range {
+        start: Position {
+            line: 2,
+            character: 0,
+        },
+        end: Position {
+            line: 2,
+            character: 3,
+        },
+    };
+    let tree = document::parse(source, None).unwrap();
+    assert_eq!(
+        range_format(
+            source,
+            &tree,
+            range,
+            &FormatStyle::new(2, true, None, FormatStyleConfig::default())
+        )
+        .unwrap(),
+        vec![TextEdit {
+            range: Range {
+                start: Position {
+                    line: 1,
+                    character: 0,
+                },
+                end: Position {
+                    line: 2,
+                    character: 0,
+                },
+            },
+            new_text: "  message(STATUS\n".to_string(),
+        }]
+    );
+}
+
+#[test]
+fn tst_range_format_clip() {
+    // the changes of message() and set() are in one hunk, only message() is selected
+    let source = "if(A)\nmessage(STATUS)\nset(B   C)\nendif()\n";
+    let range = Range {
+        start: Position {
+            line: 1,
+            character: 0,
+        },
+        end: Position {
+            line: 1,
+            character: 3,
+        },
+    };
+    let tree = document::parse(source, None).unwrap();
+    let style = FormatStyle::new(2, true, None, FormatStyleConfig::default());
+    assert_eq!(
+        range_format(source, &tree, range, &style).unwrap(),
+        vec![TextEdit {
+            range: Range {
+                start: Position {
+                    line: 1,
+                    character: 0,
+                },
+                end: Position {
+                    line: 2,
+                    character: 0,
+                },
+            },
+            new_text: "  message(STATUS)\n".to_string(),
+        }]
+    );
+    // only set() is selected
+    let range = Range {
+        start: Position {
+            line: 2,
+            character: 0,
+        },
+        end: Position {
+            line: 2,
+            character: 0,
+        },
+    };
+    assert_eq!(
+        range_format(source, &tree, range, &style).unwrap(),
+        vec![TextEdit {
+            range: Range {
+                start: Position {
+                    line: 2,
+                    character: 0,
+                },
+                end: Position {
+                    line: 3,
+                    character: 0,
+                },
+            },
+            new_text: "  set(B C)\n".to_string(),
+        }]
+    );
+}
+
+#[test]
+fn tst_on_type_format() {
+    let source = "function(abc)\n  if(A)\n\n    endif()\nendfunction()\n";
+    let tree = document::parse(source, None).unwrap();
+    let edit = |line, character, ch| {
+        get_on_type_format(source, &tree, Position { line, character }, ch, 2, true)
+            .map(|edits| edits[0].new_text.clone())
+    };
+    assert_eq!(edit(2, 0, "\n"), Some("    ".to_string()));
+    assert_eq!(edit(3, 11, ")"), Some("  ".to_string()));
+    assert_eq!(edit(1, 7, ")"), None);
+}
+
+#[test]
+fn tst_diff_edits() {
+    let edit = |start_line, start_character, end_line, end_character, new_text: &str| TextEdit {
+        range: Range {
+            start: Position {
+                line: start_line,
+                character: start_character,
+            },
+            end: Position {
+                line: end_line,
+                character: end_character,
+            },
+        },
+        new_text: new_text.to_string(),
+    };
+    assert_eq!(
+        get_diff_edits("a\nb\nc\nd\n", "a\nB\nc\nd\ne\n"),
+        vec![edit(1, 0, 2, 0, "B\n"), edit(4, 0, 4, 0, "e\n")]
+    );
+    assert_eq!(
+        get_diff_edits("a\nb\nc", "a\nc\n"),
+        vec![edit(1, 0, 2, 1, "c\n")]
+    );
+    assert!(get_diff_edits("a\nb\n", "a\nb\n").is_empty());
+    assert_eq!(
+        get_diff_edits("a\r\nb\r\nc\r\n", "a\nB\nc\n"),
+        vec![edit(1, 0, 2, 0, "B\r\n")]
+    );
+}
+
+#[test]
+fn tst_format_wrap() {
+    let source = r#"target_link_libraries(my_target PUBLIC Qt6::Core Qt6::Gui PRIVATE my_helper)
+target_link_libraries(lib PUBLIC sources private version INTERFACE a)
+if(WIN32)
+  set(SOURCES main.cpp window.cpp dialog.cpp settings.cpp widget.cpp view.cpp)
+  message(STATUS
+    "short")
+endif()
+"#;
+    let sourceafter = r#"target_link_libraries(
+  my_target
+  PUBLIC Qt6::Core Qt6::Gui
+  PRIVATE my_helper)
+target_link_libraries(
+  lib
+  PUBLIC sources private version
+  INTERFACE a)
+if(WIN32)
+  set(
+    SOURCES
+    main.cpp
+    window.cpp
+    dialog.cpp
+    settings.cpp
+    widget.cpp
+    view.cpp)
+  message(STATUS
+    "short")
+endif()
+"#;
+    let style = |line_width| FormatStyle {
+        command_signatures: Some(&TEST_SIGNATURES),
+        ..FormatStyle::new(2, true, Some(line_width), FormatStyleConfig::default())
+    };
+    assert_eq!(
+        get_format_cli(source, &style(40), false).unwrap(),
+        sourceafter
+    );
+    assert_eq!(get_format_cli(source, &style(80), false).unwrap(), source);
+}
+
+#[cfg(test)]
+static TEST_SIGNATURES: std::sync::LazyLock<HashMap<String, Vec<CommandSignature>>> =
+    std::sync::LazyLock::new(|| {
+        let mut signatures: HashMap<String, Vec<CommandSignature>> = HashMap::new();
+        for signature in [
+            "add_library(<name> [<type>] [EXCLUDE_FROM_ALL] <sources>...)",
+            "set(<variable> <value>... [PARENT_SCOPE])",
+            "target_link_libraries(<target> <item>...)",
+            "target_link_libraries(<target> <PRIVATE|PUBLIC|INTERFACE> <item>... [<PRIVATE|PUBLIC|INTERFACE> <item>...]...)",
+        ] {
+            let name = signature[..signature.find('(').unwrap()].to_string();
+            signatures
+                .entry(name)
+                .or_default()
+                .push(CommandSignature::parse(signature).unwrap());
+        }
+        signatures
+    });
+
+#[test]
+fn tst_format_style() {
+    let source = r#"ADD_LIBRARY (my_lib STATIC main.cpp)
+
+
+
+target_link_libraries(my_lib public Qt6::Core Qt6::Gui private my_helper)
+"#;
+    let sourceafter = r#"add_library(my_lib STATIC main.cpp)
+
+target_link_libraries(
+  my_lib
+  PUBLIC Qt6::Core Qt6::Gui
+  PRIVATE my_helper
+)
+"#;
+    let style = FormatStyle::new(
+        2,
+        true,
+        Some(80),
+        FormatStyleConfig {
+            command_case: crate::config::CaseStyle::Lower,
+            keyword_case: KeywordCase::Upper,
+            space_before_paren: Some(false),
+            max_blank_lines: Some(1),
+            line_width: Some(40),
+            closing_paren: ClosingParen::NewLine,
+        },
+    );
+    let style = FormatStyle {
+        command_signatures: Some(&TEST_SIGNATURES),
+        ..style
+    };
+    assert_eq!(get_format_cli(source, &style, false).unwrap(), sourceafter);
+}
+
+#[test]
+fn tst_format_keyword_case() {
+    let style = FormatStyle {
+        command_signatures: Some(&TEST_SIGNATURES),
+        ..FormatStyle::new(
+            2,
+            true,
+            None,
+            FormatStyleConfig {
+                keyword_case: KeywordCase::Upper,
+                ..FormatStyleConfig::default()
+            },
+        )
+    };
+    // only the keywords in the signatures are changed, not the names or the values
+    let source = r#"set(sources a.cpp private)
+add_library(output exclude_from_all ${sources})
+target_link_libraries(output public "a b" private sources)
+target_link_libraries(public private sources)
+"#;
+    let sourceafter = r#"set(sources a.cpp private)
+add_library(output EXCLUDE_FROM_ALL ${sources})
+target_link_libraries(output PUBLIC "a b" PRIVATE sources)
+target_link_libraries(public PRIVATE sources)
+"#;
+    assert_eq!(get_format_cli(source, &style, false).unwrap(), sourceafter);
+}
+
+#[test]
+fn tst_unified_diff() {
+    let origin = "a\nb\nc\nd\ne\nf\ng\nh\ni\nj\n";
+    let new_text = "a\nB\nc\nd\ne\nf\ng\nh\ni\nj\nk";
+    assert_eq!(
+        get_unified_diff("CMakeLists.txt", origin, new_text).unwrap(),
+        r#"--- a/CMakeLists.txt
++++ b/CMakeLists.txt
+@@ -1,5 +1,5 @@
+ a
+-b
++B
+ c
+ d
+ e
+@@ -8,3 +8,4 @@
+ h
+ i
+ j
++k
+\ No newline at end of file
+"#
+    );
+    assert!(get_unified_diff("CMakeLists.txt", origin, origin).is_none());
+}
+
+#[test]
+fn tst_format_error() {
+    let style = FormatStyle::new(2, true, None, FormatStyleConfig::default());
+    let source = "  set(A 1)\n    message(STATUS \"a\")\n\nif(\n";
+    assert_eq!(
+        get_format_cli(source, &style, false).unwrap(),
+        "set(A 1)\nmessage(STATUS \"a\")\n\nif(\n"
+    );
+}
+
+#[test]
+fn tst_format_trim_trailing_whitespace() {
+    let mut style = FormatStyle::new(2, true, None, FormatStyleConfig::default());
+    let source = "#[[ \nbracket comment  \n]]\nmessage(STATUS \"a\")  \n";
+    assert_eq!(
+        get_format_cli(source, &style, false).unwrap(),
+        "#[[ \nbracket comment  \n]]\nmessage(STATUS \"a\")\n"
+    );
+    style.trim_trailing_whitespace = true;
+    assert_eq!(
+        get_format_cli(source, &style, false).unwrap(),
+        "#[[\nbracket comment\n]]\nmessage(STATUS \"a\")\n"
+    );
+}
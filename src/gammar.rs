@@ -1,17 +1,31 @@
+mod arguments;
+pub mod condition;
+mod genex;
+mod modern;
+pub mod rules;
+mod style;
+
 use std::ops::Deref;
 use std::path::Path;
 use std::process::Command;
 use tower_lsp::lsp_types::DiagnosticSeverity;
 use tree_sitter::Point;
 
+use crate::code_action::QuickFix;
+use crate::complete::BUILDIN_COMMAND_SIGNATURES;
 use crate::config::{self, CMAKE_LINT_CONFIG};
 use crate::consts::TREESITTER_CMAKE_LANGUAGE;
+use crate::editorconfig;
 
+use crate::utils::treehelper::ToPosition;
 use crate::utils::{include_is_module, remove_quotation_and_replace_placeholders};
 use crate::CMakeNodeKinds;
 
 const INCLUDE_CHECK_KEYWORDS: &[&str; 2] = &["include", "add_subdirectory"];
 
+/// the same as cmake-lint
+const DEFAULT_MAX_LINE_LENGTH: usize = 80;
+
 pub(crate) struct LintConfigInfo {
     pub use_lint: bool,
     pub use_extra_cmake_lint: bool,
@@ -23,6 +37,9 @@ pub struct ErrorInformation {
     pub end_point: tree_sitter::Point,
     pub message: String,
     pub severity: Option<DiagnosticSeverity>,
+    pub fix: Option<QuickFix>,
+    /// the id of the lint rule
+    pub rule: Option<&'static str>,
 }
 
 /// checkerror the gammer error
@@ -42,6 +59,7 @@ impl Deref for ErrorInfo {
 pub fn checkerror<P: AsRef<Path>>(
     local_path: &P,
     source: &str,
+    tree: &tree_sitter::Tree,
     LintConfigInfo {
         use_lint,
         use_extra_cmake_lint,
@@ -52,15 +70,40 @@ pub fn checkerror<P: AsRef<Path>>(
     } else {
         None
     };
-    let mut parse = tree_sitter::Parser::new();
-    parse.set_language(&TREESITTER_CMAKE_LANGUAGE).unwrap();
-    let thetree = parse.parse(source, None)?;
-    let mut result = checkerror_inner(
-        local_path,
-        &source.lines().collect(),
-        thetree.root_node(),
-        use_lint,
-    );
+    let source: Vec<&str> = source.lines().collect();
+    let mut result = checkerror_inner(local_path, &source, tree.root_node(), use_lint);
+    let condition_info = condition::check_conditions(tree.root_node(), &source);
+    let genex_info = genex::check_genexes(tree.root_node(), &source);
+    let arguments_info =
+        arguments::check_arguments(tree.root_node(), &source, &BUILDIN_COMMAND_SIGNATURES);
+    if !condition_info.is_empty() || !genex_info.is_empty() || !arguments_info.is_empty() {
+        let error_info = result.get_or_insert(ErrorInfo { inner: vec![] });
+        error_info.inner.extend(condition_info);
+        error_info.inner.extend(arguments_info);
+        error_info.inner.extend(genex_info);
+    }
+    if use_lint {
+        let max_line_length = CMAKE_LINT_CONFIG.max_line_length.unwrap_or_else(|| {
+            editorconfig::get_properties(local_path)
+                .max_line_length
+                .unwrap_or(DEFAULT_MAX_LINE_LENGTH)
+        });
+        let style_info = style::check_style(
+            &source,
+            tree.root_node(),
+            &style::StyleConfig {
+                max_line_length,
+                max_arguments: CMAKE_LINT_CONFIG.max_arguments,
+                function_name_pattern: &CMAKE_LINT_CONFIG.function_name_pattern,
+            },
+        );
+        let modern_info = modern::check_modern_cmake(local_path, &source, tree.root_node());
+        if !style_info.is_empty() || !modern_info.is_empty() {
+            let error_info = result.get_or_insert(ErrorInfo { inner: vec![] });
+            error_info.inner.extend(style_info);
+            error_info.inner.extend(modern_info);
+        }
+    }
     if let Some(v) = cmake_lint_info {
         let error_info = result.get_or_insert(ErrorInfo { inner: vec![] });
         for item in v.inner {
@@ -68,7 +111,12 @@ pub fn checkerror<P: AsRef<Path>>(
         }
     };
 
-    result
+    let inner = rules::apply_rules(result?.inner, &CMAKE_LINT_CONFIG, tree.root_node(), &source);
+    if inner.is_empty() {
+        None
+    } else {
+        Some(ErrorInfo { inner })
+    }
 }
 
 const RE_MATCH_LINT_RESULT: &str =
@@ -101,12 +149,9 @@ fn run_cmake_lint<P: AsRef<Path>>(path: P) -> Option<ErrorInfo> {
             let message = m.name("message").unwrap().as_str().to_owned();
 
             let start_point = Point { row, column };
-            let end_point = start_point;
             info.push(ErrorInformation {
-                start_point,
-                end_point,
-                message,
                 severity: Some(severity),
+                ..rules::CMAKE_LINT.diagnostic(start_point, start_point, message)
             });
         }
     }
@@ -118,6 +163,11 @@ fn run_cmake_lint<P: AsRef<Path>>(path: P) -> Option<ErrorInfo> {
     }
 }
 
+// NOTE: "_" and digits are not uppercase, so the name like ADD_LIBRARY is checked by no lowercase chars
+fn is_upcase_command(name: &str) -> bool {
+    !name.chars().any(|c| c.is_lowercase())
+}
+
 fn checkerror_inner<P: AsRef<Path>>(
     local_path: P,
     newsource: &Vec<&str>,
@@ -126,12 +176,11 @@ fn checkerror_inner<P: AsRef<Path>>(
 ) -> Option<ErrorInfo> {
     if input.is_error() {
         return Some(ErrorInfo {
-            inner: vec![ErrorInformation {
-                start_point: input.start_position(),
-                end_point: input.end_position(),
-                message: "Grammar error".to_string(),
-                severity: None,
-            }],
+            inner: vec![rules::SYNTAX_ERROR.diagnostic(
+                input.start_position(),
+                input.end_position(),
+                "Grammar error".to_string(),
+            )],
         });
     }
     let local_path = local_path.as_ref();
@@ -152,12 +201,19 @@ fn checkerror_inner<P: AsRef<Path>>(
         let x = ids.start_position().column;
         let y = ids.end_position().column;
         let name = &newsource[h][x..y];
-        if use_lint && !config::CMAKE_LINT.lint_match(name.chars().all(|a| a.is_uppercase())) {
+        if use_lint && !config::CMAKE_LINT.lint_match(is_upcase_command(name)) {
+            let new_name = if config::CMAKE_LINT.command_upcase == "upcase" {
+                name.to_uppercase()
+            } else {
+                name.to_lowercase()
+            };
             output.push(ErrorInformation {
-                start_point: ids.start_position(),
-                end_point: ids.end_position(),
-                message: config::CMAKE_LINT.hint.clone(),
-                severity: Some(DiagnosticSeverity::HINT),
+                fix: Some(QuickFix::ChangeCase { new_name }),
+                ..rules::COMMAND_CASE.diagnostic(
+                    ids.start_position(),
+                    ids.end_position(),
+                    config::CMAKE_LINT.hint.clone(),
+                )
             });
         }
         let lowercase_name = name.to_lowercase();
@@ -181,12 +237,11 @@ fn checkerror_inner<P: AsRef<Path>>(
                 let y = child.end_position().column;
                 let name = &newsource[h][x..y];
                 if errorpackages.contains(&name.to_string()) {
-                    output.push(ErrorInformation {
-                        start_point: child.start_position(),
-                        end_point: child.end_position(),
-                        message: "Cannot find such package".to_string(),
-                        severity: Some(DiagnosticSeverity::ERROR),
-                    });
+                    output.push(rules::MISSING_PACKAGE.diagnostic(
+                        child.start_position(),
+                        child.end_position(),
+                        "Cannot find such package".to_string(),
+                    ));
                 }
             }
             continue;
@@ -214,11 +269,23 @@ fn checkerror_inner<P: AsRef<Path>>(
             };
             let first_arg = first_arg.replace("\\\\", "\\"); // TODO: proper string escape
             if first_arg.is_empty() {
+                // NOTE: also remove the space between it and the next argument
+                let remove_end = match first_arg_node.next_sibling() {
+                    Some(next) if next.start_position().row == h => next.start_position(),
+                    _ => first_arg_node.end_position(),
+                };
                 output.push(ErrorInformation {
-                    start_point: first_arg_node.start_position(),
-                    end_point: first_arg_node.end_position(),
-                    message: "Argument is empty".to_string(),
-                    severity: Some(DiagnosticSeverity::ERROR),
+                    fix: Some(QuickFix::RemoveArgument {
+                        range: tower_lsp::lsp_types::Range {
+                            start: first_arg_node.start_position().to_position(),
+                            end: remove_end.to_position(),
+                        },
+                    }),
+                    ..rules::EMPTY_ARGUMENT.diagnostic(
+                        first_arg_node.start_position(),
+                        first_arg_node.end_position(),
+                        "Argument is empty".to_string(),
+                    )
                 });
                 continue;
             }
@@ -230,45 +297,48 @@ fn checkerror_inner<P: AsRef<Path>>(
                 Ok(true) => {
                     if include_path.is_file() {
                         if scanner_include_error(include_path) {
-                            output.push(ErrorInformation {
-                                start_point: first_arg_node.start_position(),
-                                end_point: first_arg_node.end_position(),
-                                message: "Error in include file".to_string(),
-                                severity: Some(DiagnosticSeverity::ERROR),
-                            });
+                            output.push(rules::INCLUDE_ERROR.diagnostic(
+                                first_arg_node.start_position(),
+                                first_arg_node.end_position(),
+                                "Error in include file".to_string(),
+                            ));
                         }
                     } else {
                         if lowercase_name == "add_subdirectory" {
                             continue;
                         }
-                        output.push(ErrorInformation {
-                            start_point: first_arg_node.start_position(),
-                            end_point: first_arg_node.end_position(),
-                            message: format!(
-                                "\"{}\" is a directory",
-                                include_path.to_str().unwrap()
-                            ),
-                            severity: Some(DiagnosticSeverity::ERROR),
-                        });
+                        output.push(rules::INCLUDE_DIRECTORY.diagnostic(
+                            first_arg_node.start_position(),
+                            first_arg_node.end_position(),
+                            format!("\"{}\" is a directory", include_path.to_str().unwrap()),
+                        ));
                     }
                 }
                 _ => {
-                    let message = if is_sub_directory {
-                        format!(
-                            "Directory \"{}\" does not exist or is inaccessible",
-                            include_path.to_str().unwrap()
+                    let (message, missing_file) = if is_sub_directory {
+                        (
+                            format!(
+                                "Directory \"{}\" does not exist or is inaccessible",
+                                include_path.to_str().unwrap()
+                            ),
+                            include_path.join("CMakeLists.txt"),
                         )
                     } else {
-                        format!(
-                            "File \"{}\" does not exist or is inaccessible",
-                            include_path.to_str().unwrap()
+                        (
+                            format!(
+                                "File \"{}\" does not exist or is inaccessible",
+                                include_path.to_str().unwrap()
+                            ),
+                            include_path.clone(),
                         )
                     };
                     output.push(ErrorInformation {
-                        start_point: first_arg_node.start_position(),
-                        end_point: first_arg_node.end_position(),
-                        message,
-                        severity: Some(DiagnosticSeverity::WARNING),
+                        fix: Some(QuickFix::CreateFile { path: missing_file }),
+                        ..rules::MISSING_FILE.diagnostic(
+                            first_arg_node.start_position(),
+                            first_arg_node.end_position(),
+                            message,
+                        )
                     });
                 }
             }
@@ -355,7 +425,11 @@ add_subdirectory("unexist_subdir")
                     "File \"{}\" does not exist or is inaccessible",
                     hello_cmake_error.display()
                 ),
-                severity: Some(DiagnosticSeverity::WARNING)
+                severity: Some(DiagnosticSeverity::WARNING),
+                fix: Some(QuickFix::CreateFile {
+                    path: hello_cmake_error.clone()
+                }),
+                rule: Some("missing-file"),
             },
             ErrorInformation {
                 start_point: Point { row: 4, column: 17 },
@@ -364,7 +438,11 @@ add_subdirectory("unexist_subdir")
                     "Directory \"{}\" does not exist or is inaccessible",
                     unexist_subdir.display()
                 ),
-                severity: Some(DiagnosticSeverity::WARNING)
+                severity: Some(DiagnosticSeverity::WARNING),
+                fix: Some(QuickFix::CreateFile {
+                    path: unexist_subdir.join("CMakeLists.txt")
+                }),
+                rule: Some("missing-file"),
             },
         ]
     );
@@ -383,6 +461,14 @@ fn scanner_include_error<P: AsRef<Path>>(path: P) -> bool {
     tree.root_node().has_error()
 }
 
+#[test]
+fn tst_upcase_command() {
+    assert!(is_upcase_command("ADD_LIBRARY"));
+    assert!(is_upcase_command("CMAKE_MINIMUM_REQUIRED"));
+    assert!(!is_upcase_command("add_library"));
+    assert!(!is_upcase_command("Add_Library"));
+}
+
 #[test]
 fn include_error_tst() {
     use std::fs::File;
@@ -434,7 +520,9 @@ fn gammer_passed_check_1() {
                 start_point: input.start_position(),
                 end_point: input.end_position(),
                 message: "Grammar error".to_string(),
-                severity: None,
+                severity: Some(DiagnosticSeverity::ERROR),
+                fix: None,
+                rule: Some("syntax-error"),
             }]
         })
     );
@@ -0,0 +1,221 @@
+/// check the arguments of the buildin commands with their signatures
+use std::collections::{HashMap, HashSet};
+
+use tree_sitter::Node;
+
+use super::{rules, ErrorInformation};
+use crate::complete::{is_keyword, CommandSignature};
+use crate::utils::find_similar;
+use crate::utils::treehelper::{command_arguments, node_text};
+use crate::CMakeNodeKinds;
+
+/// the unquoted argument with the variables or ";" can be expanded to several arguments
+fn may_be_list(source: &[&str], argument: &Node) -> bool {
+    argument.child(0).is_none_or(|child| {
+        child.kind() == CMakeNodeKinds::UNQUOTED_ARGUMENT
+            && node_text(source, &child).is_none_or(|text| text.contains(['$', ';']))
+    })
+}
+
+struct ArgumentChecker<'a> {
+    source: &'a [&'a str],
+    signatures: &'a HashMap<String, Vec<CommandSignature>>,
+    output: Vec<ErrorInformation>,
+}
+
+impl ArgumentChecker<'_> {
+    fn check_command(&mut self, command: Node) {
+        let Some(identifier) = command.child(0) else {
+            return;
+        };
+        let Some(name) = node_text(self.source, &identifier) else {
+            return;
+        };
+        let name = name.to_lowercase();
+        let Some(signatures) = self
+            .signatures
+            .get(&name)
+            .filter(|signatures| !signatures.is_empty())
+        else {
+            return;
+        };
+        let arguments = command_arguments(command);
+        let texts: Vec<Option<&str>> = arguments
+            .iter()
+            .map(|argument| node_text(self.source, argument))
+            .collect();
+
+        let keywords: HashSet<&str> = signatures
+            .iter()
+            .flat_map(CommandSignature::keywords)
+            .collect();
+        let mut candidates: Vec<&str> = keywords.iter().copied().collect();
+        candidates.sort_unstable();
+        // NOTE: the first argument is the name given by the user, like the target
+        let skip = usize::from(
+            signatures
+                .iter()
+                .all(CommandSignature::starts_with_positional),
+        );
+        for (argument, text) in arguments.iter().zip(&texts).skip(skip) {
+            let Some(text) = text.filter(|text| is_keyword(text) && !keywords.contains(text))
+            else {
+                continue;
+            };
+            // NOTE: the uppercase values are common, only the ones like the keywords are reported
+            if let Some(similar) = find_similar(text, &candidates) {
+                self.output.push(rules::UNKNOWN_KEYWORD.diagnostic(
+                    argument.start_position(),
+                    argument.end_position(),
+                    format!("Unknown keyword \"{text}\" of {name}, did you mean \"{similar}\"?"),
+                ));
+            }
+        }
+
+        if arguments
+            .iter()
+            .any(|argument| may_be_list(self.source, argument))
+        {
+            return;
+        }
+        let first = texts.first().copied().flatten();
+        let mut matched: Vec<&CommandSignature> = signatures
+            .iter()
+            .filter(|signature| first.is_some_and(|first| signature.modes().contains(&first)))
+            .collect();
+        if matched.is_empty() {
+            matched = signatures
+                .iter()
+                .filter(|signature| signature.modes().is_empty())
+                .collect();
+        }
+        let Some(min_arguments) = matched
+            .iter()
+            .map(|signature| signature.min_arguments())
+            .min()
+        else {
+            return;
+        };
+        if arguments.len() < min_arguments {
+            self.output.push(rules::MISSING_ARGUMENT.diagnostic(
+                identifier.start_position(),
+                identifier.end_position(),
+                format!(
+                    "{name} needs at least {min_arguments} arguments, but {} given",
+                    arguments.len()
+                ),
+            ));
+        }
+    }
+
+    fn scan_commands(&mut self, input: Node) {
+        let mut course = input.walk();
+        for child in input.children(&mut course) {
+            match child.kind() {
+                CMakeNodeKinds::NORMAL_COMMAND => {
+                    if !child.has_error() {
+                        self.check_command(child);
+                    }
+                }
+                CMakeNodeKinds::LINE_COMMENT | CMakeNodeKinds::BRACKET_COMMENT => {}
+                _ => self.scan_commands(child),
+            }
+        }
+    }
+}
+
+/// check the keywords and the count of the arguments of the buildin commands
+pub fn check_arguments(
+    root: Node,
+    source: &[&str],
+    signatures: &HashMap<String, Vec<CommandSignature>>,
+) -> Vec<ErrorInformation> {
+    let mut checker = ArgumentChecker {
+        source,
+        signatures,
+        output: vec![],
+    };
+    checker.scan_commands(root);
+    checker.output
+}
+
+#[cfg(test)]
+mod arguments_test {
+    use super::*;
+    use crate::consts::TREESITTER_CMAKE_LANGUAGE;
+
+    #[test]
+    fn tst_check_arguments() {
+        let signatures: HashMap<String, Vec<CommandSignature>> = [
+            (
+                "target_link_libraries",
+                vec![
+                    "target_link_libraries(<target> <item>...)",
+                    "target_link_libraries(<target> <PRIVATE|PUBLIC|INTERFACE> <item>... [<PRIVATE|PUBLIC|INTERFACE> <item>...]...)",
+                ],
+            ),
+            (
+                "file",
+                vec![
+                    "file(READ <filename> <variable> [OFFSET <offset>] [LIMIT <max-in>] [HEX])",
+                    "file(<HASH> <filename> <variable>)",
+                ],
+            ),
+        ]
+        .into_iter()
+        .map(|(name, signatures)| {
+            let signatures = signatures
+                .into_iter()
+                .map(|signature| CommandSignature::parse(signature).unwrap())
+                .collect();
+            (name.to_string(), signatures)
+        })
+        .collect();
+        let source = r#"target_link_libraries(foo PUBLLIC bar)
+target_link_libraries(FOO PRIVATE bar)
+file(READ a)
+file(READ ${ARGS})
+file(REED a b)
+"#;
+        let mut parse = tree_sitter::Parser::new();
+        parse.set_language(&TREESITTER_CMAKE_LANGUAGE).unwrap();
+        let tree = parse.parse(source, None).unwrap();
+        let lines: Vec<&str> = source.lines().collect();
+        let result: Vec<(&str, usize, usize, String)> =
+            check_arguments(tree.root_node(), &lines, &signatures)
+                .into_iter()
+                .map(|info| {
+                    (
+                        info.rule.unwrap(),
+                        info.start_point.row,
+                        info.start_point.column,
+                        info.message,
+                    )
+                })
+                .collect();
+        assert_eq!(
+            result,
+            vec![
+                (
+                    "unknown-keyword",
+                    0,
+                    26,
+                    "Unknown keyword \"PUBLLIC\" of target_link_libraries, did you mean \"PUBLIC\"?"
+                        .to_string()
+                ),
+                (
+                    "missing-argument",
+                    2,
+                    0,
+                    "file needs at least 3 arguments, but 1 given".to_string()
+                ),
+                (
+                    "unknown-keyword",
+                    4,
+                    5,
+                    "Unknown keyword \"REED\" of file, did you mean \"READ\"?".to_string()
+                ),
+            ]
+        );
+    }
+}
@@ -0,0 +1,504 @@
+/// the parser of the conditions of if, elseif and while
+use tree_sitter::{Node, Point};
+
+use super::{rules, ErrorInformation};
+use crate::utils::find_similar;
+use crate::CMakeNodeKinds;
+
+const CONDITION_COMMANDS: &[&str] = &[
+    CMakeNodeKinds::IF_COMMAND,
+    CMakeNodeKinds::ELSEIF_COMMAND,
+    CMakeNodeKinds::WHILE_COMMAND,
+];
+
+pub const UNARY_OPERATORS: &[&str] = &[
+    "EXISTS",
+    "COMMAND",
+    "DEFINED",
+    "POLICY",
+    "TARGET",
+    "TEST",
+    "IS_DIRECTORY",
+    "IS_SYMLINK",
+    "IS_ABSOLUTE",
+    "IS_READABLE",
+    "IS_WRITABLE",
+    "IS_EXECUTABLE",
+];
+
+pub const BINARY_OPERATORS: &[&str] = &[
+    "EQUAL",
+    "LESS",
+    "LESS_EQUAL",
+    "GREATER",
+    "GREATER_EQUAL",
+    "STREQUAL",
+    "STRLESS",
+    "STRLESS_EQUAL",
+    "STRGREATER",
+    "STRGREATER_EQUAL",
+    "VERSION_EQUAL",
+    "VERSION_LESS",
+    "VERSION_LESS_EQUAL",
+    "VERSION_GREATER",
+    "VERSION_GREATER_EQUAL",
+    "PATH_EQUAL",
+    "MATCHES",
+    "IN_LIST",
+    "IS_NEWER_THAN",
+];
+
+pub const LOGICAL_OPERATORS: &[&str] = &["NOT", "AND", "OR"];
+
+/// whether the word is an operator of the conditions
+pub fn is_operator(word: &str) -> bool {
+    UNARY_OPERATORS.contains(&word)
+        || BINARY_OPERATORS.contains(&word)
+        || LOGICAL_OPERATORS.contains(&word)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TokenKind {
+    Open,
+    Close,
+    /// the unquoted argument, which can be an operator
+    Word,
+    /// the quoted or bracket argument, which is never an operator
+    Quoted,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Token<'a> {
+    kind: TokenKind,
+    text: &'a str,
+    start_point: Point,
+    end_point: Point,
+}
+
+impl Token<'_> {
+    fn is(&self, word: &str) -> bool {
+        self.kind == TokenKind::Word && self.text == word
+    }
+
+    fn is_operator(&self) -> bool {
+        self.kind == TokenKind::Word && is_operator(self.text)
+    }
+
+    fn is_binary_operator(&self) -> bool {
+        self.kind == TokenKind::Word && BINARY_OPERATORS.contains(&self.text)
+    }
+
+    // such as if(${VAR}), whose value is used as the variable name again
+    fn expanded_variable(&self) -> Option<&str> {
+        if self.kind != TokenKind::Word {
+            return None;
+        }
+        let name = self.text.strip_prefix("${")?.strip_suffix('}')?;
+        if name.is_empty() || name.contains(['$', '{', '}']) {
+            return None;
+        }
+        Some(name)
+    }
+
+    fn is_upper_word(&self) -> bool {
+        self.kind == TokenKind::Word
+            && self.text.len() > 1
+            && self
+                .text
+                .chars()
+                .all(|c| c.is_ascii_uppercase() || c == '_')
+    }
+}
+
+fn collect_tokens<'a>(input: Node, source: &[&'a str], tokens: &mut Vec<Token<'a>>) {
+    let mut course = input.walk();
+    for child in input.children(&mut course) {
+        let start_point = child.start_position();
+        let end_point = child.end_position();
+        let kind = match child.kind() {
+            "(" => TokenKind::Open,
+            ")" => TokenKind::Close,
+            CMakeNodeKinds::ARGUMENT => match child.child(0).map(|argument| argument.kind()) {
+                Some(CMakeNodeKinds::UNQUOTED_ARGUMENT) => TokenKind::Word,
+                _ => TokenKind::Quoted,
+            },
+            CMakeNodeKinds::LINE_COMMENT | CMakeNodeKinds::BRACKET_COMMENT => continue,
+            _ => {
+                collect_tokens(child, source, tokens);
+                continue;
+            }
+        };
+        let text = if start_point.row == end_point.row {
+            source
+                .get(start_point.row)
+                .and_then(|line| line.get(start_point.column..end_point.column))
+                .unwrap_or_default()
+        } else {
+            ""
+        };
+        tokens.push(Token {
+            kind,
+            text,
+            start_point,
+            end_point,
+        });
+    }
+}
+
+struct ConditionParser<'a> {
+    tokens: Vec<Token<'a>>,
+    index: usize,
+    warnings: Vec<ErrorInformation>,
+}
+
+type ParseResult<T> = Result<T, ErrorInformation>;
+
+fn condition_error(token: &Token, message: String) -> ErrorInformation {
+    rules::INVALID_CONDITION.diagnostic(token.start_point, token.end_point, message)
+}
+
+impl<'a> ConditionParser<'a> {
+    fn peek(&self) -> Option<Token<'a>> {
+        self.tokens.get(self.index).copied()
+    }
+
+    fn next(&mut self) -> Option<Token<'a>> {
+        let token = self.peek()?;
+        self.index += 1;
+        Some(token)
+    }
+
+    fn previous(&self) -> Option<Token<'a>> {
+        self.tokens.get(self.index.checked_sub(1)?).copied()
+    }
+
+    fn missing_operand(&self, token: Option<Token>) -> ErrorInformation {
+        match (token, self.previous()) {
+            (Some(token), _) => {
+                condition_error(&token, format!("Missing operand before \"{}\"", token.text))
+            }
+            (None, Some(previous)) => condition_error(
+                &previous,
+                format!("Missing operand after \"{}\"", previous.text),
+            ),
+            (None, None) => unreachable!("the empty condition is not parsed"),
+        }
+    }
+
+    // the token is found where AND, OR, a binary operator or ")" is expected
+    fn unexpected(&self, token: Token) -> ErrorInformation {
+        if token.kind == TokenKind::Close {
+            return condition_error(&token, "Unbalanced \")\"".to_string());
+        }
+        let operators: Vec<&str> = BINARY_OPERATORS
+            .iter()
+            .chain(LOGICAL_OPERATORS.iter())
+            .copied()
+            .collect();
+        if token.is_upper_word() && self.tokens.get(self.index + 1).is_some() {
+            return unknown_operator(&token, &operators);
+        }
+        // such as if(EXIST file)
+        if let Some(previous) = self
+            .previous()
+            .filter(|previous| previous.is_upper_word() && !previous.is_operator())
+        {
+            if find_similar(previous.text, UNARY_OPERATORS).is_some() {
+                return unknown_operator(&previous, UNARY_OPERATORS);
+            }
+        }
+        if token.is_upper_word() {
+            return unknown_operator(&token, &operators);
+        }
+        condition_error(
+            &token,
+            format!(
+                "Unexpected argument \"{}\", expected an operator",
+                token.text
+            ),
+        )
+    }
+
+    fn check_expanded_variable(&mut self, token: &Token) {
+        let Some(name) = token.expanded_variable() else {
+            return;
+        };
+        self.warnings.push(rules::CONDITION_VARIABLE_EXPANSION.diagnostic(
+            token.start_point,
+            token.end_point,
+            format!(
+                "\"{}\" is expanded before the condition is evaluated, and its value is used as a variable name, use \"{name}\" instead",
+                token.text
+            ),
+        ));
+    }
+
+    fn parse(&mut self) -> ParseResult<()> {
+        self.parse_or()?;
+        match self.peek() {
+            Some(token) => Err(self.unexpected(token)),
+            None => Ok(()),
+        }
+    }
+
+    fn parse_or(&mut self) -> ParseResult<()> {
+        self.parse_and()?;
+        while self.peek().is_some_and(|token| token.is("OR")) {
+            self.next();
+            self.parse_and()?;
+        }
+        Ok(())
+    }
+
+    fn parse_and(&mut self) -> ParseResult<()> {
+        self.parse_not()?;
+        while self.peek().is_some_and(|token| token.is("AND")) {
+            self.next();
+            self.parse_not()?;
+        }
+        Ok(())
+    }
+
+    fn parse_not(&mut self) -> ParseResult<()> {
+        if self.peek().is_some_and(|token| token.is("NOT")) {
+            self.next();
+            return self.parse_not();
+        }
+        self.parse_comparison()
+    }
+
+    fn parse_comparison(&mut self) -> ParseResult<()> {
+        let operand = self.parse_primary()?;
+        let mut has_operator = false;
+        while self.peek().is_some_and(|token| token.is_binary_operator()) {
+            self.next();
+            self.parse_operand()?;
+            has_operator = true;
+        }
+        if let (Some(operand), false) = (operand, has_operator) {
+            self.check_expanded_variable(&operand);
+        }
+        Ok(())
+    }
+
+    // the operand which is used alone is returned
+    fn parse_primary(&mut self) -> ParseResult<Option<Token<'a>>> {
+        let Some(token) = self.peek() else {
+            return Err(self.missing_operand(None));
+        };
+        match token.kind {
+            TokenKind::Open => {
+                self.next();
+                if self
+                    .peek()
+                    .is_some_and(|token| token.kind == TokenKind::Close)
+                {
+                    return Err(condition_error(&token, "Empty parentheses".to_string()));
+                }
+                self.parse_or()?;
+                match self.peek() {
+                    Some(close) if close.kind == TokenKind::Close => {
+                        self.next();
+                        Ok(None)
+                    }
+                    Some(other) => Err(self.unexpected(other)),
+                    None => Err(condition_error(&token, "Unbalanced \"(\"".to_string())),
+                }
+            }
+            TokenKind::Close => Err(self.missing_operand(Some(token))),
+            _ if UNARY_OPERATORS.contains(&token.text) && token.kind == TokenKind::Word => {
+                // NOTE: DEFINED ${name} is common in the functions, the name is given by the caller
+                self.next();
+                self.parse_operand()?;
+                Ok(None)
+            }
+            _ if token.is_operator() => Err(self.missing_operand(Some(token))),
+            _ => {
+                self.next();
+                Ok(Some(token))
+            }
+        }
+    }
+
+    fn parse_operand(&mut self) -> ParseResult<Token<'a>> {
+        match self.peek() {
+            Some(token) if token.kind == TokenKind::Open => Err(self.missing_operand(Some(token))),
+            Some(token) if token.kind == TokenKind::Close || token.is_operator() => {
+                Err(self.missing_operand(Some(token)))
+            }
+            Some(token) => {
+                self.next();
+                Ok(token)
+            }
+            None => Err(self.missing_operand(None)),
+        }
+    }
+}
+
+fn unknown_operator(token: &Token, candidates: &[&str]) -> ErrorInformation {
+    let message = match find_similar(token.text, candidates) {
+        Some(similar) => format!(
+            "Unknown operator \"{}\", did you mean \"{similar}\"?",
+            token.text
+        ),
+        None => format!("Unknown operator \"{}\"", token.text),
+    };
+    condition_error(token, message)
+}
+
+fn check_condition(command: Node, source: &[&str], output: &mut Vec<ErrorInformation>) {
+    let mut tokens = vec![];
+    collect_tokens(command, source, &mut tokens);
+    // NOTE: the tokens of the command are if ( ... ), the keyword is not collected
+    if tokens
+        .first()
+        .is_some_and(|token| token.kind == TokenKind::Open)
+    {
+        tokens.remove(0);
+    }
+    if tokens
+        .last()
+        .is_some_and(|token| token.kind == TokenKind::Close)
+    {
+        tokens.pop();
+    }
+    if tokens.is_empty() {
+        return;
+    }
+    let mut parser = ConditionParser {
+        tokens,
+        index: 0,
+        warnings: vec![],
+    };
+    if let Err(error) = parser.parse() {
+        output.push(error);
+    }
+    output.append(&mut parser.warnings);
+}
+
+/// check the conditions of all the if, elseif and while commands
+pub fn check_conditions(root: Node, source: &[&str]) -> Vec<ErrorInformation> {
+    fn scan(input: Node, source: &[&str], output: &mut Vec<ErrorInformation>) {
+        let mut course = input.walk();
+        for child in input.children(&mut course) {
+            if CONDITION_COMMANDS.contains(&child.kind()) {
+                if !child.has_error() {
+                    check_condition(child, source, output);
+                }
+                continue;
+            }
+            scan(child, source, output);
+        }
+    }
+    let mut output = vec![];
+    scan(root, source, &mut output);
+    output
+}
+
+#[cfg(test)]
+mod condition_test {
+    use super::*;
+    use crate::consts::TREESITTER_CMAKE_LANGUAGE;
+
+    fn check(source: &str) -> Vec<(&'static str, String)> {
+        let mut parse = tree_sitter::Parser::new();
+        parse.set_language(&TREESITTER_CMAKE_LANGUAGE).unwrap();
+        let tree = parse.parse(source, None).unwrap();
+        let lines: Vec<&str> = source.lines().collect();
+        check_conditions(tree.root_node(), &lines)
+            .into_iter()
+            .map(|info| (info.rule.unwrap(), info.message))
+            .collect()
+    }
+
+    #[test]
+    fn tst_valid_conditions() {
+        let source = r#"if(NOT DEFINED VAR AND (A OR B))
+elseif(EXISTS "${PATH}" AND CMAKE_VERSION VERSION_GREATER_EQUAL 3.20)
+endif()
+while(item IN_LIST items)
+endwhile()
+if("${A}" STREQUAL "AND")
+endif()
+"#;
+        assert!(check(source).is_empty());
+    }
+
+    #[test]
+    fn tst_invalid_conditions() {
+        let error = |message: &str| ("invalid-condition", message.to_string());
+        assert_eq!(
+            check("if(A STREQUALS B)\nendif()\n"),
+            vec![error(
+                "Unknown operator \"STREQUALS\", did you mean \"STREQUAL\"?"
+            )]
+        );
+        assert_eq!(
+            check("if(EXIST file)\nendif()\n"),
+            vec![error(
+                "Unknown operator \"EXIST\", did you mean \"EXISTS\"?"
+            )]
+        );
+        assert_eq!(
+            check("if(A AND)\nendif()\n"),
+            vec![error("Missing operand after \"AND\"")]
+        );
+        assert_eq!(
+            check("if(STREQUAL B)\nendif()\n"),
+            vec![error("Missing operand before \"STREQUAL\"")]
+        );
+        assert_eq!(
+            check("if(A a b)\nendif()\n"),
+            vec![error("Unexpected argument \"a\", expected an operator")]
+        );
+    }
+
+    #[test]
+    fn tst_parentheses() {
+        let parse = |kinds: &[TokenKind]| {
+            let point = Point { row: 0, column: 0 };
+            let mut parser = ConditionParser {
+                tokens: kinds
+                    .iter()
+                    .map(|kind| Token {
+                        kind: *kind,
+                        text: match kind {
+                            TokenKind::Open => "(",
+                            TokenKind::Close => ")",
+                            _ => "A",
+                        },
+                        start_point: point,
+                        end_point: point,
+                    })
+                    .collect(),
+                index: 0,
+                warnings: vec![],
+            };
+            parser.parse().map_err(|error| error.message)
+        };
+        use TokenKind::{Close, Open, Word};
+        assert_eq!(parse(&[Open, Word, Close]), Ok(()));
+        assert_eq!(
+            parse(&[Open, Open, Word, Close]),
+            Err("Unbalanced \"(\"".to_string())
+        );
+        assert_eq!(parse(&[Word, Close]), Err("Unbalanced \")\"".to_string()));
+        assert_eq!(parse(&[Open, Close]), Err("Empty parentheses".to_string()));
+    }
+
+    #[test]
+    fn tst_expanded_variable() {
+        assert_eq!(
+            check("if(${USE_QT} OR NOT DEFINED ${NAME})\nendif()\n"),
+            vec![(
+                "condition-variable-expansion",
+                "\"${USE_QT}\" is expanded before the condition is evaluated, and its value is used as a variable name, use \"USE_QT\" instead".to_string()
+            )]
+        );
+        assert!(check("if(EXISTS ${PATH} AND ${A} STREQUAL B)\nendif()\n").is_empty());
+        assert!(check(
+            "function(set_default var)\n  if(NOT DEFINED ${var})\n    set(${var} ON PARENT_SCOPE)\n  endif()\nendfunction()\n"
+        )
+        .is_empty());
+    }
+}
@@ -0,0 +1,185 @@
+/// check the generator expressions in the arguments
+use tree_sitter::{Node, Point};
+
+use super::{rules, ErrorInformation};
+use crate::genex::{find_genex, parse_genexes, GENEXES};
+use crate::utils::find_similar;
+use crate::utils::treehelper::{command_arguments, node_text};
+use crate::CMakeNodeKinds;
+
+fn check_argument(row: usize, column: usize, text: &str, output: &mut Vec<ErrorInformation>) {
+    let point = |offset: usize| Point {
+        row,
+        column: column + offset,
+    };
+    for genex in parse_genexes(text) {
+        if genex.end.is_none() {
+            output.push(rules::UNBALANCED_GENEX.diagnostic(
+                point(genex.start),
+                point(genex.name_end),
+                format!(
+                    "Unclosed generator expression \"$<{}\", missing \">\"",
+                    genex.name
+                ),
+            ));
+            continue;
+        }
+        if genex.name.is_empty() {
+            if !genex.has_nested_condition(text) {
+                output.push(rules::UNKNOWN_GENEX.diagnostic(
+                    point(genex.start),
+                    point(genex.name_end),
+                    "Missing the name of the generator expression".to_string(),
+                ));
+            }
+            continue;
+        }
+        if find_genex(genex.name).is_some() {
+            continue;
+        }
+        let names: Vec<&str> = GENEXES.iter().map(|info| info.name).collect();
+        let message = match find_similar(genex.name, &names) {
+            Some(similar) => {
+                format!(
+                    "Unknown generator expression \"{}\", did you mean \"{similar}\"?",
+                    genex.name
+                )
+            }
+            None => format!("Unknown generator expression \"{}\"", genex.name),
+        };
+        output.push(rules::UNKNOWN_GENEX.diagnostic(
+            point(genex.start + 2),
+            point(genex.name_end),
+            message,
+        ));
+    }
+}
+
+/// the regular expressions in the arguments of the command, "$<" in them is not a generator expression,
+/// such as string(REGEX MATCH <regex> ...), list(FILTER ... REGEX <regex>) and if(... MATCHES <regex>)
+fn regex_arguments<'a>(command: Node<'a>, source: &[&str]) -> Vec<Node<'a>> {
+    let arguments = command_arguments(command);
+    let texts: Vec<&str> = arguments
+        .iter()
+        .map(|argument| node_text(source, argument).unwrap_or_default())
+        .collect();
+    let after_keyword = |keyword: &str| -> Vec<Node<'a>> {
+        texts
+            .iter()
+            .zip(arguments.iter().skip(1))
+            .filter(|(text, _)| **text == keyword)
+            .map(|(_, argument)| *argument)
+            .collect()
+    };
+    if command.kind() != CMakeNodeKinds::NORMAL_COMMAND {
+        return after_keyword("MATCHES");
+    }
+    let Some(name) = command
+        .child(0)
+        .and_then(|name| node_text(source, &name))
+        .map(str::to_lowercase)
+    else {
+        return vec![];
+    };
+    match (name.as_str(), texts.as_slice()) {
+        ("string", ["REGEX", "MATCH" | "MATCHALL" | "REPLACE", ..]) => {
+            arguments.get(2).copied().into_iter().collect()
+        }
+        ("list", ["FILTER", ..]) => after_keyword("REGEX"),
+        _ => vec![],
+    }
+}
+
+fn scan_arguments(
+    input: Node,
+    source: &[&str],
+    regexes: &[Node],
+    output: &mut Vec<ErrorInformation>,
+) {
+    let mut course = input.walk();
+    for child in input.children(&mut course) {
+        match child.kind() {
+            CMakeNodeKinds::ARGUMENT if regexes.contains(&child) => {}
+            CMakeNodeKinds::ARGUMENT => {
+                // NOTE: the multiline strings are skipped
+                if let Some(text) = node_text(source, &child) {
+                    let start = child.start_position();
+                    check_argument(start.row, start.column, text, output);
+                }
+            }
+            CMakeNodeKinds::NORMAL_COMMAND
+            | CMakeNodeKinds::IF_COMMAND
+            | CMakeNodeKinds::ELSEIF_COMMAND
+            | CMakeNodeKinds::WHILE_COMMAND => {
+                scan_arguments(child, source, &regex_arguments(child, source), output)
+            }
+            CMakeNodeKinds::LINE_COMMENT | CMakeNodeKinds::BRACKET_COMMENT => {}
+            _ => scan_arguments(child, source, regexes, output),
+        }
+    }
+}
+
+pub fn check_genexes(root: Node, source: &[&str]) -> Vec<ErrorInformation> {
+    let mut output = vec![];
+    scan_arguments(root, source, &[], &mut output);
+    output
+}
+
+#[cfg(test)]
+mod genex_check_test {
+    use super::*;
+    use crate::consts::TREESITTER_CMAKE_LANGUAGE;
+
+    #[test]
+    fn tst_check_genexes() {
+        let source = r#"target_include_directories(demo PUBLIC $<BUILD_INTERFACE:${CMAKE_CURRENT_SOURCE_DIR}/include>)
+target_compile_options(demo PRIVATE $<$<CONFIG:Debug>:-O0> "$<TARGET_FILE:demo")
+add_custom_command(TARGET demo COMMAND echo $<TARGET_FILES:demo> $<:a>)
+target_compile_definitions(demo PRIVATE $<${USE_X}:USE_X> BUILD_TYPE=$<CONFIGURATION>)
+string(REGEX REPLACE "\\$<[^>]*>" "" out "${in}")
+list(FILTER genexes INCLUDE REGEX "^$<")
+if(in MATCHES "$<:")
+endif()
+message(STATUS "\\$<")
+"#;
+        let mut parse = tree_sitter::Parser::new();
+        parse.set_language(&TREESITTER_CMAKE_LANGUAGE).unwrap();
+        let tree = parse.parse(source, None).unwrap();
+        let lines: Vec<&str> = source.lines().collect();
+        let result: Vec<(&str, usize, usize, String)> = check_genexes(tree.root_node(), &lines)
+            .into_iter()
+            .map(|info| {
+                (
+                    info.rule.unwrap(),
+                    info.start_point.row,
+                    info.start_point.column,
+                    info.message,
+                )
+            })
+            .collect();
+        assert_eq!(
+            result,
+            vec![
+                (
+                    "unbalanced-genex",
+                    1,
+                    60,
+                    "Unclosed generator expression \"$<TARGET_FILE\", missing \">\"".to_string()
+                ),
+                (
+                    "unknown-genex",
+                    2,
+                    46,
+                    "Unknown generator expression \"TARGET_FILES\", did you mean \"TARGET_FILE\"?"
+                        .to_string()
+                ),
+                (
+                    "unknown-genex",
+                    2,
+                    65,
+                    "Missing the name of the generator expression".to_string()
+                ),
+            ]
+        );
+    }
+}
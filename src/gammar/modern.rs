@@ -0,0 +1,268 @@
+/// the modern cmake rules, the usages which should be replaced by the target based commands
+use std::path::Path;
+
+use tree_sitter::Node;
+
+use super::{rules, ErrorInformation};
+use crate::utils::treehelper::{command_arguments, node_text};
+use crate::CMakeNodeKinds;
+
+/// the directory scope commands, and the target ones to use
+const DIRECTORY_SCOPE_COMMANDS: &[(&str, &str)] = &[
+    ("include_directories", "target_include_directories"),
+    ("link_libraries", "target_link_libraries"),
+    ("add_definitions", "target_compile_definitions"),
+    ("add_compile_options", "target_compile_options"),
+];
+
+const LINK_SCOPE_KEYWORDS: &[&str] = &[
+    "PUBLIC",
+    "PRIVATE",
+    "INTERFACE",
+    "LINK_PUBLIC",
+    "LINK_PRIVATE",
+    "LINK_INTERFACE_LIBRARIES",
+];
+
+fn is_cmake_flags(name: &str) -> bool {
+    let Some(rest) = name
+        .strip_prefix("CMAKE_CXX_FLAGS")
+        .or_else(|| name.strip_prefix("CMAKE_C_FLAGS"))
+    else {
+        return false;
+    };
+    // such as CMAKE_CXX_FLAGS_RELEASE
+    rest.is_empty()
+        || rest.strip_prefix('_').is_some_and(|config| {
+            !config.is_empty() && config.chars().all(|c| c.is_ascii_uppercase())
+        })
+}
+
+// the lowercase name and the identifier node of the normal command
+fn command_name<'a>(source: &[&str], node: &Node<'a>) -> Option<(String, Node<'a>)> {
+    if node.kind() != CMakeNodeKinds::NORMAL_COMMAND {
+        return None;
+    }
+    let identifier = node.child(0)?;
+    Some((node_text(source, &identifier)?.to_lowercase(), identifier))
+}
+
+struct ModernChecker<'a> {
+    source: &'a [&'a str],
+    output: Vec<ErrorInformation>,
+}
+
+impl ModernChecker<'_> {
+    fn check_command(&mut self, command: Node) {
+        let Some(identifier) = command.child(0) else {
+            return;
+        };
+        let Some(name) = node_text(self.source, &identifier) else {
+            return;
+        };
+        let name = name.to_lowercase();
+        let arguments = command_arguments(command);
+        let texts: Vec<&str> = arguments
+            .iter()
+            .map(|argument| node_text(self.source, argument).unwrap_or_default())
+            .collect();
+
+        if let Some((_, target_command)) = DIRECTORY_SCOPE_COMMANDS
+            .iter()
+            .find(|(command, _)| *command == name)
+        {
+            self.output.push(rules::DIRECTORY_SCOPE_COMMAND.diagnostic(
+                identifier.start_position(),
+                identifier.end_position(),
+                format!("{name} affects all the targets in the directory, use {target_command}"),
+            ));
+            return;
+        }
+        match name.as_str() {
+            "set" | "string" | "list" => {
+                // set(CMAKE_CXX_FLAGS ...), string(APPEND CMAKE_CXX_FLAGS ...)
+                let index = if name == "set" { 0 } else { 1 };
+                if name != "set" && !matches!(texts.first(), Some(&"APPEND" | &"PREPEND")) {
+                    return;
+                }
+                let (Some(variable), Some(node)) = (texts.get(index), arguments.get(index)) else {
+                    return;
+                };
+                if is_cmake_flags(variable) {
+                    self.output.push(rules::CMAKE_FLAGS_EDIT.diagnostic(
+                        node.start_position(),
+                        node.end_position(),
+                        format!("{variable} affects all the targets, use target_compile_options"),
+                    ));
+                }
+            }
+            "target_link_libraries" => {
+                if texts.len() < 2
+                    || texts
+                        .iter()
+                        .skip(1)
+                        .any(|text| LINK_SCOPE_KEYWORDS.contains(text))
+                {
+                    return;
+                }
+                self.output
+                    .push(rules::TARGET_LINK_WITHOUT_SCOPE.diagnostic(
+                        identifier.start_position(),
+                        identifier.end_position(),
+                        "target_link_libraries without PUBLIC, PRIVATE or INTERFACE".to_string(),
+                    ));
+            }
+            "file" => {
+                let Some(glob) = texts
+                    .first()
+                    .filter(|text| matches!(**text, "GLOB" | "GLOB_RECURSE"))
+                else {
+                    return;
+                };
+                if texts.contains(&"CONFIGURE_DEPENDS") {
+                    return;
+                }
+                self.output.push(rules::GLOB_WITHOUT_CONFIGURE_DEPENDS.diagnostic(
+                    arguments[0].start_position(),
+                    arguments[0].end_position(),
+                    format!(
+                        "file({glob}) without CONFIGURE_DEPENDS, the new files are not found until cmake runs again"
+                    ),
+                ));
+            }
+            _ => {}
+        }
+    }
+
+    fn scan_commands(&mut self, input: Node) {
+        let mut course = input.walk();
+        for child in input.children(&mut course) {
+            match child.kind() {
+                CMakeNodeKinds::NORMAL_COMMAND => self.check_command(child),
+                CMakeNodeKinds::LINE_COMMENT | CMakeNodeKinds::BRACKET_COMMENT => {}
+                _ => self.scan_commands(child),
+            }
+        }
+    }
+
+    // only checked in the CMakeLists.txt with project(), which is the top one
+    fn check_minimum_required(&mut self, root: Node) {
+        let mut course = root.walk();
+        let statements: Vec<Node> = root
+            .children(&mut course)
+            .filter(|child| {
+                !matches!(
+                    child.kind(),
+                    CMakeNodeKinds::LINE_COMMENT | CMakeNodeKinds::BRACKET_COMMENT
+                )
+            })
+            .collect();
+        let Some((_, project)) = statements
+            .iter()
+            .filter_map(|node| command_name(self.source, node))
+            .find(|(name, _)| name == "project")
+        else {
+            return;
+        };
+        let minimum_required = statements
+            .iter()
+            .enumerate()
+            .filter_map(|(index, node)| Some((index, command_name(self.source, node)?)))
+            .find(|(_, (name, _))| name == "cmake_minimum_required");
+        match minimum_required {
+            None => self.output.push(rules::CMAKE_MINIMUM_REQUIRED.diagnostic(
+                project.start_position(),
+                project.end_position(),
+                "cmake_minimum_required is missing".to_string(),
+            )),
+            Some((index, (_, identifier))) if index != 0 => {
+                self.output.push(rules::CMAKE_MINIMUM_REQUIRED.diagnostic(
+                    identifier.start_position(),
+                    identifier.end_position(),
+                    "cmake_minimum_required should be the first command".to_string(),
+                ))
+            }
+            _ => {}
+        }
+    }
+}
+
+/// check the modern cmake rules of the file
+pub fn check_modern_cmake<P: AsRef<Path>>(
+    local_path: P,
+    source: &[&str],
+    root: Node,
+) -> Vec<ErrorInformation> {
+    let mut checker = ModernChecker {
+        source,
+        output: vec![],
+    };
+    checker.scan_commands(root);
+    if local_path
+        .as_ref()
+        .file_name()
+        .is_some_and(|name| name == "CMakeLists.txt")
+    {
+        checker.check_minimum_required(root);
+    }
+    checker.output
+}
+
+#[cfg(test)]
+mod modern_test {
+    use super::*;
+    use crate::consts::TREESITTER_CMAKE_LANGUAGE;
+
+    fn check(source: &str) -> Vec<(&'static str, usize)> {
+        let mut parse = tree_sitter::Parser::new();
+        parse.set_language(&TREESITTER_CMAKE_LANGUAGE).unwrap();
+        let tree = parse.parse(source, None).unwrap();
+        let lines: Vec<&str> = source.lines().collect();
+        check_modern_cmake("/tmp/CMakeLists.txt", &lines, tree.root_node())
+            .iter()
+            .map(|info| (info.rule.unwrap(), info.start_point.row))
+            .collect()
+    }
+
+    #[test]
+    fn tst_modern_cmake() {
+        let source = r#"# the project
+project(demo)
+cmake_minimum_required(VERSION 3.20)
+include_directories(include)
+set(CMAKE_CXX_FLAGS "${CMAKE_CXX_FLAGS} -Wall")
+string(APPEND CMAKE_C_FLAGS_DEBUG " -O0")
+set(CMAKE_CXX_STANDARD 17)
+file(GLOB SOURCES src/*.cpp)
+file(GLOB_RECURSE HEADERS CONFIGURE_DEPENDS include/*.h)
+if(WIN32)
+  target_link_libraries(demo ws2_32)
+endif()
+target_link_libraries(demo PRIVATE fmt)
+"#;
+        assert_eq!(
+            check(source),
+            vec![
+                ("directory-scope-command", 3),
+                ("cmake-flags-edit", 4),
+                ("cmake-flags-edit", 5),
+                ("glob-without-configure-depends", 7),
+                ("target-link-without-scope", 10),
+                ("cmake-minimum-required", 2),
+            ]
+        );
+        assert_eq!(
+            check("project(demo)\n"),
+            vec![("cmake-minimum-required", 0)]
+        );
+        assert!(check("cmake_minimum_required(VERSION 3.20)\nproject(demo)\n").is_empty());
+    }
+
+    #[test]
+    fn tst_is_cmake_flags() {
+        assert!(is_cmake_flags("CMAKE_CXX_FLAGS"));
+        assert!(is_cmake_flags("CMAKE_C_FLAGS_RELEASE"));
+        assert!(!is_cmake_flags("CMAKE_CXX_FLAGS_"));
+        assert!(!is_cmake_flags("CMAKE_CXX_STANDARD"));
+    }
+}
@@ -0,0 +1,428 @@
+/// the lint rules, every diagnostic belongs to one rule, which can be re-leveled or disabled
+use std::collections::{HashMap, HashSet};
+
+use tree_sitter::{Node, Point};
+
+use super::ErrorInformation;
+use crate::config::{CMakeLintConfig, RuleLevel};
+use crate::CMakeNodeKinds;
+
+const RULES_DOC_URL: &str =
+    "https://github.com/neocmakelsp/neocmakelsp/blob/master/docs/lint_rules.md";
+
+/// the comment used to disable the rules, such as `# neocmakelsp: disable=command-case`
+const DISABLE_COMMENT_PREFIX: &str = "neocmakelsp:";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LintRule {
+    /// the stable id, used in the config, the disable comments and the diagnostic code
+    pub id: &'static str,
+    /// the level used when it is not set in the config
+    pub level: RuleLevel,
+    pub description: &'static str,
+}
+
+impl LintRule {
+    pub fn doc_url(&self) -> String {
+        format!("{RULES_DOC_URL}#{}", self.id)
+    }
+
+    /// create the diagnostic with the default level of the rule
+    pub fn diagnostic(
+        &self,
+        start_point: Point,
+        end_point: Point,
+        message: String,
+    ) -> ErrorInformation {
+        ErrorInformation {
+            start_point,
+            end_point,
+            message,
+            severity: self.level.severity(),
+            fix: None,
+            rule: Some(self.id),
+        }
+    }
+}
+
+pub const SYNTAX_ERROR: LintRule = LintRule {
+    id: "syntax-error",
+    level: RuleLevel::Error,
+    description: "The file cannot be parsed",
+};
+
+pub const COMMAND_CASE: LintRule = LintRule {
+    id: "command-case",
+    level: RuleLevel::Hint,
+    description: "The command name does not follow `command_upcase`",
+};
+
+pub const MISSING_PACKAGE: LintRule = LintRule {
+    id: "missing-package",
+    level: RuleLevel::Error,
+    description: "The package of find_package is not found in CMakeCache.txt",
+};
+
+pub const EMPTY_ARGUMENT: LintRule = LintRule {
+    id: "empty-argument",
+    level: RuleLevel::Error,
+    description: "The path of include or add_subdirectory is empty",
+};
+
+pub const INCLUDE_ERROR: LintRule = LintRule {
+    id: "include-error",
+    level: RuleLevel::Error,
+    description: "The included file cannot be parsed",
+};
+
+pub const INCLUDE_DIRECTORY: LintRule = LintRule {
+    id: "include-directory",
+    level: RuleLevel::Error,
+    description: "include is used with a directory",
+};
+
+pub const MISSING_FILE: LintRule = LintRule {
+    id: "missing-file",
+    level: RuleLevel::Warning,
+    description: "The file of include or the directory of add_subdirectory does not exist",
+};
+
+pub const LINE_TOO_LONG: LintRule = LintRule {
+    id: "line-too-long",
+    level: RuleLevel::Information,
+    description: "The line is longer than `max_line_length`",
+};
+
+pub const TRAILING_WHITESPACE: LintRule = LintRule {
+    id: "trailing-whitespace",
+    level: RuleLevel::Information,
+    description: "There are spaces or tabs at the end of the line",
+};
+
+pub const MIXED_INDENTATION: LintRule = LintRule {
+    id: "mixed-indentation",
+    level: RuleLevel::Warning,
+    description: "The indentation uses both tabs and spaces",
+};
+
+pub const TOO_MANY_ARGUMENTS: LintRule = LintRule {
+    id: "too-many-arguments",
+    level: RuleLevel::Information,
+    description: "The function or macro has more named arguments than `max_arguments`",
+};
+
+pub const INVALID_FUNCTION_NAME: LintRule = LintRule {
+    id: "invalid-function-name",
+    level: RuleLevel::Information,
+    description: "The function name does not match `function_name_pattern`",
+};
+
+pub const INVALID_CONDITION: LintRule = LintRule {
+    id: "invalid-condition",
+    level: RuleLevel::Error,
+    description: "The condition of if, elseif or while cannot be evaluated",
+};
+
+pub const CONDITION_VARIABLE_EXPANSION: LintRule = LintRule {
+    id: "condition-variable-expansion",
+    level: RuleLevel::Warning,
+    description:
+        "The variable is expanded in the condition, and its value is used as a variable name",
+};
+
+pub const UNKNOWN_KEYWORD: LintRule = LintRule {
+    id: "unknown-keyword",
+    level: RuleLevel::Warning,
+    description: "The argument looks like a misspelled keyword of the buildin command",
+};
+
+pub const MISSING_ARGUMENT: LintRule = LintRule {
+    id: "missing-argument",
+    level: RuleLevel::Warning,
+    description: "The buildin command has fewer arguments than its signatures need",
+};
+
+pub const UNBALANCED_GENEX: LintRule = LintRule {
+    id: "unbalanced-genex",
+    level: RuleLevel::Error,
+    description: "The generator expression is not closed by \">\"",
+};
+
+pub const UNKNOWN_GENEX: LintRule = LintRule {
+    id: "unknown-genex",
+    level: RuleLevel::Warning,
+    description: "The generator expression is not known by CMake",
+};
+
+/// the results of the external cmake-lint, the severity is given by cmake-lint
+pub const CMAKE_LINT: LintRule = LintRule {
+    id: "cmake-lint",
+    level: RuleLevel::Information,
+    description: "Reported by the external cmake-lint",
+};
+
+pub const RULES: &[LintRule] = &[
+    SYNTAX_ERROR,
+    COMMAND_CASE,
+    MISSING_PACKAGE,
+    EMPTY_ARGUMENT,
+    INCLUDE_ERROR,
+    INCLUDE_DIRECTORY,
+    MISSING_FILE,
+    LINE_TOO_LONG,
+    TRAILING_WHITESPACE,
+    MIXED_INDENTATION,
+    TOO_MANY_ARGUMENTS,
+    INVALID_FUNCTION_NAME,
+    INVALID_CONDITION,
+    CONDITION_VARIABLE_EXPANSION,
+    UNKNOWN_KEYWORD,
+    MISSING_ARGUMENT,
+    UNBALANCED_GENEX,
+    UNKNOWN_GENEX,
+    CMAKE_LINT,
+];
+
+pub const DIRECTORY_SCOPE_COMMAND: LintRule = LintRule {
+    id: "directory-scope-command",
+    level: RuleLevel::Warning,
+    description: "The command affects all the targets in the directory, use the target_* one",
+};
+
+pub const CMAKE_FLAGS_EDIT: LintRule = LintRule {
+    id: "cmake-flags-edit",
+    level: RuleLevel::Warning,
+    description: "CMAKE_CXX_FLAGS or CMAKE_C_FLAGS is edited, use target_compile_options",
+};
+
+pub const TARGET_LINK_WITHOUT_SCOPE: LintRule = LintRule {
+    id: "target-link-without-scope",
+    level: RuleLevel::Warning,
+    description: "target_link_libraries is used without PUBLIC, PRIVATE or INTERFACE",
+};
+
+pub const GLOB_WITHOUT_CONFIGURE_DEPENDS: LintRule = LintRule {
+    id: "glob-without-configure-depends",
+    level: RuleLevel::Warning,
+    description: "file(GLOB) is used without CONFIGURE_DEPENDS",
+};
+
+pub const CMAKE_MINIMUM_REQUIRED: LintRule = LintRule {
+    id: "cmake-minimum-required",
+    level: RuleLevel::Warning,
+    description: "cmake_minimum_required is missing or not the first command",
+};
+
+/// the modern cmake rules, only checked when `enable_modern_cmake_lint` is true
+/// or the rule is set in `[rules]`
+pub const MODERN_CMAKE_RULES: &[LintRule] = &[
+    DIRECTORY_SCOPE_COMMAND,
+    CMAKE_FLAGS_EDIT,
+    TARGET_LINK_WITHOUT_SCOPE,
+    GLOB_WITHOUT_CONFIGURE_DEPENDS,
+    CMAKE_MINIMUM_REQUIRED,
+];
+
+/// all the rules, including the modern cmake ones
+pub fn all_rules() -> impl Iterator<Item = &'static LintRule> {
+    RULES.iter().chain(MODERN_CMAKE_RULES.iter())
+}
+
+pub fn find_rule(id: &str) -> Option<&'static LintRule> {
+    all_rules().find(|rule| rule.id == id)
+}
+
+// whether the rule is reported when it is not set in `[rules]`
+fn is_default_enabled(id: &str, config: &CMakeLintConfig) -> bool {
+    if MODERN_CMAKE_RULES.iter().any(|rule| rule.id == id) {
+        return config.enable_modern_cmake_lint;
+    }
+    find_rule(id).is_none_or(|rule| rule.level != RuleLevel::Off)
+}
+
+// the rule ids in the comment, None if it is not a disable comment
+fn parse_disable_comment(comment: &str) -> Option<Vec<&str>> {
+    let comment = comment.trim_start_matches('#').trim();
+    let ids = comment
+        .strip_prefix(DISABLE_COMMENT_PREFIX)?
+        .trim()
+        .strip_prefix("disable=")?;
+    Some(
+        ids.split(',')
+            .map(|id| id.trim())
+            .filter(|id| !id.is_empty())
+            .collect(),
+    )
+}
+
+fn collect_nodes<'a>(input: Node<'a>, comments: &mut Vec<Node<'a>>, commands: &mut Vec<Node<'a>>) {
+    let mut course = input.walk();
+    for child in input.children(&mut course) {
+        match child.kind() {
+            CMakeNodeKinds::LINE_COMMENT => comments.push(child),
+            kind if kind.ends_with("_command") => commands.push(child),
+            _ => collect_nodes(child, comments, commands),
+        }
+    }
+}
+
+/// the disabled rules of every row
+/// the comment after a command disables the rules in its line,
+/// the comment in its own line disables the rules in the next command
+fn get_disabled_rules(root: Node, source: &[&str]) -> HashMap<usize, HashSet<String>> {
+    let mut comments = vec![];
+    let mut commands = vec![];
+    collect_nodes(root, &mut comments, &mut commands);
+    let mut disabled: HashMap<usize, HashSet<String>> = HashMap::new();
+    for comment in comments {
+        let start = comment.start_position();
+        let Some(line) = source.get(start.row) else {
+            continue;
+        };
+        let Some(ids) = line.get(start.column..).and_then(parse_disable_comment) else {
+            continue;
+        };
+        let rows = if line[..start.column].trim().is_empty() {
+            let Some(command) = commands
+                .iter()
+                .find(|command| command.start_position().row > start.row)
+            else {
+                continue;
+            };
+            command.start_position().row..=command.end_position().row
+        } else {
+            start.row..=start.row
+        };
+        for row in rows {
+            disabled
+                .entry(row)
+                .or_default()
+                .extend(ids.iter().map(|id| id.to_string()));
+        }
+    }
+    disabled
+}
+
+/// apply the levels in the config and the disable comments to the diagnostics
+pub fn apply_rules(
+    diagnostics: Vec<ErrorInformation>,
+    config: &CMakeLintConfig,
+    root: Node,
+    source: &[&str],
+) -> Vec<ErrorInformation> {
+    let disabled = get_disabled_rules(root, source);
+    diagnostics
+        .into_iter()
+        .filter_map(|mut diagnostic| {
+            let Some(id) = diagnostic.rule else {
+                return Some(diagnostic);
+            };
+            if disabled
+                .get(&diagnostic.start_point.row)
+                .is_some_and(|ids| ids.contains(id))
+            {
+                return None;
+            }
+            match config.rules.get(id) {
+                Some(RuleLevel::Off) => return None,
+                Some(level) => diagnostic.severity = level.severity(),
+                None if !is_default_enabled(id, config) => return None,
+                None => {}
+            }
+            Some(diagnostic)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod rules_test {
+    use super::*;
+    use crate::consts::TREESITTER_CMAKE_LANGUAGE;
+    use tower_lsp::lsp_types::DiagnosticSeverity;
+
+    #[test]
+    fn tst_rule_ids() {
+        let ids: HashSet<&str> = all_rules().map(|rule| rule.id).collect();
+        assert_eq!(ids.len(), RULES.len() + MODERN_CMAKE_RULES.len());
+        assert_eq!(find_rule("missing-file"), Some(&MISSING_FILE));
+        assert_eq!(
+            COMMAND_CASE.doc_url(),
+            format!("{RULES_DOC_URL}#command-case")
+        );
+    }
+
+    #[test]
+    fn tst_parse_disable_comment() {
+        assert_eq!(
+            parse_disable_comment("# neocmakelsp: disable=command-case, missing-file"),
+            Some(vec!["command-case", "missing-file"])
+        );
+        assert_eq!(parse_disable_comment("# some comment"), None);
+    }
+
+    #[test]
+    fn tst_apply_rules() {
+        let source = r#"SET(A 1) # neocmakelsp: disable=command-case
+# neocmakelsp: disable=missing-file
+include(
+  a.cmake)
+SET(B 1)
+"#;
+        let mut parse = tree_sitter::Parser::new();
+        parse.set_language(&TREESITTER_CMAKE_LANGUAGE).unwrap();
+        let tree = parse.parse(source, None).unwrap();
+        let lines: Vec<&str> = source.lines().collect();
+        let point = |row| Point { row, column: 0 };
+        let diagnostics = vec![
+            COMMAND_CASE.diagnostic(point(0), point(0), "case".to_string()),
+            MISSING_FILE.diagnostic(point(3), point(3), "missing".to_string()),
+            COMMAND_CASE.diagnostic(point(4), point(4), "case".to_string()),
+        ];
+        let config_with_rules = |rules: &[(&str, RuleLevel)]| CMakeLintConfig {
+            rules: rules
+                .iter()
+                .map(|(id, level)| (id.to_string(), *level))
+                .collect(),
+            ..Default::default()
+        };
+        let result = apply_rules(
+            diagnostics.clone(),
+            &config_with_rules(&[]),
+            tree.root_node(),
+            &lines,
+        );
+        assert_eq!(result, vec![diagnostics[2].clone()]);
+
+        let config = config_with_rules(&[("command-case", RuleLevel::Warning)]);
+        let result = apply_rules(diagnostics.clone(), &config, tree.root_node(), &lines);
+        assert_eq!(result[0].severity, Some(DiagnosticSeverity::WARNING));
+
+        let config = config_with_rules(&[("command-case", RuleLevel::Off)]);
+        assert!(apply_rules(diagnostics, &config, tree.root_node(), &lines).is_empty());
+    }
+
+    #[test]
+    fn tst_modern_cmake_rules() {
+        let source = "include_directories(include)\n";
+        let mut parse = tree_sitter::Parser::new();
+        parse.set_language(&TREESITTER_CMAKE_LANGUAGE).unwrap();
+        let tree = parse.parse(source, None).unwrap();
+        let lines: Vec<&str> = source.lines().collect();
+        let point = Point { row: 0, column: 0 };
+        let diagnostics = vec![DIRECTORY_SCOPE_COMMAND.diagnostic(point, point, "dir".to_string())];
+        let apply = |config: &CMakeLintConfig| {
+            apply_rules(diagnostics.clone(), config, tree.root_node(), &lines)
+        };
+        assert!(apply(&CMakeLintConfig::default()).is_empty());
+        let config = CMakeLintConfig {
+            enable_modern_cmake_lint: true,
+            ..Default::default()
+        };
+        assert_eq!(apply(&config), diagnostics);
+        let config = CMakeLintConfig {
+            rules: HashMap::from([("directory-scope-command".to_string(), RuleLevel::Error)]),
+            ..Default::default()
+        };
+        assert_eq!(apply(&config)[0].severity, Some(DiagnosticSeverity::ERROR));
+    }
+}
@@ -0,0 +1,218 @@
+/// the style checks, which are done by the external cmake-lint before
+use std::collections::HashSet;
+
+use tree_sitter::{Node, Point};
+
+use super::{rules, ErrorInformation};
+use crate::code_action::QuickFix;
+use crate::utils::treehelper::{command_arguments, node_text};
+use crate::CMakeNodeKinds;
+
+/// the kinds whose content can take more than one line, the lines inside are kept as they are
+const MULTILINE_TEXT: &[&str] = &[
+    CMakeNodeKinds::QUOTED_ARGUMENT,
+    CMakeNodeKinds::BRACKET_ARGUMENT,
+    CMakeNodeKinds::BRACKET_COMMENT,
+];
+
+pub struct StyleConfig<'a> {
+    pub max_line_length: usize,
+    /// the max count of the named arguments of the function or macro
+    pub max_arguments: usize,
+    /// the whole function name should match it
+    pub function_name_pattern: &'a str,
+}
+
+// the rows which are inside the multiline strings or comments, except the first one
+fn collect_text_rows(input: Node, rows: &mut HashSet<usize>) {
+    let mut course = input.walk();
+    for child in input.children(&mut course) {
+        if MULTILINE_TEXT.contains(&child.kind()) {
+            rows.extend(child.start_position().row + 1..=child.end_position().row);
+            continue;
+        }
+        collect_text_rows(child, rows);
+    }
+}
+
+fn check_lines(source: &[&str], config: &StyleConfig, root: Node) -> Vec<ErrorInformation> {
+    let mut text_rows = HashSet::new();
+    collect_text_rows(root, &mut text_rows);
+    let mut output = vec![];
+    // the first indent char of the file, the others should be the same
+    let mut indent_char = None;
+    for (row, line) in source.iter().enumerate() {
+        if let Some((column, _)) = line.char_indices().nth(config.max_line_length) {
+            output.push(rules::LINE_TOO_LONG.diagnostic(
+                Point { row, column },
+                Point {
+                    row,
+                    column: line.len(),
+                },
+                format!(
+                    "Line too long ({}/{})",
+                    line.chars().count(),
+                    config.max_line_length
+                ),
+            ));
+        }
+        let trimmed = line.trim_end();
+        // NOTE: the spaces are part of the string if the string goes on in the next line
+        if trimmed.len() != line.len() && !text_rows.contains(&(row + 1)) {
+            output.push(ErrorInformation {
+                fix: Some(QuickFix::RemoveTrailingWhitespace),
+                ..rules::TRAILING_WHITESPACE.diagnostic(
+                    Point {
+                        row,
+                        column: trimmed.len(),
+                    },
+                    Point {
+                        row,
+                        column: line.len(),
+                    },
+                    "Trailing whitespace".to_string(),
+                )
+            });
+        }
+        if text_rows.contains(&row) || trimmed.is_empty() {
+            continue;
+        }
+        let indent = &line[..line.len() - line.trim_start().len()];
+        let Some(first) = indent.chars().next() else {
+            continue;
+        };
+        let expected = *indent_char.get_or_insert(first);
+        if indent.chars().any(|c| c != expected) {
+            output.push(rules::MIXED_INDENTATION.diagnostic(
+                Point { row, column: 0 },
+                Point {
+                    row,
+                    column: indent.len(),
+                },
+                "Mixed tabs and spaces in indentation".to_string(),
+            ));
+        }
+    }
+    output
+}
+
+struct DefinitionChecker<'a> {
+    source: &'a [&'a str],
+    max_arguments: usize,
+    function_name_pattern: &'a str,
+    /// None if the pattern in the config is invalid
+    function_name_re: Option<regex::Regex>,
+}
+
+impl DefinitionChecker<'_> {
+    fn check_name(&self, name_node: &Node, output: &mut Vec<ErrorInformation>) {
+        let (Some(name), Some(re)) = (node_text(self.source, name_node), &self.function_name_re)
+        else {
+            return;
+        };
+        if !re.is_match(name) {
+            output.push(rules::INVALID_FUNCTION_NAME.diagnostic(
+                name_node.start_position(),
+                name_node.end_position(),
+                format!(
+                    "Invalid function name \"{name}\", it should match \"{}\"",
+                    self.function_name_pattern
+                ),
+            ));
+        }
+    }
+
+    fn check_definitions(&self, input: Node, output: &mut Vec<ErrorInformation>) {
+        let mut course = input.walk();
+        for child in input.children(&mut course) {
+            if !matches!(
+                child.kind(),
+                CMakeNodeKinds::FUNCTION_DEF | CMakeNodeKinds::MACRO_DEF
+            ) {
+                self.check_definitions(child, output);
+                continue;
+            }
+            if let Some(command) = child.child(0) {
+                let arguments = command_arguments(command);
+                if let Some(name_node) = arguments.first() {
+                    if child.kind() == CMakeNodeKinds::FUNCTION_DEF {
+                        self.check_name(name_node, output);
+                    }
+                }
+                let argument_count = arguments.len().saturating_sub(1);
+                if argument_count > self.max_arguments {
+                    output.push(rules::TOO_MANY_ARGUMENTS.diagnostic(
+                        arguments[self.max_arguments + 1].start_position(),
+                        arguments[arguments.len() - 1].end_position(),
+                        format!(
+                            "Too many named arguments ({argument_count}/{})",
+                            self.max_arguments
+                        ),
+                    ));
+                }
+            }
+            self.check_definitions(child, output);
+        }
+    }
+}
+
+/// check the lines, and the definitions of the functions and macros
+pub fn check_style(source: &[&str], root: Node, config: &StyleConfig) -> Vec<ErrorInformation> {
+    let mut output = check_lines(source, config, root);
+    let function_name_re =
+        match regex::Regex::new(&format!("^(?:{})$", config.function_name_pattern)) {
+            Ok(re) => Some(re),
+            Err(e) => {
+                tracing::warn!("Invalid function_name_pattern: {e}");
+                None
+            }
+        };
+    let checker = DefinitionChecker {
+        source,
+        max_arguments: config.max_arguments,
+        function_name_pattern: config.function_name_pattern,
+        function_name_re,
+    };
+    checker.check_definitions(root, &mut output);
+    output
+}
+
+#[cfg(test)]
+mod style_test {
+    use super::*;
+    use crate::consts::TREESITTER_CMAKE_LANGUAGE;
+
+    #[test]
+    fn tst_check_style() {
+        let source = "function(MyFunc a b c d e f)\n\tset(A 1)   \n  \tset(B 2)\nendfunction()\nset(LONG \"this line is longer than forty characters\")\nset(TEXT \"a  \n  b\")\n";
+        let mut parse = tree_sitter::Parser::new();
+        parse.set_language(&TREESITTER_CMAKE_LANGUAGE).unwrap();
+        let tree = parse.parse(source, None).unwrap();
+        let lines: Vec<&str> = source.lines().collect();
+        let config = StyleConfig {
+            max_line_length: 40,
+            max_arguments: 5,
+            function_name_pattern: "[0-9a-z_]+",
+        };
+        let result: Vec<(&str, usize, usize)> = check_style(&lines, tree.root_node(), &config)
+            .iter()
+            .map(|info| {
+                (
+                    info.rule.unwrap(),
+                    info.start_point.row,
+                    info.start_point.column,
+                )
+            })
+            .collect();
+        assert_eq!(
+            result,
+            vec![
+                ("trailing-whitespace", 1, 9),
+                ("mixed-indentation", 2, 0),
+                ("line-too-long", 4, 40),
+                ("invalid-function-name", 0, 9),
+                ("too-many-arguments", 0, 26),
+            ]
+        );
+    }
+}
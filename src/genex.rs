@@ -0,0 +1,337 @@
+/// The generator expressions, such as $<TARGET_FILE:tgt>, their documents and the parser
+use tower_lsp::lsp_types::{CompletionItem, CompletionItemKind, Documentation};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GenexInfo {
+    pub name: &'static str,
+    pub signature: &'static str,
+    pub description: &'static str,
+}
+
+impl GenexInfo {
+    pub fn document(&self) -> String {
+        format!("```cmake\n{}\n```\n\n{}", self.signature, self.description)
+    }
+}
+
+macro_rules! genex {
+    ($name:literal, $signature:literal, $description:literal) => {
+        GenexInfo {
+            name: $name,
+            signature: $signature,
+            description: $description,
+        }
+    };
+}
+
+pub const GENEXES: &[GenexInfo] = &[
+    // conditional expressions
+    genex!("0", "$<0:...>", "Evaluates to an empty string."),
+    genex!("1", "$<1:...>", "Evaluates to the content."),
+    genex!("IF", "$<IF:condition,true_string,false_string>", "Evaluates to true_string if condition is 1, or false_string if condition is 0."),
+    // logical operators
+    genex!("BOOL", "$<BOOL:string>", "Converts string to 0 or 1, with the same rule as if()."),
+    genex!("AND", "$<AND:conditions>", "1 if all the conditions are 1, the conditions are separated by commas."),
+    genex!("OR", "$<OR:conditions>", "1 if any of the conditions is 1, the conditions are separated by commas."),
+    genex!("NOT", "$<NOT:condition>", "0 if condition is 1, or 1 if condition is 0."),
+    // string comparisons
+    genex!("STREQUAL", "$<STREQUAL:string1,string2>", "1 if string1 and string2 are equal, case sensitive."),
+    genex!("STRLESS", "$<STRLESS:string1,string2>", "1 if string1 is lexicographically less than string2."),
+    genex!("STRLESS_EQUAL", "$<STRLESS_EQUAL:string1,string2>", "1 if string1 is lexicographically less than or equal to string2."),
+    genex!("STRGREATER", "$<STRGREATER:string1,string2>", "1 if string1 is lexicographically greater than string2."),
+    genex!("STRGREATER_EQUAL", "$<STRGREATER_EQUAL:string1,string2>", "1 if string1 is lexicographically greater than or equal to string2."),
+    genex!("EQUAL", "$<EQUAL:value1,value2>", "1 if value1 and value2 are numerically equal."),
+    genex!("IN_LIST", "$<IN_LIST:string,list>", "1 if string is an item of the semicolon separated list."),
+    genex!("VERSION_LESS", "$<VERSION_LESS:v1,v2>", "1 if v1 is a version less than v2."),
+    genex!("VERSION_GREATER", "$<VERSION_GREATER:v1,v2>", "1 if v1 is a version greater than v2."),
+    genex!("VERSION_EQUAL", "$<VERSION_EQUAL:v1,v2>", "1 if v1 is the same version as v2."),
+    genex!("VERSION_LESS_EQUAL", "$<VERSION_LESS_EQUAL:v1,v2>", "1 if v1 is a version less than or equal to v2."),
+    genex!("VERSION_GREATER_EQUAL", "$<VERSION_GREATER_EQUAL:v1,v2>", "1 if v1 is a version greater than or equal to v2."),
+    // string transformations
+    genex!("LOWER_CASE", "$<LOWER_CASE:string>", "Content of string converted to lower case."),
+    genex!("UPPER_CASE", "$<UPPER_CASE:string>", "Content of string converted to upper case."),
+    genex!("MAKE_C_IDENTIFIER", "$<MAKE_C_IDENTIFIER:string>", "Content of string converted to a C identifier."),
+    genex!("STRING", "$<STRING:OPERATION,string,...>", "Returns the result of the string operation, such as LENGTH, SUBSTRING, FIND or REPLACE."),
+    // list and path expressions
+    genex!("LIST", "$<LIST:OPERATION,list,...>", "Returns the result of the list operation, such as LENGTH, GET, APPEND, FILTER or SORT."),
+    genex!("JOIN", "$<JOIN:list,string>", "Joins the items of list with string."),
+    genex!("REMOVE_DUPLICATES", "$<REMOVE_DUPLICATES:list>", "Removes the duplicated items of list."),
+    genex!("FILTER", "$<FILTER:list,INCLUDE|EXCLUDE,regex>", "Includes or removes the items of list that match regex."),
+    genex!("PATH", "$<PATH:OPERATION,path,...>", "Returns the result of the path operation, such as GET_FILENAME, HAS_ROOT_NAME or APPEND."),
+    genex!("PATH_EQUAL", "$<PATH_EQUAL:path1,path2>", "1 if the two paths are equal, compared component by component."),
+    genex!("SHELL_PATH", "$<SHELL_PATH:...>", "Content converted to the shell path style of the host."),
+    // configuration
+    genex!("CONFIG", "$<CONFIG:cfgs>", "1 if the configuration is any one of the comma separated cfgs, or the configuration name if there are no arguments."),
+    genex!("CONFIGURATION", "$<CONFIGURATION>", "Configuration name, it is deprecated, use $<CONFIG> instead."),
+    genex!("OUTPUT_CONFIG", "$<OUTPUT_CONFIG:...>", "Evaluates the content with the output configuration of the custom command."),
+    genex!("COMMAND_CONFIG", "$<COMMAND_CONFIG:...>", "Evaluates the content with the command configuration of the custom command."),
+    // toolchain and language
+    genex!("PLATFORM_ID", "$<PLATFORM_ID:platform_ids>", "1 if the platform is any one of platform_ids, or the platform id if there are no arguments."),
+    genex!("C_COMPILER_ID", "$<C_COMPILER_ID:compiler_ids>", "1 if the C compiler is any one of compiler_ids, or the compiler id if there are no arguments."),
+    genex!("CXX_COMPILER_ID", "$<CXX_COMPILER_ID:compiler_ids>", "1 if the CXX compiler is any one of compiler_ids, or the compiler id if there are no arguments."),
+    genex!("CUDA_COMPILER_ID", "$<CUDA_COMPILER_ID:compiler_ids>", "1 if the CUDA compiler is any one of compiler_ids, or the compiler id if there are no arguments."),
+    genex!("OBJC_COMPILER_ID", "$<OBJC_COMPILER_ID:compiler_ids>", "1 if the Objective-C compiler is any one of compiler_ids, or the compiler id if there are no arguments."),
+    genex!("OBJCXX_COMPILER_ID", "$<OBJCXX_COMPILER_ID:compiler_ids>", "1 if the Objective-C++ compiler is any one of compiler_ids, or the compiler id if there are no arguments."),
+    genex!("Fortran_COMPILER_ID", "$<Fortran_COMPILER_ID:compiler_ids>", "1 if the Fortran compiler is any one of compiler_ids, or the compiler id if there are no arguments."),
+    genex!("HIP_COMPILER_ID", "$<HIP_COMPILER_ID:compiler_ids>", "1 if the HIP compiler is any one of compiler_ids, or the compiler id if there are no arguments."),
+    genex!("ISPC_COMPILER_ID", "$<ISPC_COMPILER_ID:compiler_ids>", "1 if the ISPC compiler is any one of compiler_ids, or the compiler id if there are no arguments."),
+    genex!("C_COMPILER_VERSION", "$<C_COMPILER_VERSION:version>", "1 if the version of the C compiler matches version, or the version if there are no arguments."),
+    genex!("CXX_COMPILER_VERSION", "$<CXX_COMPILER_VERSION:version>", "1 if the version of the CXX compiler matches version, or the version if there are no arguments."),
+    genex!("CUDA_COMPILER_VERSION", "$<CUDA_COMPILER_VERSION:version>", "1 if the version of the CUDA compiler matches version, or the version if there are no arguments."),
+    genex!("OBJC_COMPILER_VERSION", "$<OBJC_COMPILER_VERSION:version>", "1 if the version of the Objective-C compiler matches version, or the version if there are no arguments."),
+    genex!("OBJCXX_COMPILER_VERSION", "$<OBJCXX_COMPILER_VERSION:version>", "1 if the version of the Objective-C++ compiler matches version, or the version if there are no arguments."),
+    genex!("Fortran_COMPILER_VERSION", "$<Fortran_COMPILER_VERSION:version>", "1 if the version of the Fortran compiler matches version, or the version if there are no arguments."),
+    genex!("HIP_COMPILER_VERSION", "$<HIP_COMPILER_VERSION:version>", "1 if the version of the HIP compiler matches version, or the version if there are no arguments."),
+    genex!("ISPC_COMPILER_VERSION", "$<ISPC_COMPILER_VERSION:version>", "1 if the version of the ISPC compiler matches version, or the version if there are no arguments."),
+    genex!("C_COMPILER_FRONTEND_VARIANT", "$<C_COMPILER_FRONTEND_VARIANT:ids>", "1 if the frontend variant of the C compiler is any one of ids, or the variant if there are no arguments."),
+    genex!("CXX_COMPILER_FRONTEND_VARIANT", "$<CXX_COMPILER_FRONTEND_VARIANT:ids>", "1 if the frontend variant of the CXX compiler is any one of ids, or the variant if there are no arguments."),
+    genex!("CUDA_COMPILER_FRONTEND_VARIANT", "$<CUDA_COMPILER_FRONTEND_VARIANT:ids>", "1 if the frontend variant of the CUDA compiler is any one of ids, or the variant if there are no arguments."),
+    genex!("OBJC_COMPILER_FRONTEND_VARIANT", "$<OBJC_COMPILER_FRONTEND_VARIANT:ids>", "1 if the frontend variant of the Objective-C compiler is any one of ids, or the variant if there are no arguments."),
+    genex!("OBJCXX_COMPILER_FRONTEND_VARIANT", "$<OBJCXX_COMPILER_FRONTEND_VARIANT:ids>", "1 if the frontend variant of the Objective-C++ compiler is any one of ids, or the variant if there are no arguments."),
+    genex!("Fortran_COMPILER_FRONTEND_VARIANT", "$<Fortran_COMPILER_FRONTEND_VARIANT:ids>", "1 if the frontend variant of the Fortran compiler is any one of ids, or the variant if there are no arguments."),
+    genex!("HIP_COMPILER_FRONTEND_VARIANT", "$<HIP_COMPILER_FRONTEND_VARIANT:ids>", "1 if the frontend variant of the HIP compiler is any one of ids, or the variant if there are no arguments."),
+    genex!("COMPILE_FEATURES", "$<COMPILE_FEATURES:features>", "1 if all the features are available for the head target."),
+    genex!("COMPILE_LANG_AND_ID", "$<COMPILE_LANG_AND_ID:language,compiler_ids>", "1 if the language of the compilation unit is language and the compiler is any one of compiler_ids."),
+    genex!("COMPILE_LANGUAGE", "$<COMPILE_LANGUAGE:languages>", "1 if the language of the compilation unit is any one of languages, or the language if there are no arguments."),
+    genex!("LINK_LANG_AND_ID", "$<LINK_LANG_AND_ID:language,compiler_ids>", "1 if the link language is language and the compiler is any one of compiler_ids."),
+    genex!("LINK_LANGUAGE", "$<LINK_LANGUAGE:languages>", "1 if the link language is any one of languages, or the link language if there are no arguments."),
+    genex!("DEVICE_LINK", "$<DEVICE_LINK:list>", "list if it is a device link step, or empty."),
+    genex!("HOST_LINK", "$<HOST_LINK:list>", "list if it is a normal link step, or empty."),
+    genex!("LINK_LIBRARY", "$<LINK_LIBRARY:feature,library-list>", "Links the libraries with the link feature, such as WHOLE_ARCHIVE."),
+    genex!("LINK_GROUP", "$<LINK_GROUP:feature,library-list>", "Links the libraries as a group, such as RESCAN."),
+    // target
+    genex!("TARGET_EXISTS", "$<TARGET_EXISTS:tgt>", "1 if tgt exists as a target."),
+    genex!("TARGET_NAME_IF_EXISTS", "$<TARGET_NAME_IF_EXISTS:tgt>", "The target name if tgt exists, or an empty string."),
+    genex!("TARGET_NAME", "$<TARGET_NAME:...>", "Marks ... as the name of a target when exporting, it is deprecated."),
+    genex!("TARGET_PROPERTY", "$<TARGET_PROPERTY:tgt,prop>", "Value of the property prop of the target tgt, or the head target if tgt is omitted."),
+    genex!("TARGET_OBJECTS", "$<TARGET_OBJECTS:tgt>", "The list of the object files of the target tgt."),
+    genex!("TARGET_POLICY", "$<TARGET_POLICY:policy>", "1 if policy was NEW when the head target was created."),
+    genex!("TARGET_FILE", "$<TARGET_FILE:tgt>", "Full path to the main file of the target tgt, such as the executable or the library."),
+    genex!("TARGET_FILE_BASE_NAME", "$<TARGET_FILE_BASE_NAME:tgt>", "Base name of the main file of tgt, without the prefix and the suffix."),
+    genex!("TARGET_FILE_PREFIX", "$<TARGET_FILE_PREFIX:tgt>", "Prefix of the main file of tgt, such as lib."),
+    genex!("TARGET_FILE_SUFFIX", "$<TARGET_FILE_SUFFIX:tgt>", "Suffix of the main file of tgt, such as .so or .exe."),
+    genex!("TARGET_FILE_NAME", "$<TARGET_FILE_NAME:tgt>", "Name of the main file of tgt."),
+    genex!("TARGET_FILE_DIR", "$<TARGET_FILE_DIR:tgt>", "Directory of the main file of tgt."),
+    genex!("TARGET_IMPORT_FILE", "$<TARGET_IMPORT_FILE:tgt>", "Full path to the import file of tgt, such as the .lib of a dll."),
+    genex!("TARGET_IMPORT_FILE_BASE_NAME", "$<TARGET_IMPORT_FILE_BASE_NAME:tgt>", "Base name of the import file of tgt."),
+    genex!("TARGET_IMPORT_FILE_PREFIX", "$<TARGET_IMPORT_FILE_PREFIX:tgt>", "Prefix of the import file of tgt."),
+    genex!("TARGET_IMPORT_FILE_SUFFIX", "$<TARGET_IMPORT_FILE_SUFFIX:tgt>", "Suffix of the import file of tgt."),
+    genex!("TARGET_IMPORT_FILE_NAME", "$<TARGET_IMPORT_FILE_NAME:tgt>", "Name of the import file of tgt."),
+    genex!("TARGET_IMPORT_FILE_DIR", "$<TARGET_IMPORT_FILE_DIR:tgt>", "Directory of the import file of tgt."),
+    genex!("TARGET_LINKER_FILE", "$<TARGET_LINKER_FILE:tgt>", "Full path to the file used to link tgt."),
+    genex!("TARGET_LINKER_FILE_BASE_NAME", "$<TARGET_LINKER_FILE_BASE_NAME:tgt>", "Base name of the file used to link tgt."),
+    genex!("TARGET_LINKER_FILE_PREFIX", "$<TARGET_LINKER_FILE_PREFIX:tgt>", "Prefix of the file used to link tgt."),
+    genex!("TARGET_LINKER_FILE_SUFFIX", "$<TARGET_LINKER_FILE_SUFFIX:tgt>", "Suffix of the file used to link tgt."),
+    genex!("TARGET_LINKER_FILE_NAME", "$<TARGET_LINKER_FILE_NAME:tgt>", "Name of the file used to link tgt."),
+    genex!("TARGET_LINKER_FILE_DIR", "$<TARGET_LINKER_FILE_DIR:tgt>", "Directory of the file used to link tgt."),
+    genex!("TARGET_LINKER_LIBRARY_FILE", "$<TARGET_LINKER_LIBRARY_FILE:tgt>", "Full path to the library file used to link tgt."),
+    genex!("TARGET_LINKER_LIBRARY_FILE_BASE_NAME", "$<TARGET_LINKER_LIBRARY_FILE_BASE_NAME:tgt>", "Base name of the library file used to link tgt."),
+    genex!("TARGET_LINKER_LIBRARY_FILE_PREFIX", "$<TARGET_LINKER_LIBRARY_FILE_PREFIX:tgt>", "Prefix of the library file used to link tgt."),
+    genex!("TARGET_LINKER_LIBRARY_FILE_SUFFIX", "$<TARGET_LINKER_LIBRARY_FILE_SUFFIX:tgt>", "Suffix of the library file used to link tgt."),
+    genex!("TARGET_LINKER_LIBRARY_FILE_NAME", "$<TARGET_LINKER_LIBRARY_FILE_NAME:tgt>", "Name of the library file used to link tgt."),
+    genex!("TARGET_LINKER_LIBRARY_FILE_DIR", "$<TARGET_LINKER_LIBRARY_FILE_DIR:tgt>", "Directory of the library file used to link tgt."),
+    genex!("TARGET_LINKER_IMPORT_FILE", "$<TARGET_LINKER_IMPORT_FILE:tgt>", "Full path to the import file used to link tgt."),
+    genex!("TARGET_LINKER_IMPORT_FILE_BASE_NAME", "$<TARGET_LINKER_IMPORT_FILE_BASE_NAME:tgt>", "Base name of the import file used to link tgt."),
+    genex!("TARGET_LINKER_IMPORT_FILE_PREFIX", "$<TARGET_LINKER_IMPORT_FILE_PREFIX:tgt>", "Prefix of the import file used to link tgt."),
+    genex!("TARGET_LINKER_IMPORT_FILE_SUFFIX", "$<TARGET_LINKER_IMPORT_FILE_SUFFIX:tgt>", "Suffix of the import file used to link tgt."),
+    genex!("TARGET_LINKER_IMPORT_FILE_NAME", "$<TARGET_LINKER_IMPORT_FILE_NAME:tgt>", "Name of the import file used to link tgt."),
+    genex!("TARGET_LINKER_IMPORT_FILE_DIR", "$<TARGET_LINKER_IMPORT_FILE_DIR:tgt>", "Directory of the import file used to link tgt."),
+    genex!("TARGET_SONAME_FILE", "$<TARGET_SONAME_FILE:tgt>", "Full path to the file with the soname of tgt."),
+    genex!("TARGET_SONAME_FILE_NAME", "$<TARGET_SONAME_FILE_NAME:tgt>", "Name of the file with the soname of tgt."),
+    genex!("TARGET_SONAME_FILE_DIR", "$<TARGET_SONAME_FILE_DIR:tgt>", "Directory of the file with the soname of tgt."),
+    genex!("TARGET_PDB_FILE", "$<TARGET_PDB_FILE:tgt>", "Full path to the pdb file of tgt."),
+    genex!("TARGET_PDB_FILE_BASE_NAME", "$<TARGET_PDB_FILE_BASE_NAME:tgt>", "Base name of the pdb file of tgt."),
+    genex!("TARGET_PDB_FILE_NAME", "$<TARGET_PDB_FILE_NAME:tgt>", "Name of the pdb file of tgt."),
+    genex!("TARGET_PDB_FILE_DIR", "$<TARGET_PDB_FILE_DIR:tgt>", "Directory of the pdb file of tgt."),
+    genex!("TARGET_BUNDLE_DIR", "$<TARGET_BUNDLE_DIR:tgt>", "Full path to the bundle directory of tgt."),
+    genex!("TARGET_BUNDLE_DIR_NAME", "$<TARGET_BUNDLE_DIR_NAME:tgt>", "Name of the bundle directory of tgt."),
+    genex!("TARGET_BUNDLE_CONTENT_DIR", "$<TARGET_BUNDLE_CONTENT_DIR:tgt>", "Full path to the content directory of the bundle of tgt."),
+    genex!("TARGET_RUNTIME_DLLS", "$<TARGET_RUNTIME_DLLS:tgt>", "The list of the dlls that tgt depends on at runtime."),
+    genex!("TARGET_RUNTIME_DLL_DIRS", "$<TARGET_RUNTIME_DLL_DIRS:tgt>", "The list of the directories of the dlls that tgt depends on at runtime."),
+    // export and install
+    genex!("INSTALL_INTERFACE", "$<INSTALL_INTERFACE:...>", "Content of ... when the property is exported with install(EXPORT), or empty."),
+    genex!("BUILD_INTERFACE", "$<BUILD_INTERFACE:...>", "Content of ... when the property is used by another target in the same buildsystem, or empty."),
+    genex!("BUILD_LOCAL_INTERFACE", "$<BUILD_LOCAL_INTERFACE:...>", "Content of ... when it is used by another target in the same buildsystem, and not exported."),
+    genex!("INSTALL_PREFIX", "$<INSTALL_PREFIX>", "The install prefix when the target is exported with install(EXPORT)."),
+    genex!("LINK_ONLY", "$<LINK_ONLY:...>", "Content of ... when it is used for linking, the usage requirements are not propagated."),
+    genex!("COMPILE_ONLY", "$<COMPILE_ONLY:...>", "Content of ... when it is used for compiling, the library is not linked."),
+    // evaluation
+    genex!("GENEX_EVAL", "$<GENEX_EVAL:expr>", "Content of expr evaluated as a generator expression in the current context."),
+    genex!("TARGET_GENEX_EVAL", "$<TARGET_GENEX_EVAL:tgt,expr>", "Content of expr evaluated as a generator expression in the context of tgt."),
+    // escaped characters
+    genex!("ANGLE-R", "$<ANGLE-R>", "A literal >."),
+    genex!("COMMA", "$<COMMA>", "A literal ,."),
+    genex!("SEMICOLON", "$<SEMICOLON>", "A literal ;."),
+    genex!("QUOTE", "$<QUOTE>", "A literal \"."),
+];
+
+pub fn find_genex(name: &str) -> Option<&'static GenexInfo> {
+    GENEXES.iter().find(|genex| genex.name == name)
+}
+
+/// one generator expression in the text, the positions are the byte offsets
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GenexNode<'a> {
+    pub name: &'a str,
+    /// the position of "$<"
+    pub start: usize,
+    pub name_end: usize,
+    /// the position after ">", None if it is not closed
+    pub end: Option<usize>,
+}
+
+impl GenexNode<'_> {
+    /// the name is empty in $<$<CONFIG:Debug>:...> and $<${USE_X}:...>,
+    /// whose condition is a generator expression or a variable
+    pub fn has_nested_condition(&self, text: &str) -> bool {
+        let rest = &text[self.name_end..];
+        self.name.is_empty() && (rest.starts_with("$<") || rest.starts_with("${"))
+    }
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_' || c == '-'
+}
+
+/// get all the generator expressions in the text, the nested ones are included,
+/// the escaped "\$<" is not a generator expression
+pub fn parse_genexes(text: &str) -> Vec<GenexNode<'_>> {
+    let mut genexes = vec![];
+    let mut opened: Vec<usize> = vec![];
+    let mut index = 0;
+    while index < text.len() {
+        let rest = &text[index..];
+        if rest.starts_with("$<") && !text[..index].ends_with('\\') {
+            let name_start = index + 2;
+            let name_len = text[name_start..]
+                .find(|c| !is_name_char(c))
+                .unwrap_or(text.len() - name_start);
+            opened.push(genexes.len());
+            genexes.push(GenexNode {
+                name: &text[name_start..name_start + name_len],
+                start: index,
+                name_end: name_start + name_len,
+                end: None,
+            });
+            index = name_start + name_len;
+            continue;
+        }
+        if rest.starts_with('>') {
+            if let Some(genex_index) = opened.pop() {
+                genexes[genex_index].end = Some(index + 1);
+            }
+        }
+        index += rest.chars().next().map_or(1, char::len_utf8);
+    }
+    genexes
+}
+
+/// the ranges of "$<NAME" and ">" of all the generator expressions, sorted by the start
+pub fn highlight_ranges(text: &str) -> Vec<(usize, usize)> {
+    let mut ranges: Vec<(usize, usize)> = parse_genexes(text)
+        .into_iter()
+        .flat_map(|genex| {
+            let close = genex.end.map(|end| (end - 1, end));
+            std::iter::once((genex.start, genex.name_end)).chain(close)
+        })
+        .collect();
+    ranges.sort_unstable();
+    ranges
+}
+
+/// the generator expression whose "$<" or name is at the column
+pub fn genex_at(line: &str, column: usize) -> Option<&'static GenexInfo> {
+    parse_genexes(line)
+        .into_iter()
+        .find(|genex| genex.start <= column && column < genex.name_end)
+        .and_then(|genex| find_genex(genex.name))
+}
+
+/// the typed name if the cursor is just after "$<"
+pub fn completion_prefix(line_before_cursor: &str) -> Option<&str> {
+    let name_start = line_before_cursor
+        .rfind(|c| !is_name_char(c))
+        .map_or(0, |index| index + 1);
+    if !line_before_cursor[..name_start].ends_with("$<") {
+        return None;
+    }
+    Some(&line_before_cursor[name_start..])
+}
+
+pub fn completion_items() -> Vec<CompletionItem> {
+    GENEXES
+        .iter()
+        .filter(|genex| genex.name != "0" && genex.name != "1")
+        .map(|genex| CompletionItem {
+            label: genex.name.to_string(),
+            kind: Some(CompletionItemKind::KEYWORD),
+            detail: Some(genex.signature.to_string()),
+            documentation: Some(Documentation::String(genex.description.to_string())),
+            ..Default::default()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod genex_test {
+    use super::*;
+
+    #[test]
+    fn tst_parse_genexes() {
+        let text = "$<$<CONFIG:Debug>:-O0>";
+        let genexes = parse_genexes(text);
+        assert_eq!(
+            genexes,
+            vec![
+                GenexNode {
+                    name: "",
+                    start: 0,
+                    name_end: 2,
+                    end: Some(22),
+                },
+                GenexNode {
+                    name: "CONFIG",
+                    start: 2,
+                    name_end: 10,
+                    end: Some(17),
+                },
+            ]
+        );
+        assert!(genexes[0].has_nested_condition(text));
+        assert!(parse_genexes("$<${USE_X}:foo>")[0].has_nested_condition("$<${USE_X}:foo>"));
+        assert_eq!(parse_genexes("$<TARGET_FILE:foo")[0].end, None);
+        assert!(parse_genexes("a>b").is_empty());
+        assert!(parse_genexes(r#""\\$<""#).is_empty());
+        assert_eq!(
+            highlight_ranges(text),
+            vec![(0, 2), (2, 10), (16, 17), (21, 22)]
+        );
+    }
+
+    #[test]
+    fn tst_genex_at() {
+        let line = "  $<BUILD_INTERFACE:${PROJECT_SOURCE_DIR}/include>";
+        assert_eq!(
+            genex_at(line, 6).map(|genex| genex.name),
+            Some("BUILD_INTERFACE")
+        );
+        assert_eq!(genex_at(line, 30), None);
+        assert_eq!(
+            genex_at("$<CONFIGURATION>", 2).map(|genex| genex.name),
+            Some("CONFIGURATION")
+        );
+    }
+
+    #[test]
+    fn tst_completion_prefix() {
+        assert_eq!(completion_prefix("target_link_libraries(foo $<"), Some(""));
+        assert_eq!(
+            completion_prefix("$<$<CONFIG:Debug>:$<TARGET_F"),
+            Some("TARGET_F")
+        );
+        assert_eq!(completion_prefix("set(A TARGET_F"), None);
+        assert!(completion_items()
+            .iter()
+            .any(|item| item.label == "TARGET_FILE"));
+    }
+}
@@ -1,4 +1,5 @@
 use crate::fileapi;
+use crate::genex;
 use crate::utils::get_the_packagename;
 #[cfg(unix)]
 use crate::utils::packagepkgconfig::PKG_CONFIG_PACKAGES_WITHKEY;
@@ -56,6 +57,13 @@ PackageVersion: {}
 /// get the doc for on hover
 pub async fn get_hovered_doc(location: Position, root: Node<'_>, source: &str) -> Option<String> {
     let current_point = location.to_point();
+    if let Some(genex) = source
+        .lines()
+        .nth(current_point.row)
+        .and_then(|line| genex::genex_at(line, current_point.column))
+    {
+        return Some(genex.document());
+    }
     let message = get_point_string(current_point, root, &source.lines().collect())?;
     let inner_result = match get_pos_type(current_point, root, source) {
         #[cfg(unix)]
@@ -129,3 +137,27 @@ find_package(bash-completion-fake)
     .unwrap();
     assert_eq!(document, cmakepackage_document_fmt(&fake_package));
 }
+
+#[tokio::test]
+async fn tst_hover_genex() {
+    use crate::consts::TREESITTER_CMAKE_LANGUAGE;
+
+    let content = "target_include_directories(demo PUBLIC $<BUILD_INTERFACE:include>)\n";
+    let mut parse = tree_sitter::Parser::new();
+    parse.set_language(&TREESITTER_CMAKE_LANGUAGE).unwrap();
+    let thetree = parse.parse(content, None).unwrap();
+    let document = get_hovered_doc(
+        Position {
+            line: 0,
+            character: 45,
+        },
+        thetree.root_node(),
+        content,
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        document,
+        genex::find_genex("BUILD_INTERFACE").unwrap().document()
+    );
+}
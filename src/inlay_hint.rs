@@ -0,0 +1,416 @@
+/// Show the values of the variables, from the cmake cache or the last set() before them
+use std::collections::{HashMap, HashSet};
+
+use tower_lsp::lsp_types::{InlayHint, InlayHintLabel, InlayHintTooltip, Range};
+use tree_sitter::Node;
+
+use crate::complete::buildin_variable_positions;
+use crate::fileapi;
+use crate::utils::treehelper::{command_arguments, node_text, ToPosition};
+use crate::utils::{remove_quotation, replace_placeholders_with_hashmap};
+use crate::CMakeNodeKinds;
+
+const MAX_HINT_LEN: usize = 30;
+
+const CONDITION_COMMANDS: &[&str] = &[
+    CMakeNodeKinds::IF_COMMAND,
+    CMakeNodeKinds::ELSEIF_COMMAND,
+    CMakeNodeKinds::WHILE_COMMAND,
+];
+
+/// the value known before the current node
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct StaticValue {
+    value: String,
+    row: usize,
+}
+
+struct HintContext<'a> {
+    source: Vec<&'a str>,
+    cached_values: HashMap<String, String>,
+    static_values: HashMap<String, StaticValue>,
+    range: Range,
+    hints: Vec<InlayHint>,
+}
+
+fn shorter_value(value: &str) -> String {
+    if value.chars().count() <= MAX_HINT_LEN {
+        return value.to_string();
+    }
+    let shorter: String = value.chars().take(MAX_HINT_LEN).collect();
+    format!("{shorter}...")
+}
+
+impl HintContext<'_> {
+    fn get_value(&self, name: &str) -> Option<(String, String)> {
+        if let Some(value) = self.cached_values.get(name) {
+            return Some((value.clone(), "current cached value".to_string()));
+        }
+        let StaticValue { value, row } = self.static_values.get(name)?;
+        Some((value.clone(), format!("set at line {}", row + 1)))
+    }
+
+    fn push_hint(&mut self, name: &str, node: Node) {
+        let position = node.end_position().to_position();
+        if position.line < self.range.start.line || position.line > self.range.end.line {
+            return;
+        }
+        let Some((value, tooltip)) = self.get_value(name) else {
+            return;
+        };
+        self.hints.push(InlayHint {
+            position,
+            label: InlayHintLabel::String(format!("= {}", shorter_value(&value))),
+            kind: None,
+            text_edits: None,
+            tooltip: Some(InlayHintTooltip::String(tooltip)),
+            padding_left: Some(true),
+            padding_right: None,
+            data: None,
+        });
+    }
+
+    // the value of the argument, the variables inside are replaced if all of them are known
+    fn argument_value(&self, argument: Node) -> Option<String> {
+        let text = node_text(&self.source, &argument)?;
+        let text = remove_quotation(text);
+        if !text.contains("${") {
+            return Some(text.to_string());
+        }
+        let mut values = self.cached_values.clone();
+        for (name, StaticValue { value, .. }) in self.static_values.iter() {
+            values.entry(name.clone()).or_insert(value.clone());
+        }
+        replace_placeholders_with_hashmap(text, &values)
+    }
+
+    fn update_static_value(&mut self, command: Node) {
+        let Some(command_name) = command.child(0).and_then(|id| node_text(&self.source, &id))
+        else {
+            return;
+        };
+        let command_name = command_name.to_lowercase();
+        let arguments = command_arguments(command);
+        let Some(name) = arguments
+            .first()
+            .and_then(|name| node_text(&self.source, name))
+        else {
+            return;
+        };
+        let name = remove_quotation(name).to_string();
+        let row = command.start_position().row;
+        match command_name.as_str() {
+            "set" => {
+                let mut values = vec![];
+                for argument in arguments.iter().skip(1) {
+                    let Some(text) = node_text(&self.source, argument) else {
+                        return;
+                    };
+                    if text == "CACHE" || text == "PARENT_SCOPE" {
+                        break;
+                    }
+                    let Some(value) = self.argument_value(*argument) else {
+                        // the value is unknown now
+                        self.static_values.remove(&name);
+                        return;
+                    };
+                    values.push(value);
+                }
+                if values.is_empty() {
+                    self.static_values.remove(&name);
+                    return;
+                }
+                self.static_values.insert(
+                    name,
+                    StaticValue {
+                        value: values.join(";"),
+                        row,
+                    },
+                );
+            }
+            "option" => {
+                let value = arguments
+                    .get(2)
+                    .and_then(|value| self.argument_value(*value))
+                    .unwrap_or("OFF".to_string());
+                self.static_values
+                    .entry(name)
+                    .or_insert(StaticValue { value, row });
+            }
+            "unset" => {
+                self.static_values.remove(&name);
+            }
+            // the variables are named like <prefix>_<keyword>
+            "cmake_parse_arguments" => {
+                let prefix = if name == "PARSE_ARGV" {
+                    let Some(prefix) = arguments
+                        .get(2)
+                        .and_then(|prefix| node_text(&self.source, prefix))
+                    else {
+                        return;
+                    };
+                    remove_quotation(prefix).to_string()
+                } else {
+                    name
+                };
+                let prefix = format!("{prefix}_");
+                self.static_values
+                    .retain(|name, _| !name.starts_with(&prefix));
+            }
+            // NOTE: the variable given to the command, like list(APPEND VAR), is changed to unknown
+            _ => {
+                let texts: Vec<Option<&str>> = arguments
+                    .iter()
+                    .map(|argument| {
+                        argument
+                            .child(0)
+                            .filter(|node| {
+                                node.kind() == CMakeNodeKinds::UNQUOTED_ARGUMENT
+                                    && node.child_count() == 0
+                            })
+                            .and_then(|bare| node_text(&self.source, &bare))
+                    })
+                    .collect();
+                for index in buildin_variable_positions(&command_name, &texts) {
+                    if let Some(name) = texts[index] {
+                        self.static_values.remove(name);
+                    }
+                }
+            }
+        }
+    }
+
+    fn scan_variables(&mut self, input: Node) {
+        if matches!(
+            input.kind(),
+            CMakeNodeKinds::NORMAL_VAR | CMakeNodeKinds::CACHE_VAR
+        ) {
+            let mut course = input.walk();
+            let Some(variable) = input
+                .children(&mut course)
+                .find(|child| child.kind() == CMakeNodeKinds::VARIABLE)
+            else {
+                return;
+            };
+            if variable.child_count() != 0 {
+                self.scan_variables(variable);
+                return;
+            }
+            if let Some(name) = node_text(&self.source, &variable) {
+                self.push_hint(name, input);
+            }
+            return;
+        }
+        let mut course = input.walk();
+        for child in input.children(&mut course) {
+            self.scan_variables(child);
+        }
+    }
+
+    fn scan_condition(&mut self, command: Node) {
+        for argument in command_arguments(command) {
+            let bare = argument.child(0).filter(|node| {
+                node.kind() == CMakeNodeKinds::UNQUOTED_ARGUMENT && node.child_count() == 0
+            });
+            match bare.and_then(|bare| node_text(&self.source, &bare)) {
+                Some(name) => self.push_hint(name, argument),
+                None => self.scan_variables(argument),
+            }
+        }
+    }
+
+    fn changed_names(&self, before: &HashMap<String, StaticValue>) -> Vec<String> {
+        let mut names: Vec<String> = self
+            .static_values
+            .iter()
+            .filter(|(name, value)| before.get(*name) != Some(value))
+            .map(|(name, _)| name.clone())
+            .collect();
+        names.extend(
+            before
+                .keys()
+                .filter(|name| !self.static_values.contains_key(*name))
+                .cloned(),
+        );
+        names
+    }
+
+    // NOTE: which branch runs is unknown, so the values set in the branches are unknown after the block
+    fn scan_branches(&mut self, block: Node) {
+        let before = self.static_values.clone();
+        let mut changed: HashSet<String> = HashSet::new();
+        let mut course = block.walk();
+        for child in block.children(&mut course) {
+            if matches!(
+                child.kind(),
+                CMakeNodeKinds::ELSEIF_COMMAND | CMakeNodeKinds::ELSE_COMMAND
+            ) {
+                changed.extend(self.changed_names(&before));
+                self.static_values = before.clone();
+            }
+            if !self.scan_node(child) {
+                break;
+            }
+        }
+        changed.extend(self.changed_names(&before));
+        self.static_values = before;
+        self.static_values.retain(|name, _| !changed.contains(name));
+    }
+
+    /// false if the node is after the range
+    fn scan_node(&mut self, node: Node) -> bool {
+        if node.start_position().row > self.range.end.line as usize {
+            return false;
+        }
+        match node.kind() {
+            CMakeNodeKinds::NORMAL_COMMAND => {
+                self.scan_variables(node);
+                self.update_static_value(node);
+            }
+            kind if CONDITION_COMMANDS.contains(&kind) => self.scan_condition(node),
+            // the body only runs when it is called, the values set inside are not seen outside
+            CMakeNodeKinds::FUNCTION_DEF | CMakeNodeKinds::MACRO_DEF => {
+                let values = self.static_values.clone();
+                self.scan_hints(node);
+                self.static_values = values;
+            }
+            CMakeNodeKinds::IF_CONDITION
+            | CMakeNodeKinds::FOREACH_LOOP
+            | CMakeNodeKinds::WHILE_LOOP => self.scan_branches(node),
+            CMakeNodeKinds::LINE_COMMENT | CMakeNodeKinds::BRACKET_COMMENT => {}
+            _ => self.scan_hints(node),
+        }
+        true
+    }
+
+    fn scan_hints(&mut self, input: Node) {
+        let mut course = input.walk();
+        for child in input.children(&mut course) {
+            if !self.scan_node(child) {
+                return;
+            }
+        }
+    }
+}
+
+/// get the inlay hints of the variables in the range
+pub fn get_inlay_hints(root: Node, source: &str, range: Range) -> Option<Vec<InlayHint>> {
+    let mut context = HintContext {
+        source: source.lines().collect(),
+        cached_values: fileapi::get_entries_data().unwrap_or_default(),
+        static_values: HashMap::new(),
+        range,
+        hints: vec![],
+    };
+    context.scan_hints(root);
+    if context.hints.is_empty() {
+        None
+    } else {
+        Some(context.hints)
+    }
+}
+
+#[cfg(test)]
+mod inlay_hint_test {
+    use super::*;
+    use crate::consts::TREESITTER_CMAKE_LANGUAGE;
+    use tower_lsp::lsp_types::Position;
+
+    #[test]
+    fn tst_inlay_hints() {
+        let source = r#"set(PREFIX "/usr")
+set(LIB_DIR ${PREFIX}/lib)
+option(USE_QT "use qt" ON)
+if(USE_QT AND UNKNOWN)
+  message(STATUS "${LIB_DIR}")
+endif()
+set(PREFIX)
+message(STATUS ${PREFIX})
+set(SRCS a.cpp)
+list(APPEND SRCS b.cpp)
+message(STATUS ${SRCS})
+set(ARG_NAME demo)
+cmake_parse_arguments(ARG "" "NAME" "" ${ARGN})
+message(STATUS ${ARG_NAME})
+"#;
+        let mut parse = tree_sitter::Parser::new();
+        parse.set_language(&TREESITTER_CMAKE_LANGUAGE).unwrap();
+        let tree = parse.parse(source, None).unwrap();
+        let range = Range {
+            start: Position {
+                line: 0,
+                character: 0,
+            },
+            end: Position {
+                line: 14,
+                character: 0,
+            },
+        };
+        let hints: Vec<(u32, u32, String)> = get_inlay_hints(tree.root_node(), source, range)
+            .unwrap()
+            .into_iter()
+            .map(|hint| {
+                let InlayHintLabel::String(label) = hint.label else {
+                    panic!("label should be string");
+                };
+                (hint.position.line, hint.position.character, label)
+            })
+            .collect();
+        assert_eq!(
+            hints,
+            vec![
+                (1, 21, "= /usr".to_string()),
+                (3, 9, "= ON".to_string()),
+                (4, 28, "= /usr/lib".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn tst_inlay_hints_scope() {
+        let source = r#"set(A 1)
+function(foo)
+  set(A 2)
+  message(${A})
+endfunction()
+message(${A})
+if(B)
+  set(A 3)
+else()
+  message(${A})
+endif()
+message(${A})
+"#;
+        let mut parse = tree_sitter::Parser::new();
+        parse.set_language(&TREESITTER_CMAKE_LANGUAGE).unwrap();
+        let tree = parse.parse(source, None).unwrap();
+        let range = Range {
+            start: Position {
+                line: 0,
+                character: 0,
+            },
+            end: Position {
+                line: 12,
+                character: 0,
+            },
+        };
+        let hints: Vec<(u32, u32, String)> = get_inlay_hints(tree.root_node(), source, range)
+            .unwrap()
+            .into_iter()
+            .map(|hint| {
+                let InlayHintLabel::String(label) = hint.label else {
+                    panic!("label should be string");
+                };
+                (hint.position.line, hint.position.character, label)
+            })
+            .collect();
+        assert_eq!(
+            hints,
+            vec![
+                (3, 14, "= 2".to_string()),
+                (5, 12, "= 1".to_string()),
+                (9, 14, "= 1".to_string()),
+            ]
+        );
+    }
+}
@@ -9,7 +9,6 @@ use tokio::sync::Mutex;
 /// provide go to definition
 use crate::{
     consts::TREESITTER_CMAKE_LANGUAGE,
-    languageserver::BUFFERS_CACHE,
     scansubs::TREE_MAP,
     utils::{
         gen_module_pattern, get_the_packagename, include_is_module, replace_placeholders,
@@ -91,12 +90,6 @@ pub async fn get_cached_defs<P: AsRef<Path>>(path: P, key: &str) -> Option<Locat
     }
     drop(jump_cache);
     if let Ok(context) = tokio::fs::read_to_string(&path).await {
-        let mut buffer_cache = BUFFERS_CACHE.lock().await;
-        buffer_cache.insert(
-            lsp_types::Url::from_file_path(&path).unwrap(),
-            context.clone(),
-        );
-        drop(buffer_cache);
         update_cache(&path, context.as_str()).await;
         let jump_cache = JUMP_CACHE.lock().await;
         if let Some(JumpCacheUnit { location, .. }) = jump_cache.get(key) {
@@ -111,12 +104,6 @@ pub async fn get_cached_defs<P: AsRef<Path>>(path: P, key: &str) -> Option<Locat
         }
         drop(jump_cache);
         if let Ok(context) = tokio::fs::read_to_string(&parent).await {
-            let mut buffer_cache = BUFFERS_CACHE.lock().await;
-            buffer_cache.insert(
-                lsp_types::Url::from_file_path(&path).unwrap(),
-                context.clone(),
-            );
-            drop(buffer_cache);
             update_cache(&path, context.as_str()).await;
             let jump_cache = JUMP_CACHE.lock().await;
             if let Some(JumpCacheUnit { location, .. }) = jump_cache.get(key) {
@@ -133,12 +120,13 @@ pub async fn get_cached_defs<P: AsRef<Path>>(path: P, key: &str) -> Option<Locat
 pub async fn godef<P: AsRef<Path>>(
     location: Position,
     source: &str,
+    tree: &tree_sitter::Tree,
     originuri: P,
     client: &tower_lsp::Client,
     is_jump: bool,
 ) -> Option<Vec<Location>> {
     let current_point = location.to_point();
-    let locations = godef_inner(current_point, source, originuri, is_jump).await;
+    let locations = godef_inner(current_point, source, tree, originuri, is_jump).await;
     if locations.is_none() {
         client
             .log_message(MessageType::INFO, "Not find any locations")
@@ -150,13 +138,10 @@ pub async fn godef<P: AsRef<Path>>(
 async fn godef_inner<P: AsRef<Path>>(
     location: tree_sitter::Point,
     source: &str,
+    tree: &tree_sitter::Tree,
     originuri: P,
     is_jump: bool,
 ) -> Option<Vec<Location>> {
-    let mut parse = tree_sitter::Parser::new();
-    parse.set_language(&TREESITTER_CMAKE_LANGUAGE).unwrap();
-    let tree = parse.parse(source, None)?;
-
     let tofind = get_point_string(location, tree.root_node(), &source.lines().collect())?;
 
     let jumptype = get_pos_type(location, tree.root_node(), source);
@@ -593,9 +578,13 @@ mod jump_test {
         let subdir_file = subdir.join("CMakeLists.txt");
         File::create_new(&subdir_file).unwrap();
 
+        let mut parse = tree_sitter::Parser::new();
+        parse.set_language(&TREESITTER_CMAKE_LANGUAGE).unwrap();
+        let tree = parse.parse(jump_file_src, None).unwrap();
         let locations = godef_inner(
             Point { row: 0, column: 20 },
             &jump_file_src,
+            &tree,
             &top_cmake,
             true,
         )
@@ -645,9 +634,13 @@ add_subdirectory(abcd_test)
         let subdir_file = subdir.join("CMakeLists.txt");
         File::create_new(&subdir_file).unwrap();
 
+        let mut parse = tree_sitter::Parser::new();
+        parse.set_language(&TREESITTER_CMAKE_LANGUAGE).unwrap();
+        let tree = parse.parse(jump_file_src, None).unwrap();
         let locations = godef_inner(
             Point { row: 2, column: 18 },
             &jump_file_src,
+            &tree,
             &top_cmake,
             true,
         )
@@ -673,6 +666,7 @@ add_subdirectory(abcd_test)
         let locations_2 = godef_inner(
             Point { row: 4, column: 13 },
             &jump_file_src,
+            &tree,
             &top_cmake,
             true,
         )
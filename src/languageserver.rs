@@ -5,41 +5,44 @@ use self::config::Config;
 
 use super::Backend;
 use crate::ast;
+use crate::code_action;
 use crate::complete;
-use crate::consts::TREESITTER_CMAKE_LANGUAGE;
+use crate::config::read_neocmake_config;
+use crate::document;
+use crate::document::Document;
+use crate::document_highlight;
 use crate::document_link;
+use crate::editorconfig::{self, EditorConfigProperties};
 use crate::fileapi;
 use crate::fileapi::DEFAULT_QUERY;
 use crate::filewatcher;
-use crate::formatting::getformat;
+use crate::folding_range;
+use crate::formatting::{get_on_type_format, get_range_format, getformat, FormatStyle};
 use crate::gammar::checkerror;
+use crate::gammar::rules;
 use crate::gammar::ErrorInformation;
 use crate::gammar::LintConfigInfo;
 use crate::hover;
+use crate::inlay_hint;
 use crate::jump;
+use crate::references;
+use crate::rename;
 use crate::scansubs;
 use crate::semantic_token;
-use crate::semantic_token::LEGEND_TYPE;
+use crate::semantic_token::{LEGEND_MODIFIER, LEGEND_TYPE};
+use crate::signature_help;
 use crate::utils;
 use crate::utils::did_vcpkg_project;
 use crate::utils::treehelper;
 use crate::utils::VCPKG_LIBS;
 use crate::utils::VCPKG_PREFIX;
-use std::collections::HashMap;
-use std::sync::Arc;
+use crate::workspace_symbol;
 use std::sync::RwLock;
-use tokio::sync::Mutex;
 use tower_lsp::jsonrpc::Error as LspError;
 use tower_lsp::jsonrpc::Result;
 use tower_lsp::lsp_types;
 use tower_lsp::lsp_types::*;
 use tower_lsp::LanguageServer;
-use tree_sitter::Parser;
-
-use std::sync::LazyLock;
-
-pub static BUFFERS_CACHE: LazyLock<Arc<Mutex<HashMap<lsp_types::Url, String>>>> =
-    LazyLock::new(|| Arc::new(Mutex::new(HashMap::new())));
 
 static CLIENT_CAPABILITIES: RwLock<Option<TextDocumentClientCapabilities>> = RwLock::new(None);
 
@@ -64,7 +67,49 @@ pub fn client_support_snippet() -> bool {
     }
 }
 
+// the .editorconfig properties of the file, the buffers not saved as files have none
+fn get_editorconfig(uri: &Url) -> EditorConfigProperties {
+    uri.to_file_path()
+        .map(editorconfig::get_properties)
+        .unwrap_or_default()
+}
+
+// the references and the symbols of the files not opened are read from the disk
+async fn remove_file_caches(path: &str) {
+    references::remove_cache(path).await;
+    workspace_symbol::remove_cache(path).await;
+}
+
 impl Backend {
+    /// the style from the formatting options, the init options and the config in the workspace root
+    /// the .editorconfig of the file is used first
+    async fn format_style(
+        &self,
+        options: &FormattingOptions,
+        editorconfig: &EditorConfigProperties,
+    ) -> FormatStyle {
+        let use_space = editorconfig.use_space.unwrap_or(options.insert_spaces);
+        let space_line = match editorconfig.indent_unit() {
+            Some(indent_unit) => indent_unit,
+            None if use_space => options.tab_size,
+            None => 1,
+        };
+        let line_width =
+            editorconfig
+                .max_line_length
+                .or(self.init_info.lock().await.format_line_width);
+        let config = match self.root_path.lock().await.as_ref() {
+            Some(root_path) => read_neocmake_config(root_path).format,
+            None => Default::default(),
+        };
+        let mut style = FormatStyle::new(space_line, use_space, line_width, config);
+        style.trim_trailing_whitespace = editorconfig
+            .trim_trailing_whitespace
+            .or(options.trim_trailing_whitespace)
+            .unwrap_or(false);
+        style
+    }
+
     async fn path_in_project(&self, path: &str) -> bool {
         if self.root_path.lock().await.is_none() {
             return true;
@@ -89,7 +134,10 @@ impl Backend {
             return;
         };
 
-        let gammererror = checkerror(&file_path, &context, lint_info);
+        let Some(tree) = document::get_tree(&uri, &context).await else {
+            return;
+        };
+        let gammererror = checkerror(&file_path, &context, &tree, lint_info);
         if let Some(diagnoses) = gammererror {
             let mut pusheddiagnoses = vec![];
             for ErrorInformation {
@@ -97,6 +145,8 @@ impl Backend {
                 end_point,
                 message,
                 severity,
+                fix,
+                rule,
             } in diagnoses.inner
             {
                 let pointx =
@@ -110,13 +160,16 @@ impl Backend {
                 let diagnose = Diagnostic {
                     range,
                     severity,
-                    code: None,
-                    code_description: None,
+                    code: rule.map(|id| NumberOrString::String(id.to_string())),
+                    code_description: rule
+                        .and_then(rules::find_rule)
+                        .and_then(|rule| Url::parse(&rule.doc_url()).ok())
+                        .map(|href| CodeDescription { href }),
                     source: None,
                     message,
                     related_information: None,
                     tags: None,
-                    data: None,
+                    data: fix.and_then(|fix| serde_json::to_value(fix).ok()),
                 };
                 pusheddiagnoses.push(diagnose);
             }
@@ -128,11 +181,16 @@ impl Backend {
         }
     }
     async fn update_diagnostics(&self) {
-        let storemap = BUFFERS_CACHE.lock().await;
-        for (uri, context) in storemap.iter() {
+        let documents: Vec<(Url, String)> = document::DOCUMENTS_CACHE
+            .lock()
+            .await
+            .iter()
+            .map(|(uri, document)| (uri.clone(), document.text.clone()))
+            .collect();
+        for (uri, context) in documents {
             self.publish_diagnostics(
-                uri.clone(),
-                context.to_string(),
+                uri,
+                context,
                 LintConfigInfo {
                     use_lint: self.init_info.lock().await.enable_lint,
                     use_extra_cmake_lint: true,
@@ -160,6 +218,7 @@ impl LanguageServer for Backend {
         let mut init_info = self.init_info.lock().await;
         init_info.scan_cmake_in_package = find_cmake_in_package;
         init_info.enable_lint = lint_enable;
+        init_info.format_line_width = initial_config.format_line_width();
 
         if let Some(workspace) = initial.capabilities.workspace {
             if let Some(watch_file) = workspace.did_change_watched_files {
@@ -176,32 +235,9 @@ impl LanguageServer for Backend {
                         }
 
                         tracing::info!("find cache-v2 json, start reading the data");
-                        let cache_path = std::path::Path::new(uri.path())
-                            .join("build")
-                            .join(".cmake")
-                            .join("api")
-                            .join("v1")
-                            .join("reply");
-                        if cache_path.is_dir() {
-                            use std::fs;
-                            if let Ok(entries) = fs::read_dir(cache_path) {
-                                for entry in entries.flatten() {
-                                    let file_path = entry.path();
-                                    if file_path.is_file() {
-                                        let Some(file_name) = file_path.file_name() else {
-                                            continue;
-                                        };
-                                        let file_name = file_name.to_string_lossy().to_string();
-                                        if file_name.starts_with("cache-v2")
-                                            && file_name.ends_with(".json")
-                                        {
-                                            fileapi::update_cache_data(file_path);
-                                            break;
-                                        }
-                                    }
-                                }
-                            }
-                        }
+                        fileapi::update_cache_data_in_build_dir(
+                            std::path::Path::new(uri.path()).join("build"),
+                        );
                         tracing::info!("Finish getting the data in cache-v2 json");
                     }
                 }
@@ -269,7 +305,7 @@ impl LanguageServer for Backend {
                 text_document_sync: Some(TextDocumentSyncCapability::Options(
                     TextDocumentSyncOptions {
                         open_close: Some(true),
-                        change: Some(TextDocumentSyncKind::FULL),
+                        change: Some(TextDocumentSyncKind::INCREMENTAL),
                         will_save: Some(false),
                         will_save_wait_until: Some(false),
                         save: Some(TextDocumentSyncSaveOptions::Supported(true)),
@@ -277,18 +313,37 @@ impl LanguageServer for Backend {
                 )),
                 completion_provider: Some(CompletionOptions {
                     resolve_provider: Some(false),
-                    trigger_characters: None,
+                    trigger_characters: Some(vec!["<".to_string()]),
                     work_done_progress_options: Default::default(),
                     all_commit_characters: None,
                     completion_item: None,
                 }),
+                signature_help_provider: Some(SignatureHelpOptions {
+                    trigger_characters: Some(vec!["(".to_string()]),
+                    retrigger_characters: Some(vec![" ".to_string()]),
+                    work_done_progress_options: WorkDoneProgressOptions::default(),
+                }),
                 document_symbol_provider: Some(OneOf::Left(true)),
+                folding_range_provider: Some(FoldingRangeProviderCapability::Simple(true)),
                 definition_provider: Some(OneOf::Left(true)),
                 document_formatting_provider: if do_format {
                     Some(OneOf::Left(true))
                 } else {
                     None
                 },
+                document_range_formatting_provider: if do_format {
+                    Some(OneOf::Left(true))
+                } else {
+                    None
+                },
+                document_on_type_formatting_provider: if do_format {
+                    Some(DocumentOnTypeFormattingOptions {
+                        first_trigger_character: ")".to_string(),
+                        more_trigger_character: Some(vec!["\n".to_string()]),
+                    })
+                } else {
+                    None
+                },
                 hover_provider: Some(HoverProviderCapability::Simple(true)),
                 workspace: Some(WorkspaceServerCapabilities {
                     workspace_folders: Some(WorkspaceFoldersServerCapabilities {
@@ -314,10 +369,12 @@ impl LanguageServer for Backend {
                                     work_done_progress_options: WorkDoneProgressOptions::default(),
                                     legend: SemanticTokensLegend {
                                         token_types: LEGEND_TYPE.into(),
-                                        token_modifiers: vec![],
+                                        token_modifiers: LEGEND_MODIFIER.into(),
                                     },
-                                    range: None,
-                                    full: Some(SemanticTokensFullOptions::Bool(true)),
+                                    range: Some(true),
+                                    full: Some(SemanticTokensFullOptions::Delta {
+                                        delta: Some(true),
+                                    }),
                                 },
                                 static_registration_options: StaticRegistrationOptions::default(),
                             },
@@ -327,6 +384,20 @@ impl LanguageServer for Backend {
                     None
                 },
                 references_provider: Some(OneOf::Left(true)),
+                document_highlight_provider: Some(OneOf::Left(true)),
+                workspace_symbol_provider: Some(OneOf::Left(true)),
+                inlay_hint_provider: Some(OneOf::Left(true)),
+                code_action_provider: Some(CodeActionProviderCapability::Options(
+                    CodeActionOptions {
+                        code_action_kinds: Some(vec![CodeActionKind::QUICKFIX]),
+                        work_done_progress_options: WorkDoneProgressOptions::default(),
+                        resolve_provider: None,
+                    },
+                )),
+                rename_provider: Some(OneOf::Right(RenameOptions {
+                    prepare_provider: Some(true),
+                    work_done_progress_options: WorkDoneProgressOptions::default(),
+                })),
 
                 document_link_provider: Some(DocumentLinkOptions {
                     resolve_provider: Some(true),
@@ -352,7 +423,11 @@ impl LanguageServer for Backend {
                 },
                 FileSystemWatcher {
                     glob_pattern: GlobPattern::String("**/CMakeLists.txt".to_string()),
-                    kind: Some(lsp_types::WatchKind::Create | lsp_types::WatchKind::Delete),
+                    kind: Some(lsp_types::WatchKind::all()),
+                },
+                FileSystemWatcher {
+                    glob_pattern: GlobPattern::String("**/*.cmake".to_string()),
+                    kind: Some(lsp_types::WatchKind::all()),
                 },
             ],
         };
@@ -398,6 +473,13 @@ impl LanguageServer for Backend {
             if file_name.ends_with("json") && file_name.starts_with("cache-v2") {
                 fileapi::update_cache_data(change.uri.path());
             }
+            if file_name == "CMakeLists.txt" || file_name.ends_with(".cmake") {
+                remove_file_caches(change.uri.path()).await;
+                // NOTE: the subdirectories are only changed when the file is created or deleted
+                if change.typ == FileChangeType::CHANGED {
+                    continue;
+                }
+            }
             if file_name.ends_with("txt") {
                 has_cached_changed = true;
                 if file_name == "CMakeLists.txt" {
@@ -427,15 +509,16 @@ impl LanguageServer for Backend {
     }
 
     async fn did_open(&self, input: DidOpenTextDocumentParams) {
-        let mut parse = Parser::new();
-        parse.set_language(&TREESITTER_CMAKE_LANGUAGE).unwrap();
         let uri = input.text_document.uri.clone();
         let context = input.text_document.text.clone();
-        let mut storemap = BUFFERS_CACHE.lock().await;
-        storemap.entry(uri.clone()).or_insert(context.clone());
-        drop(storemap);
+        if let Some(document) = Document::new(context.clone(), input.text_document.version) {
+            let mut documents = document::DOCUMENTS_CACHE.lock().await;
+            documents.insert(uri.clone(), document);
+        }
         complete::update_cache(uri.path(), &context).await;
         jump::update_cache(uri.path(), &context).await;
+        references::update_cache(uri.path(), &context).await;
+        workspace_symbol::update_cache(uri.path(), &context).await;
         self.publish_diagnostics(
             uri,
             context,
@@ -451,12 +534,16 @@ impl LanguageServer for Backend {
     }
 
     async fn did_change(&self, input: DidChangeTextDocumentParams) {
-        // create a parse
-        let uri = input.text_document.uri.clone();
-        let context = input.content_changes[0].text.clone();
-        let mut storemap = BUFFERS_CACHE.lock().await;
-        storemap.insert(uri.clone(), context.clone());
-        drop(storemap);
+        tracing::debug!("{input:?}");
+        let uri = input.text_document.uri;
+        let mut documents = document::DOCUMENTS_CACHE.lock().await;
+        let Some(document) = documents.get_mut(&uri) else {
+            tracing::error!("{uri:?} is changed before opened");
+            return;
+        };
+        document.apply_changes(input.content_changes, input.text_document.version);
+        let context = document.text.clone();
+        drop(documents);
         if context.lines().count() < 500 {
             self.publish_diagnostics(
                 uri,
@@ -468,27 +555,23 @@ impl LanguageServer for Backend {
             )
             .await;
         }
-        self.client
-            .log_message(MessageType::INFO, &format!("{input:?}"))
-            .await;
     }
 
     async fn did_save(&self, params: DidSaveTextDocumentParams) {
         let uri = params.text_document.uri;
-        let storemap = BUFFERS_CACHE.lock().await;
-
         let has_root = self.root_path.lock().await.is_some();
-        let Some(context) = storemap.get(&uri).cloned() else {
+        let Some(context) = document::get_text(&uri).await else {
             self.client
                 .log_message(MessageType::INFO, "file saved!")
                 .await;
             return;
         };
-        drop(storemap);
         if has_root {
             scansubs::scan_dir(uri.path()).await;
             complete::update_cache(uri.path(), &context).await;
             jump::update_cache(uri.path(), &context).await;
+            references::update_cache(uri.path(), &context).await;
+            workspace_symbol::update_cache(uri.path(), &context).await;
         }
         self.publish_diagnostics(
             uri,
@@ -508,16 +591,10 @@ impl LanguageServer for Backend {
     async fn hover(&self, params: HoverParams) -> Result<Option<Hover>> {
         let position = params.text_document_position_params.position;
         let uri = params.text_document_position_params.text_document.uri;
-        let storemap = BUFFERS_CACHE.lock().await;
         self.client.log_message(MessageType::INFO, "Hovered!").await;
-        let Some(context) = storemap.get(&uri).cloned() else {
+        let Some((context, tree)) = document::get_document(&uri).await else {
             return Ok(None);
         };
-        drop(storemap);
-        let mut parse = Parser::new();
-        parse.set_language(&TREESITTER_CMAKE_LANGUAGE).unwrap();
-        let thetree = parse.parse(context.clone(), None);
-        let tree = thetree.unwrap();
         let output = hover::get_hovered_doc(position, tree.root_node(), &context).await;
         match output {
             Some(context) => Ok(Some(Hover {
@@ -539,27 +616,59 @@ impl LanguageServer for Backend {
             )
             .await;
         let uri = input.text_document.uri;
-        let storemap = BUFFERS_CACHE.lock().await;
-        let space_line = if input.options.insert_spaces {
-            input.options.tab_size
-        } else {
-            1
+        let editorconfig = get_editorconfig(&uri);
+        let style = self.format_style(&input.options, &editorconfig).await;
+        let insert_final_newline = editorconfig
+            .insert_final_newline
+            .or(input.options.insert_final_newline)
+            .unwrap_or(false);
+        let Some((context, tree)) = document::get_document(&uri).await else {
+            return Ok(None);
         };
-        let insert_final_newline = input.options.insert_final_newline.unwrap_or(false);
-        match storemap.get(&uri) {
-            Some(context) => Ok(getformat(
-                context,
-                &self.client,
-                space_line,
-                input.options.insert_spaces,
-                insert_final_newline,
-            )
-            .await),
-            None => Ok(None),
-        }
+        Ok(getformat(&context, &tree, &self.client, &style, insert_final_newline).await)
+    }
+
+    async fn range_formatting(
+        &self,
+        input: DocumentRangeFormattingParams,
+    ) -> Result<Option<Vec<TextEdit>>> {
+        let uri = input.text_document.uri;
+        let Some((context, tree)) = document::get_document(&uri).await else {
+            return Ok(None);
+        };
+        let style = self
+            .format_style(&input.options, &get_editorconfig(&uri))
+            .await;
+        Ok(get_range_format(&context, &tree, &self.client, input.range, &style).await)
+    }
+
+    async fn on_type_formatting(
+        &self,
+        input: DocumentOnTypeFormattingParams,
+    ) -> Result<Option<Vec<TextEdit>>> {
+        let uri = input.text_document_position.text_document.uri;
+        let Some((context, tree)) = document::get_document(&uri).await else {
+            return Ok(None);
+        };
+        let style = self
+            .format_style(&input.options, &get_editorconfig(&uri))
+            .await;
+        Ok(get_on_type_format(
+            &context,
+            &tree,
+            input.text_document_position.position,
+            &input.ch,
+            style.indent_size,
+            style.use_space,
+        ))
     }
 
     async fn did_close(&self, params: DidCloseTextDocumentParams) {
+        let mut documents = document::DOCUMENTS_CACHE.lock().await;
+        documents.remove(&params.text_document.uri);
+        drop(documents);
+        semantic_token::remove_cache(&params.text_document.uri).await;
+        remove_file_caches(params.text_document.uri.path()).await;
         self.client
             .log_message(
                 MessageType::INFO,
@@ -572,31 +681,108 @@ impl LanguageServer for Backend {
         self.client.log_message(MessageType::INFO, "Complete").await;
         let location = input.text_document_position.position;
         let uri = input.text_document_position.text_document.uri;
-        let storemap = BUFFERS_CACHE.lock().await;
-        let urlconent = storemap.get(&uri).cloned();
-        drop(storemap);
-        match urlconent {
-            Some(context) => Ok(complete::getcomplete(
-                &context,
-                location,
-                &self.client,
-                uri.path(),
-                self.init_info.lock().await.scan_cmake_in_package,
-            )
-            .await),
-            None => Ok(None),
-        }
+        let Some((context, tree)) = document::get_document(&uri).await else {
+            return Ok(None);
+        };
+        Ok(complete::getcomplete(
+            &context,
+            &tree,
+            location,
+            &self.client,
+            uri.path(),
+            self.init_info.lock().await.scan_cmake_in_package,
+        )
+        .await)
+    }
+    async fn signature_help(&self, input: SignatureHelpParams) -> Result<Option<SignatureHelp>> {
+        let uri = input.text_document_position_params.text_document.uri;
+        let location = input.text_document_position_params.position;
+        let Some((context, tree)) = document::get_document(&uri).await else {
+            return Ok(None);
+        };
+        let file_path = match uri.to_file_path() {
+            Ok(file_path) => file_path,
+            Err(_) => {
+                tracing::error!("Cannot get file_path from {uri:?}");
+                return Err(LspError::internal_error());
+            }
+        };
+        Ok(signature_help::get_signature_help(location, &context, &tree, &file_path).await)
     }
     async fn references(&self, input: ReferenceParams) -> Result<Option<Vec<Location>>> {
         let uri = input.text_document_position.text_document.uri;
         let location = input.text_document_position.position;
-        let storemap = BUFFERS_CACHE.lock().await;
-        let Some(context) = storemap.get(&uri).cloned() else {
+        let Some(context) = document::get_text(&uri).await else {
+            return Ok(None);
+        };
+        let file_path = match uri.to_file_path() {
+            Ok(file_path) => file_path,
+            Err(_) => {
+                tracing::error!("Cannot get file_path from {uri:?}");
+                return Err(LspError::internal_error());
+            }
+        };
+        Ok(references::get_references(
+            location,
+            context.as_str(),
+            &file_path,
+            input.context.include_declaration,
+        )
+        .await)
+    }
+    async fn document_highlight(
+        &self,
+        input: DocumentHighlightParams,
+    ) -> Result<Option<Vec<DocumentHighlight>>> {
+        let uri = input.text_document_position_params.text_document.uri;
+        let location = input.text_document_position_params.position;
+        let Some((context, tree)) = document::get_document(&uri).await else {
+            return Ok(None);
+        };
+        let file_path = match uri.to_file_path() {
+            Ok(file_path) => file_path,
+            Err(_) => {
+                tracing::error!("Cannot get file_path from {uri:?}");
+                return Err(LspError::internal_error());
+            }
+        };
+        Ok(document_highlight::get_document_highlights(
+            location,
+            tree.root_node(),
+            &context,
+            &file_path,
+        ))
+    }
+    async fn code_action(&self, input: CodeActionParams) -> Result<Option<CodeActionResponse>> {
+        Ok(code_action::get_code_actions(
+            &input.text_document.uri,
+            &input.context.diagnostics,
+        ))
+    }
+    async fn prepare_rename(
+        &self,
+        input: TextDocumentPositionParams,
+    ) -> Result<Option<PrepareRenameResponse>> {
+        let uri = input.text_document.uri;
+        let location = input.position;
+        let Some(context) = document::get_text(&uri).await else {
+            return Ok(None);
+        };
+        let file_path = match uri.to_file_path() {
+            Ok(file_path) => file_path,
+            Err(_) => {
+                tracing::error!("Cannot get file_path from {uri:?}");
+                return Err(LspError::internal_error());
+            }
+        };
+        Ok(rename::prepare_rename(location, &context, &file_path).await)
+    }
+    async fn rename(&self, input: RenameParams) -> Result<Option<WorkspaceEdit>> {
+        let uri = input.text_document_position.text_document.uri;
+        let location = input.text_document_position.position;
+        let Some(context) = document::get_text(&uri).await else {
             return Ok(None);
         };
-        drop(storemap);
-        let mut parse = Parser::new();
-        parse.set_language(&TREESITTER_CMAKE_LANGUAGE).unwrap();
         let file_path = match uri.to_file_path() {
             Ok(file_path) => file_path,
             Err(_) => {
@@ -604,7 +790,7 @@ impl LanguageServer for Backend {
                 return Err(LspError::internal_error());
             }
         };
-        Ok(jump::godef(location, context.as_str(), &file_path, &self.client, false).await)
+        rename::rename(location, &context, &file_path, &input.new_name).await
     }
     async fn goto_definition(
         &self,
@@ -612,16 +798,9 @@ impl LanguageServer for Backend {
     ) -> Result<Option<GotoDefinitionResponse>> {
         let uri = input.text_document_position_params.text_document.uri;
         let location = input.text_document_position_params.position;
-        let storemap = BUFFERS_CACHE.lock().await;
-        let Some(context) = storemap.get(&uri).cloned() else {
+        let Some((context, tree)) = document::get_document(&uri).await else {
             return Ok(None);
         };
-        drop(storemap);
-
-        let mut parse = Parser::new();
-        parse.set_language(&TREESITTER_CMAKE_LANGUAGE).unwrap();
-        let thetree = parse.parse(context.clone(), None);
-        let tree = thetree.unwrap();
         let origin_selection_range = treehelper::get_position_range(location, tree.root_node());
 
         let file_path = match uri.to_file_path() {
@@ -631,7 +810,7 @@ impl LanguageServer for Backend {
                 return Err(LspError::internal_error());
             }
         };
-        match jump::godef(location, &context, &file_path, &self.client, true).await {
+        match jump::godef(location, &context, &tree, &file_path, &self.client, true).await {
             Some(range) => Ok(Some(GotoDefinitionResponse::Link({
                 range
                     .iter()
@@ -656,11 +835,34 @@ impl LanguageServer for Backend {
         input: DocumentSymbolParams,
     ) -> Result<Option<DocumentSymbolResponse>> {
         let uri = input.text_document.uri.clone();
-        let storemap = BUFFERS_CACHE.lock().await;
-        match storemap.get(&uri) {
-            Some(context) => Ok(ast::getast(&self.client, context).await),
-            None => Ok(None),
-        }
+        let Some((context, tree)) = document::get_document(&uri).await else {
+            return Ok(None);
+        };
+        Ok(ast::getast(&self.client, &context, &tree).await)
+    }
+    async fn folding_range(&self, input: FoldingRangeParams) -> Result<Option<Vec<FoldingRange>>> {
+        let uri = input.text_document.uri;
+        let Some((context, tree)) = document::get_document(&uri).await else {
+            return Ok(None);
+        };
+        Ok(folding_range::get_folding_ranges(
+            tree.root_node(),
+            &context,
+        ))
+    }
+    async fn inlay_hint(&self, input: InlayHintParams) -> Result<Option<Vec<InlayHint>>> {
+        let uri = input.text_document.uri;
+        let Some((context, tree)) = document::get_document(&uri).await else {
+            return Ok(None);
+        };
+        Ok(inlay_hint::get_inlay_hints(
+            tree.root_node(),
+            &context,
+            input.range,
+        ))
+    }
+    async fn symbol(&self, input: WorkspaceSymbolParams) -> Result<Option<Vec<SymbolInformation>>> {
+        Ok(workspace_symbol::get_workspace_symbols(&input.query).await)
     }
     async fn semantic_tokens_full(
         &self,
@@ -670,11 +872,39 @@ impl LanguageServer for Backend {
         self.client
             .log_message(MessageType::LOG, "semantic_token_full")
             .await;
-        let storemap = BUFFERS_CACHE.lock().await;
-        match storemap.get(&uri) {
-            Some(context) => Ok(semantic_token::semantic_token(&self.client, context).await),
-            None => Ok(None),
-        }
+        let Some((context, tree)) = document::get_document(&uri).await else {
+            return Ok(None);
+        };
+        Ok(semantic_token::semantic_token(&self.client, &uri, &context, &tree).await)
+    }
+
+    async fn semantic_tokens_full_delta(
+        &self,
+        params: SemanticTokensDeltaParams,
+    ) -> Result<Option<SemanticTokensFullDeltaResult>> {
+        let uri = params.text_document.uri;
+        let Some((context, tree)) = document::get_document(&uri).await else {
+            return Ok(None);
+        };
+        Ok(
+            semantic_token::semantic_token_delta(&uri, &context, &tree, &params.previous_result_id)
+                .await,
+        )
+    }
+
+    async fn semantic_tokens_range(
+        &self,
+        params: SemanticTokensRangeParams,
+    ) -> Result<Option<SemanticTokensRangeResult>> {
+        let uri = params.text_document.uri;
+        let Some((context, tree)) = document::get_document(&uri).await else {
+            return Ok(None);
+        };
+        Ok(semantic_token::semantic_token_range(
+            &context,
+            &tree,
+            params.range,
+        ))
     }
 
     async fn document_link(&self, input: DocumentLinkParams) -> Result<Option<Vec<DocumentLink>>> {
@@ -686,10 +916,9 @@ impl LanguageServer for Backend {
                 return Err(LspError::internal_error());
             }
         };
-        let storemap = BUFFERS_CACHE.lock().await;
-        let Some(context) = storemap.get(&uri) else {
+        let Some(context) = document::get_text(&uri).await else {
             return Ok(None);
         };
-        Ok(document_link::document_link_search(context, file_path))
+        Ok(document_link::document_link_search(&context, file_path))
     }
 }
@@ -19,6 +19,10 @@ impl Config {
         self.scan_cmake_in_package.unwrap_or(true)
     }
 
+    pub fn format_line_width(&self) -> Option<usize> {
+        self.format.as_ref().and_then(|config| config.line_width)
+    }
+
     pub fn enable_semantic_token(&self) -> bool {
         self.semantic_token.unwrap_or(false)
     }
@@ -45,11 +49,15 @@ impl Default for Config {
 #[derive(Deserialize, Serialize, PartialEq, Eq, Debug)]
 pub struct FormatConfig {
     pub enable: Option<bool>,
+    pub line_width: Option<usize>,
 }
 
 impl Default for FormatConfig {
     fn default() -> Self {
-        FormatConfig { enable: Some(true) }
+        FormatConfig {
+            enable: Some(true),
+            line_width: None,
+        }
     }
 }
 
@@ -1,4 +1,7 @@
-use crate::{languageserver::Config, semantic_token::LEGEND_TYPE};
+use crate::{
+    languageserver::Config,
+    semantic_token::{LEGEND_MODIFIER, LEGEND_TYPE},
+};
 use std::sync::Arc;
 use tokio::sync::Mutex;
 use tower::{util::ServiceExt, Service};
@@ -29,6 +32,7 @@ async fn test_init() {
         init_info: Arc::new(Mutex::new(BackendInitInfo {
             scan_cmake_in_package: true,
             enable_lint: true,
+            format_line_width: None,
         })),
         root_path: Arc::new(Mutex::new(None)),
     });
@@ -79,10 +83,10 @@ async fn test_init() {
                 },
                 legend: SemanticTokensLegend {
                     token_types: LEGEND_TYPE.into(),
-                    token_modifiers: [].to_vec()
+                    token_modifiers: LEGEND_MODIFIER.into()
                 },
-                range: None,
-                full: Some(SemanticTokensFullOptions::Bool(true)),
+                range: Some(true),
+                full: Some(SemanticTokensFullOptions::Delta { delta: Some(true) }),
             }
         ))
     );
@@ -0,0 +1,377 @@
+/// Lint the files without the lsp, and print the diagnostics for human, json or sarif
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+use serde_json::json;
+use tower_lsp::lsp_types::DiagnosticSeverity;
+use tree_sitter::Point;
+
+use crate::clapargs::LintOutputFormat;
+use crate::consts::TREESITTER_CMAKE_LANGUAGE;
+use crate::gammar::{checkerror, rules, ErrorInformation, LintConfigInfo};
+use crate::{fileapi, filewatcher};
+
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+/// the diagnostics of one file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LintResult {
+    pub path: PathBuf,
+    /// the content of the file, empty if it cannot be read
+    pub source: String,
+    pub diagnostics: Vec<ErrorInformation>,
+}
+
+/// the diagnostic in the json output, the line and column start from 1, the column counts the chars
+#[derive(Debug, Serialize)]
+struct JsonDiagnostic<'a> {
+    path: String,
+    line: usize,
+    column: usize,
+    end_line: usize,
+    end_column: usize,
+    severity: &'static str,
+    message: &'a str,
+    rule: Option<&'static str>,
+}
+
+// NOTE: the grammar error has no severity, it is shown as error in the editors
+fn severity_name(severity: Option<DiagnosticSeverity>) -> &'static str {
+    match severity {
+        Some(DiagnosticSeverity::WARNING) => "warning",
+        Some(DiagnosticSeverity::INFORMATION) => "information",
+        Some(DiagnosticSeverity::HINT) => "hint",
+        _ => "error",
+    }
+}
+
+fn sarif_level(severity: Option<DiagnosticSeverity>) -> &'static str {
+    match severity {
+        Some(DiagnosticSeverity::WARNING) => "warning",
+        Some(DiagnosticSeverity::INFORMATION) | Some(DiagnosticSeverity::HINT) => "note",
+        _ => "error",
+    }
+}
+
+fn is_error(diagnostic: &ErrorInformation) -> bool {
+    severity_name(diagnostic.severity) == "error"
+}
+
+// the path with "/", without the "./" at the start
+fn display_path(path: &Path) -> String {
+    let path = path.strip_prefix(".").unwrap_or(path);
+    let components: Vec<String> = path
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy().to_string())
+        .collect();
+    components.join("/")
+}
+
+// the column counted by the chars, the column of the point is counted by the bytes
+fn char_column(source: &str, point: Point) -> usize {
+    source
+        .lines()
+        .nth(point.row)
+        .and_then(|line| line.get(..point.column))
+        .map_or(point.column, |prefix| prefix.chars().count())
+}
+
+/// read the missing packages and the cache entries from the build directory
+pub fn load_build_dir<P: AsRef<Path>>(build_dir: P) {
+    let build_dir = build_dir.as_ref();
+    let cmake_cache = build_dir.join("CMakeCache.txt");
+    if cmake_cache.is_file() {
+        filewatcher::refresh_error_packages(cmake_cache);
+    }
+    fileapi::update_cache_data_in_build_dir(build_dir);
+}
+
+/// run the same checks as the lsp on the file
+pub fn lint_file(path: &Path) -> LintResult {
+    let mut content = String::new();
+    let diagnostics = match std::fs::read_to_string(path) {
+        Ok(source) => {
+            let mut parse = tree_sitter::Parser::new();
+            parse.set_language(&TREESITTER_CMAKE_LANGUAGE).unwrap();
+            let tree = parse.parse(&source, None).unwrap();
+            let diagnostics = checkerror(
+                &path,
+                &source,
+                &tree,
+                LintConfigInfo {
+                    use_lint: true,
+                    use_extra_cmake_lint: true,
+                },
+            )
+            .map(|info| info.inner)
+            .unwrap_or_default();
+            content = source;
+            diagnostics
+        }
+        Err(e) => vec![ErrorInformation {
+            start_point: Point { row: 0, column: 0 },
+            end_point: Point { row: 0, column: 0 },
+            message: format!("Cannot read the file: {e}"),
+            severity: Some(DiagnosticSeverity::ERROR),
+            fix: None,
+            rule: None,
+        }],
+    };
+    LintResult {
+        path: path.to_path_buf(),
+        source: content,
+        diagnostics,
+    }
+}
+
+/// if there is any error, the warnings and hints are not counted
+pub fn has_error(results: &[LintResult]) -> bool {
+    results
+        .iter()
+        .any(|result| result.diagnostics.iter().any(is_error))
+}
+
+fn human_output(results: &[LintResult]) -> String {
+    let mut output = String::new();
+    let (mut error_count, mut warning_count) = (0, 0);
+    for result in results {
+        let path = display_path(&result.path);
+        for diagnostic in result.diagnostics.iter() {
+            let severity = severity_name(diagnostic.severity);
+            match severity {
+                "error" => error_count += 1,
+                "warning" => warning_count += 1,
+                _ => {}
+            }
+            output.push_str(&format!(
+                "{path}:{}:{}: {severity}: {}",
+                diagnostic.start_point.row + 1,
+                char_column(&result.source, diagnostic.start_point) + 1,
+                diagnostic.message
+            ));
+            match diagnostic.rule {
+                Some(rule) => output.push_str(&format!(" [{rule}]\n")),
+                None => output.push('\n'),
+            }
+        }
+    }
+    output.push_str(&format!(
+        "{} files checked, {error_count} errors, {warning_count} warnings\n",
+        results.len()
+    ));
+    output
+}
+
+fn json_output(results: &[LintResult]) -> String {
+    let diagnostics: Vec<JsonDiagnostic> = results
+        .iter()
+        .flat_map(|result| {
+            result.diagnostics.iter().map(|diagnostic| JsonDiagnostic {
+                path: display_path(&result.path),
+                line: diagnostic.start_point.row + 1,
+                column: char_column(&result.source, diagnostic.start_point) + 1,
+                end_line: diagnostic.end_point.row + 1,
+                end_column: char_column(&result.source, diagnostic.end_point) + 1,
+                severity: severity_name(diagnostic.severity),
+                message: &diagnostic.message,
+                rule: diagnostic.rule,
+            })
+        })
+        .collect();
+    serde_json::to_string_pretty(&diagnostics).unwrap()
+}
+
+fn sarif_output(results: &[LintResult]) -> String {
+    let sarif_results: Vec<serde_json::Value> = results
+        .iter()
+        .flat_map(|result| {
+            result.diagnostics.iter().map(|diagnostic| {
+                let mut sarif_result = json!({
+                    "level": sarif_level(diagnostic.severity),
+                    "message": { "text": diagnostic.message },
+                    "locations": [{
+                        "physicalLocation": {
+                            "artifactLocation": { "uri": display_path(&result.path) },
+                            "region": {
+                                "startLine": diagnostic.start_point.row + 1,
+                                "startColumn": char_column(&result.source, diagnostic.start_point) + 1,
+                                "endLine": diagnostic.end_point.row + 1,
+                                "endColumn": char_column(&result.source, diagnostic.end_point) + 1,
+                            }
+                        }
+                    }]
+                });
+                // NOTE: the ruleId cannot be null, the one without rule is the error such as the io error
+                if let Some(rule) = diagnostic.rule {
+                    sarif_result["ruleId"] = json!(rule);
+                }
+                sarif_result
+            })
+        })
+        .collect();
+    let sarif_rules: Vec<serde_json::Value> = rules::all_rules()
+        .map(|rule| {
+            json!({
+                "id": rule.id,
+                "shortDescription": { "text": rule.description },
+                "helpUri": rule.doc_url(),
+                "defaultConfiguration": {
+                    "level": rule.level.severity().map_or("none", |severity| sarif_level(Some(severity))),
+                },
+            })
+        })
+        .collect();
+    let sarif = json!({
+        "$schema": SARIF_SCHEMA,
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "neocmakelsp",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "informationUri": env!("CARGO_PKG_REPOSITORY"),
+                    "rules": sarif_rules,
+                }
+            },
+            "results": sarif_results,
+            "columnKind": "unicodeCodePoints",
+        }]
+    });
+    serde_json::to_string_pretty(&sarif).unwrap()
+}
+
+/// print the results in the format
+pub fn lint_output(results: &[LintResult], format: LintOutputFormat) -> String {
+    match format {
+        LintOutputFormat::Human => human_output(results),
+        LintOutputFormat::Json => format!("{}\n", json_output(results)),
+        LintOutputFormat::Sarif => format!("{}\n", sarif_output(results)),
+    }
+}
+
+#[cfg(test)]
+mod lint_test {
+    use super::*;
+
+    fn get_results() -> Vec<LintResult> {
+        vec![
+            LintResult {
+                path: PathBuf::from("./CMakeLists.txt"),
+                source: "project(demo)\nfind_package(Qt6 REQUIRED)\n\nMESSAGE(STATUS abc)\n"
+                    .to_string(),
+                diagnostics: vec![
+                    ErrorInformation {
+                        start_point: Point { row: 1, column: 8 },
+                        end_point: Point { row: 1, column: 20 },
+                        message: "Cannot find such package".to_string(),
+                        severity: Some(DiagnosticSeverity::ERROR),
+                        fix: None,
+                        rule: Some("missing-package"),
+                    },
+                    ErrorInformation {
+                        start_point: Point { row: 3, column: 0 },
+                        end_point: Point { row: 3, column: 7 },
+                        message: "suggested to use lowercase".to_string(),
+                        severity: Some(DiagnosticSeverity::HINT),
+                        fix: None,
+                        rule: None,
+                    },
+                ],
+            },
+            LintResult {
+                path: PathBuf::from("cmake/utils.cmake"),
+                source: String::new(),
+                diagnostics: vec![],
+            },
+        ]
+    }
+
+    #[test]
+    fn tst_lint_human_output() {
+        let results = get_results();
+        assert!(has_error(&results));
+        assert!(!has_error(&results[1..]));
+        assert_eq!(
+            lint_output(&results, LintOutputFormat::Human),
+            r#"CMakeLists.txt:2:9: error: Cannot find such package [missing-package]
+CMakeLists.txt:4:1: hint: suggested to use lowercase
+2 files checked, 1 errors, 0 warnings
+"#
+        );
+    }
+
+    #[test]
+    fn tst_lint_json_output() {
+        let output: serde_json::Value =
+            serde_json::from_str(&lint_output(&get_results(), LintOutputFormat::Json)).unwrap();
+        assert_eq!(
+            output[0],
+            json!({
+                "path": "CMakeLists.txt",
+                "line": 2,
+                "column": 9,
+                "end_line": 2,
+                "end_column": 21,
+                "severity": "error",
+                "message": "Cannot find such package",
+                "rule": "missing-package",
+            })
+        );
+        assert_eq!(output.as_array().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn tst_lint_sarif_output() {
+        let output: serde_json::Value =
+            serde_json::from_str(&lint_output(&get_results(), LintOutputFormat::Sarif)).unwrap();
+        assert_eq!(output["version"], "2.1.0");
+        let results = &output["runs"][0]["results"];
+        assert_eq!(results[0]["level"], "error");
+        assert_eq!(results[1]["level"], "note");
+        assert_eq!(results[0]["ruleId"], "missing-package");
+        assert!(results[1].get("ruleId").is_none());
+        assert_eq!(output["runs"][0]["columnKind"], "unicodeCodePoints");
+        assert_eq!(
+            output["runs"][0]["tool"]["driver"]["rules"]
+                .as_array()
+                .unwrap()
+                .len(),
+            rules::all_rules().count()
+        );
+        assert_eq!(
+            results[0]["locations"][0]["physicalLocation"]["artifactLocation"]["uri"],
+            "CMakeLists.txt"
+        );
+        assert_eq!(
+            results[0]["locations"][0]["physicalLocation"]["region"]["startLine"],
+            2
+        );
+    }
+
+    #[test]
+    fn tst_lint_char_column() {
+        let results = vec![LintResult {
+            path: PathBuf::from("utils.cmake"),
+            source: "message(\"é\" ${A})\n".to_string(),
+            diagnostics: vec![ErrorInformation {
+                start_point: Point { row: 0, column: 13 },
+                end_point: Point { row: 0, column: 17 },
+                message: "Unknown variable".to_string(),
+                severity: Some(DiagnosticSeverity::WARNING),
+                fix: None,
+                rule: None,
+            }],
+        }];
+        assert!(lint_output(&results, LintOutputFormat::Human)
+            .starts_with("utils.cmake:1:13: warning: Unknown variable\n"));
+        let output: serde_json::Value =
+            serde_json::from_str(&lint_output(&results, LintOutputFormat::Json)).unwrap();
+        assert_eq!(output[0]["column"], 13);
+        assert_eq!(output[0]["end_column"], 17);
+        let output: serde_json::Value =
+            serde_json::from_str(&lint_output(&results, LintOutputFormat::Sarif)).unwrap();
+        let region = &output["runs"][0]["results"][0]["locations"][0]["physicalLocation"]["region"];
+        assert_eq!(region["startColumn"], 13);
+        assert_eq!(region["endColumn"], 17);
+    }
+}
@@ -1,4 +1,3 @@
-use ini::Ini;
 use std::io::prelude::*;
 use std::net::{Ipv4Addr, SocketAddr};
 use std::path::{Path, PathBuf};
@@ -14,30 +13,44 @@ use treesitter_nodetypes as CMakeNodeKinds;
 use tokio::net::TcpListener;
 mod ast;
 mod clapargs;
+mod code_action;
 mod complete;
 mod config;
 mod consts;
+mod document;
+mod document_highlight;
 mod document_link;
+mod editorconfig;
 mod fileapi;
 mod filewatcher;
+mod folding_range;
 mod formatting;
 mod gammar;
+mod genex;
 mod hover;
+mod inlay_hint;
 mod jump;
 mod languageserver;
+mod lint;
+mod references;
+mod rename;
 mod scansubs;
 mod search;
 mod semantic_token;
 mod shellcomplete;
+mod signature_help;
 mod utils;
+mod workspace_symbol;
 use tower_lsp::lsp_types::Url;
 
 use clapargs::NeocmakeCli;
+use editorconfig::EditConfigSetting;
 
 #[derive(Debug)]
 struct BackendInitInfo {
     pub scan_cmake_in_package: bool,
     pub enable_lint: bool,
+    pub format_line_width: Option<usize>,
 }
 
 /// Beckend
@@ -50,54 +63,49 @@ struct Backend {
     root_path: Arc<Mutex<Option<PathBuf>>>,
 }
 
-#[derive(Debug, PartialEq, Eq)]
-struct EditConfigSetting {
-    use_space: bool,
-    indent_size: u32,
-    insert_final_newline: bool,
-}
-
-impl Default for EditConfigSetting {
-    fn default() -> Self {
-        Self {
-            use_space: true,
-            indent_size: 2,
-            insert_final_newline: false,
+// the files and the cmake files in the folders, the ignored ones and the missing paths are skipped
+fn get_cmake_files(paths: &[String]) -> Vec<PathBuf> {
+    let mut files = vec![];
+    for path in paths {
+        let path = Path::new(path.as_str());
+        if !path.exists() {
+            continue;
+        }
+        if path.is_file() {
+            files.push(path.to_path_buf());
+            continue;
+        }
+        for results in ignore::Walk::new(path).flatten() {
+            let file_path = results.path();
+            if file_path.is_dir() {
+                continue;
+            }
+            if file_path.ends_with("CMakeLists.txt")
+                || file_path.extension().is_some_and(|ex| ex == "cmake")
+            {
+                files.push(file_path.to_path_buf());
+            }
         }
     }
+    files
 }
 
-fn editconfig_setting() -> Option<EditConfigSetting> {
-    let editconfig_path = std::path::Path::new(".editorconfig");
-    if !editconfig_path.exists() {
-        return None;
-    }
-    editconfig_setting_read(editconfig_path)
-}
-
-fn editconfig_setting_read<P: AsRef<Path>>(editconfig_path: P) -> Option<EditConfigSetting> {
-    let conf = Ini::load_from_file(editconfig_path).unwrap();
-
-    let cmakesession = conf.section(Some("CMakeLists.txt"))?;
-
-    let indent_style = cmakesession.get("indent_style").unwrap_or("space");
-    let use_space = indent_style == "space";
-
-    let insert_final_newline =
-        cmakesession.get("insert_final_newline").unwrap_or("false") == "true";
-
-    let indent_size = cmakesession.get("indent_size").unwrap_or("2");
-    let indent_size: u32 = if use_space {
-        indent_size.parse::<u32>().unwrap_or(2)
-    } else {
-        1
-    };
-
-    Some(EditConfigSetting {
+// the style of the file from its .editorconfig and the config of the project
+fn cli_format_style(
+    file_path: &Path,
+    format_config: config::FormatStyleConfig,
+) -> (formatting::FormatStyle, bool) {
+    let EditConfigSetting {
         use_space,
         indent_size,
         insert_final_newline,
-    })
+        trim_trailing_whitespace,
+        max_line_length,
+    } = editorconfig::editconfig_setting(file_path);
+    let mut style =
+        formatting::FormatStyle::new(indent_size, use_space, max_line_length, format_config);
+    style.trim_trailing_whitespace = trim_trailing_whitespace;
+    (style, insert_final_newline)
 }
 
 #[tokio::main]
@@ -117,6 +125,7 @@ async fn main() {
                 init_info: Arc::new(Mutex::new(BackendInitInfo {
                     scan_cmake_in_package: true,
                     enable_lint: true,
+                    format_line_width: None,
                 })),
                 root_path: Arc::new(Mutex::new(None)),
             });
@@ -152,6 +161,7 @@ async fn main() {
                 init_info: Arc::new(Mutex::new(BackendInitInfo {
                     scan_cmake_in_package: true,
                     enable_lint: true,
+                    format_line_width: None,
                 })),
                 root_path: Arc::new(Mutex::new(None)),
             });
@@ -179,15 +189,60 @@ async fn main() {
         NeocmakeCli::Format {
             format_paths,
             hasoverride,
+            check,
+            diff,
+            stdin_filepath,
         } => {
-            use ignore::Walk;
-            use std::path::Path;
-            let EditConfigSetting {
-                use_space,
-                indent_size,
-                insert_final_newline,
-            } = editconfig_setting().unwrap_or_default();
-            let format_file = |format_file: &Path| {
+            if format_paths.iter().any(|path| path == "-") {
+                if format_paths.len() != 1 || hasoverride {
+                    eprintln!("`-` cannot be used with other paths or --override");
+                    std::process::exit(2);
+                }
+                let neocmake_config = match &stdin_filepath {
+                    Some(stdin_filepath) => config::find_neocmake_config(stdin_filepath),
+                    None => config::read_neocmake_config("."),
+                };
+                // NOTE: the content is thought as the CMakeLists.txt in the current directory
+                let (style, insert_final_newline) = cli_format_style(
+                    stdin_filepath
+                        .as_deref()
+                        .unwrap_or(Path::new("CMakeLists.txt")),
+                    neocmake_config.format,
+                );
+                let mut buf = String::new();
+                if let Err(e) = std::io::stdin().read_to_string(&mut buf) {
+                    eprintln!("cannot read stdin: {e}");
+                    std::process::exit(1);
+                }
+                let Some(context) = formatting::get_format_cli(&buf, &style, insert_final_newline)
+                else {
+                    // NOTE: exit with error, so the editors will keep the buffer unchanged
+                    eprintln!("There is error in stdin");
+                    std::process::exit(1);
+                };
+                if check || diff {
+                    let display_path = stdin_filepath
+                        .map(|path| path.display().to_string())
+                        .unwrap_or("<stdin>".to_string());
+                    let Some(unified_diff) =
+                        formatting::get_unified_diff(&display_path, &buf, &context)
+                    else {
+                        return;
+                    };
+                    if check {
+                        println!("{display_path}");
+                    }
+                    if diff {
+                        print!("{unified_diff}");
+                    }
+                    std::process::exit(1);
+                }
+                print!("{context}");
+                return;
+            }
+            // the files which are not formatted or cannot be formatted in check and diff mode
+            let mut failed_count = 0;
+            let mut format_file = |format_file: &Path| {
                 let mut file = match std::fs::OpenOptions::new()
                     .read(true)
                     .write(hasoverride)
@@ -196,16 +251,36 @@ async fn main() {
                     Ok(file) => file,
                     Err(e) => {
                         println!("cannot read file {} :{e}", format_file.display());
+                        failed_count += 1;
                         return;
                     }
                 };
                 let mut buf = String::new();
                 if let Err(e) = file.read_to_string(&mut buf) {
                     println!("cannot read {} : error {}", format_file.display(), e);
+                    failed_count += 1;
                     return;
                 }
-                match formatting::get_format_cli(&buf, indent_size, use_space, insert_final_newline)
-                {
+                // NOTE: the files can be in different projects, each one uses its own config
+                let format_config = config::find_neocmake_config(format_file).format;
+                let (style, insert_final_newline) = cli_format_style(format_file, format_config);
+                match formatting::get_format_cli(&buf, &style, insert_final_newline) {
+                    Some(context) if check || diff => {
+                        let Some(unified_diff) = formatting::get_unified_diff(
+                            &format_file.display().to_string(),
+                            &buf,
+                            &context,
+                        ) else {
+                            return;
+                        };
+                        failed_count += 1;
+                        if check {
+                            println!("{}", format_file.display());
+                        }
+                        if diff {
+                            print!("{unified_diff}");
+                        }
+                    }
                     Some(context) => {
                         if hasoverride {
                             if let Err(e) = file.set_len(0) {
@@ -225,119 +300,40 @@ async fn main() {
                     }
                     None => {
                         println!("There is error in file: {}", format_file.display());
+                        failed_count += 1;
                     }
                 }
             };
 
-            for format_path in format_paths {
-                let toformatpath = Path::new(format_path.as_str());
-                if !toformatpath.exists() {
-                    continue;
-                }
-                if toformatpath.is_file() {
-                    format_file(toformatpath);
-                } else {
-                    for results in Walk::new(&format_path).flatten() {
-                        let file_path = results.path();
-                        if file_path.is_dir() {
-                            continue;
-                        }
-                        if file_path.ends_with("CMakeLists.txt")
-                            || file_path.extension().is_some_and(|ex| ex == "cmake")
-                        {
-                            format_file(file_path);
-                        }
-                    }
-                }
+            for file_path in get_cmake_files(&format_paths) {
+                format_file(&file_path);
+            }
+            if (check || diff) && failed_count != 0 {
+                std::process::exit(1);
+            }
+        }
+        NeocmakeCli::Lint {
+            lint_paths,
+            output_format,
+            build_dir,
+        } => {
+            lint::load_build_dir(build_dir.unwrap_or(PathBuf::from("build")));
+            let missing_paths: Vec<&String> = lint_paths
+                .iter()
+                .filter(|path| !Path::new(path.as_str()).exists())
+                .collect();
+            for path in &missing_paths {
+                eprintln!("cannot find {path}");
+            }
+            let results: Vec<lint::LintResult> = get_cmake_files(&lint_paths)
+                .iter()
+                .map(|file_path| lint::lint_file(file_path))
+                .collect();
+            print!("{}", lint::lint_output(&results, output_format));
+            if lint::has_error(&results) || !missing_paths.is_empty() {
+                std::process::exit(1);
             }
         }
         NeocmakeCli::GenCompletions { shell } => shellcomplete::generate_shell_completions(shell),
     }
 }
-
-#[cfg(test)]
-mod editorconfig_test {
-    use super::{editconfig_setting_read, EditConfigSetting};
-    use std::io::prelude::*;
-    use tempfile::NamedTempFile;
-    #[test]
-    fn tst_editconfig_tab() {
-        let mut temp_file = NamedTempFile::new().unwrap();
-        let content = r#"
-root = true
-
-[*.{cmake}]
-indent_style = tab
-indent_size = 2
-
-[CMakeLists.txt]
-indent_style = tab
-indent_size = 2
-
-[*.{lua}]
-indent_style = space
-indent_size = 4
-"#;
-        writeln!(temp_file, "{}", content).unwrap();
-
-        assert_eq!(
-            editconfig_setting_read(temp_file),
-            Some(EditConfigSetting {
-                use_space: false,
-                indent_size: 1,
-                insert_final_newline: false
-            })
-        )
-    }
-    #[test]
-    fn tst_editconfig_space() {
-        let mut temp_file = NamedTempFile::new().unwrap();
-        let content = r#"
-root = true
-
-[CMakeLists.txt]
-indent_style = space
-indent_size = 2
-
-[*.{lua}]
-indent_style = space
-indent_size = 4
-"#;
-        writeln!(temp_file, "{}", content).unwrap();
-
-        assert_eq!(
-            editconfig_setting_read(temp_file),
-            Some(EditConfigSetting {
-                use_space: true,
-                indent_size: 2,
-                insert_final_newline: false
-            })
-        )
-    }
-    #[test]
-    fn tst_editconfig_lastline() {
-        let mut temp_file = NamedTempFile::new().unwrap();
-        let content = r#"
-root = true
-
-[CMakeLists.txt]
-indent_style = space
-indent_size = 2
-insert_final_newline = true
-
-[*.{lua}]
-indent_style = space
-indent_size = 4
-"#;
-        writeln!(temp_file, "{}", content).unwrap();
-
-        assert_eq!(
-            editconfig_setting_read(temp_file),
-            Some(EditConfigSetting {
-                use_space: true,
-                indent_size: 2,
-                insert_final_newline: true
-            })
-        )
-    }
-}
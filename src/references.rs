@@ -0,0 +1,580 @@
+/// Find the references of variables, functions and macros in the whole project
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::LazyLock;
+
+use tokio::sync::Mutex;
+use tower_lsp::lsp_types::{Location, Position, Range, Url};
+use tree_sitter::Node;
+
+use crate::complete::{buildin_command_keywords, buildin_variable_positions};
+use crate::consts::TARGET_DEFINE_COMMANDS;
+use crate::document;
+use crate::scansubs::TREE_MAP;
+use crate::utils::treehelper::{command_arguments, node_text, ToPosition};
+use crate::utils::{include_is_module, remove_quotation_and_replace_placeholders};
+use crate::CMakeNodeKinds;
+
+const VARIABLE_DEFINE_COMMANDS: &[&str] = &["set", "option"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReferenceKind {
+    /// the first argument of set or option
+    VariableDef,
+    /// the name of function or macro
+    CommandDef,
+    /// the first argument of add_library, add_executable or add_custom_target
+    TargetDef,
+    /// like ${VAR}
+    Variable,
+    /// the bare argument in if(VAR)
+    Condition,
+    /// the place where the function or macro is called
+    Call,
+    /// the bare argument at the place of a variable in the signature, like list(APPEND VAR)
+    VariableArgument,
+    /// the bare argument of normal command, maybe a target
+    Argument,
+}
+
+/// What the name stands for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReferenceSymbol {
+    Variable,
+    Command,
+    Target,
+}
+
+impl ReferenceKind {
+    pub fn is_definition(&self) -> bool {
+        matches!(self, Self::VariableDef | Self::CommandDef | Self::TargetDef)
+    }
+
+    pub fn symbol(&self) -> ReferenceSymbol {
+        match self {
+            Self::VariableDef | Self::Variable | Self::Condition | Self::VariableArgument => {
+                ReferenceSymbol::Variable
+            }
+            Self::CommandDef | Self::Call => ReferenceSymbol::Command,
+            Self::TargetDef | Self::Argument => ReferenceSymbol::Target,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReferenceUnit {
+    pub name: String,
+    pub range: Range,
+    pub kind: ReferenceKind,
+}
+
+impl ReferenceUnit {
+    pub fn is_match(&self, name: &str, symbol: ReferenceSymbol) -> bool {
+        if self.kind.symbol() != symbol {
+            return false;
+        }
+        // NOTE: commands are case insensitive in cmake, but variables are not
+        if symbol == ReferenceSymbol::Command {
+            self.name.eq_ignore_ascii_case(name)
+        } else {
+            self.name == name
+        }
+    }
+
+    pub fn contains(&self, location: Position) -> bool {
+        self.range.start.line == location.line
+            && self.range.start.character <= location.character
+            && self.range.end.character >= location.character
+    }
+}
+
+/// All the references in one file, and the local files it includes
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReferenceFileInfo {
+    pub units: Vec<ReferenceUnit>,
+    pub includes: Vec<PathBuf>,
+}
+
+impl ReferenceFileInfo {
+    pub fn has_target_definition(&self, name: &str) -> bool {
+        self.units.iter().any(|unit| {
+            unit.kind == ReferenceKind::TargetDef && unit.is_match(name, ReferenceSymbol::Target)
+        })
+    }
+}
+
+pub type ReferenceKV = HashMap<PathBuf, ReferenceFileInfo>;
+
+pub static REFERENCE_CACHE: LazyLock<Arc<Mutex<ReferenceKV>>> =
+    LazyLock::new(|| Arc::new(Mutex::new(HashMap::new())));
+
+/// scan the references of the file, the tree of the opened document is reused
+pub async fn scan_references<P: AsRef<Path>>(path: P, context: &str) -> Option<ReferenceFileInfo> {
+    let path = path.as_ref();
+    let uri = Url::from_file_path(path).ok()?;
+    let tree = document::get_tree(&uri, context).await?;
+    Some(scan_tree_references(tree.root_node(), context, path))
+}
+
+/// scan the references of the tree which is already parsed
+pub fn scan_tree_references<P: AsRef<Path>>(
+    root: Node,
+    context: &str,
+    path: P,
+) -> ReferenceFileInfo {
+    let mut info = ReferenceFileInfo::default();
+    scan_references_inner(root, &context.lines().collect(), path.as_ref(), &mut info);
+    info
+}
+
+pub async fn update_cache<P: AsRef<Path>>(path: P, context: &str) -> Option<ReferenceFileInfo> {
+    let info = scan_references(path.as_ref(), context).await?;
+    let mut cache = REFERENCE_CACHE.lock().await;
+    cache.insert(path.as_ref().to_path_buf(), info.clone());
+    Some(info)
+}
+
+/// the file is scanned again when it is needed, after it is changed outside or closed
+pub async fn remove_cache<P: AsRef<Path>>(path: P) {
+    let mut cache = REFERENCE_CACHE.lock().await;
+    cache.remove(path.as_ref());
+}
+
+pub async fn get_file_references(path: &Path) -> Option<ReferenceFileInfo> {
+    let cache = REFERENCE_CACHE.lock().await;
+    if let Some(info) = cache.get(path) {
+        return Some(info.clone());
+    }
+    drop(cache);
+    let buffer_content = match Url::from_file_path(path) {
+        Ok(uri) => document::get_text(&uri).await,
+        Err(_) => None,
+    };
+    let context = match buffer_content {
+        Some(context) => context,
+        None => tokio::fs::read_to_string(path).await.ok()?,
+    };
+    update_cache(path, &context).await
+}
+
+fn node_range(node: &Node) -> Range {
+    Range {
+        start: node.start_position().to_position(),
+        end: node.end_position().to_position(),
+    }
+}
+
+fn first_argument<'a>(command: &Node<'a>) -> Option<Node<'a>> {
+    command_arguments(*command).into_iter().next()
+}
+
+fn get_define_kind(command_name: &str) -> Option<ReferenceKind> {
+    if VARIABLE_DEFINE_COMMANDS.contains(&command_name) {
+        Some(ReferenceKind::VariableDef)
+    } else if TARGET_DEFINE_COMMANDS.contains(&command_name) {
+        Some(ReferenceKind::TargetDef)
+    } else {
+        None
+    }
+}
+
+// the unquoted argument without any variable inside
+fn get_bare_argument<'a>(argument: &Node<'a>) -> Option<Node<'a>> {
+    argument
+        .child(0)
+        .filter(|node| node.kind() == CMakeNodeKinds::UNQUOTED_ARGUMENT && node.child_count() == 0)
+}
+
+fn scan_references_inner(
+    input: Node,
+    source: &Vec<&str>,
+    local_path: &Path,
+    info: &mut ReferenceFileInfo,
+) {
+    let mut course = input.walk();
+    for child in input.children(&mut course) {
+        match child.kind() {
+            CMakeNodeKinds::NORMAL_COMMAND => {
+                let Some(identifier) = child.child(0) else {
+                    continue;
+                };
+                let Some(command_name) = node_text(source, &identifier) else {
+                    continue;
+                };
+                info.units.push(ReferenceUnit {
+                    name: command_name.to_string(),
+                    range: node_range(&identifier),
+                    kind: ReferenceKind::Call,
+                });
+                let lowercase_name = command_name.to_lowercase();
+                let first = first_argument(&child);
+                if let Some(first) = first {
+                    if let Some(kind) = get_define_kind(&lowercase_name) {
+                        if let Some(name) = node_text(source, &first) {
+                            info.units.push(ReferenceUnit {
+                                name: name.to_string(),
+                                range: node_range(&first),
+                                kind,
+                            });
+                        }
+                    } else if lowercase_name == "include" {
+                        if let Some(name) = node_text(source, &first)
+                            .and_then(remove_quotation_and_replace_placeholders)
+                        {
+                            if let (false, Some(parent)) =
+                                (include_is_module(&name), local_path.parent())
+                            {
+                                info.includes.push(parent.join(name));
+                            }
+                        }
+                    }
+                }
+                // NOTE: the keywords like STATUS or PUBLIC are not the names of anything
+                let keywords = buildin_command_keywords(&lowercase_name);
+                let bare_arguments: Vec<Option<Node>> = command_arguments(child)
+                    .iter()
+                    .map(get_bare_argument)
+                    .collect();
+                let texts: Vec<Option<&str>> = bare_arguments
+                    .iter()
+                    .map(|unquoted| unquoted.and_then(|unquoted| node_text(source, &unquoted)))
+                    .collect();
+                let variables = buildin_variable_positions(&lowercase_name, &texts);
+                for (index, (unquoted, name)) in bare_arguments.iter().zip(&texts).enumerate() {
+                    if index == 0 && get_define_kind(&lowercase_name).is_some() {
+                        continue;
+                    }
+                    let (Some(unquoted), Some(name)) = (unquoted, name) else {
+                        continue;
+                    };
+                    if keywords.contains(name) {
+                        continue;
+                    }
+                    let kind = if variables.contains(&index) {
+                        ReferenceKind::VariableArgument
+                    } else {
+                        ReferenceKind::Argument
+                    };
+                    info.units.push(ReferenceUnit {
+                        name: name.to_string(),
+                        range: node_range(unquoted),
+                        kind,
+                    });
+                }
+                scan_references_inner(child, source, local_path, info);
+            }
+            CMakeNodeKinds::FUNCTION_DEF | CMakeNodeKinds::MACRO_DEF => {
+                if let Some(name_node) = child.child(0).and_then(|command| first_argument(&command))
+                {
+                    if let Some(name) = node_text(source, &name_node) {
+                        info.units.push(ReferenceUnit {
+                            name: name.to_string(),
+                            range: node_range(&name_node),
+                            kind: ReferenceKind::CommandDef,
+                        });
+                    }
+                }
+                scan_references_inner(child, source, local_path, info);
+            }
+            CMakeNodeKinds::IF_COMMAND
+            | CMakeNodeKinds::ELSEIF_COMMAND
+            | CMakeNodeKinds::WHILE_COMMAND => {
+                for argument in command_arguments(child) {
+                    // NOTE: only the bare word like if(VAR), ${VAR} is handled below
+                    let Some(unquoted) = get_bare_argument(&argument) else {
+                        continue;
+                    };
+                    if let Some(name) = node_text(source, &unquoted) {
+                        info.units.push(ReferenceUnit {
+                            name: name.to_string(),
+                            range: node_range(&unquoted),
+                            kind: ReferenceKind::Condition,
+                        });
+                    }
+                }
+                scan_references_inner(child, source, local_path, info);
+            }
+            CMakeNodeKinds::NORMAL_VAR | CMakeNodeKinds::CACHE_VAR => {
+                let mut var_course = child.walk();
+                for variable in child.children(&mut var_course) {
+                    if variable.kind() != CMakeNodeKinds::VARIABLE {
+                        continue;
+                    }
+                    if variable.child_count() == 0 {
+                        if let Some(name) = node_text(source, &variable) {
+                            info.units.push(ReferenceUnit {
+                                name: name.to_string(),
+                                range: node_range(&variable),
+                                kind: ReferenceKind::Variable,
+                            });
+                        }
+                    } else {
+                        // like ${${NAME}_DIR}
+                        scan_references_inner(variable, source, local_path, info);
+                    }
+                }
+            }
+            _ => scan_references_inner(child, source, local_path, info),
+        }
+    }
+}
+
+/// get all the files which should be searched, the files in TREE_MAP come first
+async fn get_project_files(local_path: &Path) -> Vec<PathBuf> {
+    let tree_map = TREE_MAP.lock().await;
+    let mut files: Vec<PathBuf> = tree_map
+        .iter()
+        .flat_map(|(sub, top)| [sub.clone(), top.clone()])
+        .collect();
+    drop(tree_map);
+    files.sort();
+    files.dedup();
+    files.retain(|file| file != local_path);
+    files.insert(0, local_path.to_path_buf());
+    files
+}
+
+/// The symbol under the cursor and all the places it appears
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SymbolReferences {
+    pub name: String,
+    pub symbol: ReferenceSymbol,
+    /// the range of the symbol under the cursor
+    pub range: Range,
+    pub references: Vec<(Url, ReferenceUnit)>,
+}
+
+impl SymbolReferences {
+    pub fn has_definition(&self) -> bool {
+        self.references
+            .iter()
+            .any(|(_, unit)| unit.kind.is_definition())
+    }
+}
+
+/// find all the places where the symbol under the location appears in the project
+pub async fn find_references<P: AsRef<Path>>(
+    location: Position,
+    source: &str,
+    local_path: P,
+) -> Option<SymbolReferences> {
+    let local_path = local_path.as_ref();
+    let current = scan_references(local_path, source).await?;
+    let current_unit = current
+        .units
+        .iter()
+        .find(|unit| unit.contains(location))?
+        .clone();
+
+    let mut to_scan = get_project_files(local_path).await;
+    let mut infos: Vec<(Url, ReferenceFileInfo)> = vec![];
+    let mut index = 0;
+    while index < to_scan.len() {
+        let path = to_scan[index].clone();
+        index += 1;
+        let info = if path == local_path {
+            current.clone()
+        } else {
+            let Some(info) = get_file_references(&path).await else {
+                continue;
+            };
+            info
+        };
+        for include in info.includes.iter() {
+            if !to_scan.contains(include) {
+                to_scan.push(include.clone());
+            }
+        }
+        let Ok(uri) = Url::from_file_path(&path) else {
+            continue;
+        };
+        infos.push((uri, info));
+    }
+
+    let name = current_unit.name;
+    let mut symbol = current_unit.kind.symbol();
+    // NOTE: a bare argument is a target only when the target is defined in the project
+    if symbol == ReferenceSymbol::Target
+        && !infos
+            .iter()
+            .any(|(_, info)| info.has_target_definition(&name))
+    {
+        symbol = ReferenceSymbol::Variable;
+    }
+
+    let references = infos
+        .into_iter()
+        .flat_map(|(uri, info)| {
+            info.units
+                .into_iter()
+                .filter(|unit| unit.is_match(&name, symbol))
+                .map(move |unit| (uri.clone(), unit))
+        })
+        .collect();
+
+    Some(SymbolReferences {
+        name,
+        symbol,
+        range: current_unit.range,
+        references,
+    })
+}
+
+/// find all the references of the symbol under the location
+pub async fn get_references<P: AsRef<Path>>(
+    location: Position,
+    source: &str,
+    local_path: P,
+    include_declaration: bool,
+) -> Option<Vec<Location>> {
+    let symbol_references = find_references(location, source, local_path).await?;
+    let locations: Vec<Location> = symbol_references
+        .references
+        .into_iter()
+        .filter(|(_, unit)| include_declaration || !unit.kind.is_definition())
+        .map(|(uri, unit)| Location {
+            uri,
+            range: unit.range,
+        })
+        .collect();
+    if locations.is_empty() {
+        None
+    } else {
+        Some(locations)
+    }
+}
+
+#[cfg(test)]
+mod references_test {
+    use super::*;
+
+    fn range(line: u32, start: u32, end: u32) -> Range {
+        Range {
+            start: Position {
+                line,
+                character: start,
+            },
+            end: Position {
+                line,
+                character: end,
+            },
+        }
+    }
+
+    #[tokio::test]
+    async fn tst_scan_references() {
+        let source = r#"set(ABCD 1234)
+if(ABCD)
+  message(STATUS "${ABCD}_DIR")
+endif()
+function(my_helper)
+endfunction()
+MY_HELPER()
+"#;
+        let info = scan_references("/tmp/CMakeLists.txt", source)
+            .await
+            .unwrap();
+        let abcd: Vec<&ReferenceUnit> = info
+            .units
+            .iter()
+            .filter(|unit| unit.is_match("ABCD", ReferenceSymbol::Variable))
+            .collect();
+        assert_eq!(
+            abcd,
+            vec![
+                &ReferenceUnit {
+                    name: "ABCD".to_string(),
+                    range: range(0, 4, 8),
+                    kind: ReferenceKind::VariableDef,
+                },
+                &ReferenceUnit {
+                    name: "ABCD".to_string(),
+                    range: range(1, 3, 7),
+                    kind: ReferenceKind::Condition,
+                },
+                &ReferenceUnit {
+                    name: "ABCD".to_string(),
+                    range: range(2, 20, 24),
+                    kind: ReferenceKind::Variable,
+                },
+            ]
+        );
+        let helper: Vec<ReferenceKind> = info
+            .units
+            .iter()
+            .filter(|unit| unit.is_match("my_helper", ReferenceSymbol::Command))
+            .map(|unit| unit.kind)
+            .collect();
+        assert_eq!(helper, vec![ReferenceKind::CommandDef, ReferenceKind::Call]);
+    }
+
+    #[tokio::test]
+    async fn tst_remove_cache() {
+        let path = Path::new("/tmp/remove_cache/helper.cmake");
+        REFERENCE_CACHE
+            .lock()
+            .await
+            .insert(path.to_path_buf(), ReferenceFileInfo::default());
+        remove_cache(path).await;
+        assert!(!REFERENCE_CACHE.lock().await.contains_key(path));
+    }
+
+    #[tokio::test]
+    async fn tst_references_in_include() {
+        use std::fs::File;
+        use std::io::Write;
+        use tempfile::tempdir;
+
+        let dir = tempdir().unwrap();
+        let top_cmake = dir.path().join("CMakeLists.txt");
+        let top_source = r#"include(helper_refs.cmake)
+helper_refs_fun()
+"#;
+        let mut top_file = File::create_new(&top_cmake).unwrap();
+        top_file.write_all(top_source.as_bytes()).unwrap();
+        let helper_cmake = dir.path().join("helper_refs.cmake");
+        let mut helper_file = File::create_new(&helper_cmake).unwrap();
+        helper_file
+            .write_all(b"function(helper_refs_fun)\nendfunction()\n")
+            .unwrap();
+
+        let locations = get_references(
+            Position {
+                line: 1,
+                character: 3,
+            },
+            top_source,
+            &top_cmake,
+            true,
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            locations,
+            vec![
+                Location {
+                    uri: Url::from_file_path(&top_cmake).unwrap(),
+                    range: range(1, 0, 15),
+                },
+                Location {
+                    uri: Url::from_file_path(&helper_cmake).unwrap(),
+                    range: range(0, 9, 24),
+                },
+            ]
+        );
+
+        let locations = get_references(
+            Position {
+                line: 1,
+                character: 3,
+            },
+            top_source,
+            &top_cmake,
+            false,
+        )
+        .await
+        .unwrap();
+        assert_eq!(locations.len(), 1);
+    }
+}
@@ -0,0 +1,312 @@
+/// Rename variables, functions, macros and targets in the whole project
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::LazyLock;
+
+use tower_lsp::jsonrpc::{Error as LspError, Result};
+use tower_lsp::lsp_types::{Position, PrepareRenameResponse, TextEdit, Url, WorkspaceEdit};
+
+use crate::complete::BUILDIN_VARIABLE;
+use crate::references::{find_references, ReferenceSymbol, SymbolReferences};
+
+static COMMAND_NAME_REGEX: LazyLock<regex::Regex> =
+    LazyLock::new(|| regex::Regex::new(r"^[A-Za-z_][A-Za-z0-9_]*$").unwrap());
+
+static VARIABLE_NAME_REGEX: LazyLock<regex::Regex> =
+    LazyLock::new(|| regex::Regex::new(r"^[A-Za-z0-9_./+\-]+$").unwrap());
+
+fn is_buildin_variable(name: &str) -> bool {
+    BUILDIN_VARIABLE
+        .as_ref()
+        .is_ok_and(|variables| variables.iter().any(|variable| variable.label == name))
+}
+
+// NOTE: builtin commands, variables and targets from packages should not be renamed
+fn can_rename(symbol_references: &SymbolReferences, local_path: &Path) -> bool {
+    // the name under the cursor must be renamed too
+    let Ok(uri) = Url::from_file_path(local_path) else {
+        return false;
+    };
+    if !symbol_references
+        .references
+        .iter()
+        .any(|(reference_uri, unit)| *reference_uri == uri && unit.range == symbol_references.range)
+    {
+        return false;
+    }
+    match symbol_references.symbol {
+        ReferenceSymbol::Variable => {
+            symbol_references.has_definition() && !is_buildin_variable(&symbol_references.name)
+        }
+        ReferenceSymbol::Command | ReferenceSymbol::Target => symbol_references.has_definition(),
+    }
+}
+
+fn is_valid_name(symbol: ReferenceSymbol, new_name: &str) -> bool {
+    match symbol {
+        ReferenceSymbol::Command => COMMAND_NAME_REGEX.is_match(new_name),
+        ReferenceSymbol::Variable | ReferenceSymbol::Target => {
+            VARIABLE_NAME_REGEX.is_match(new_name)
+        }
+    }
+}
+
+pub async fn prepare_rename<P: AsRef<Path>>(
+    location: Position,
+    source: &str,
+    local_path: P,
+) -> Option<PrepareRenameResponse> {
+    let local_path = local_path.as_ref();
+    let symbol_references = find_references(location, source, local_path).await?;
+    if !can_rename(&symbol_references, local_path) {
+        return None;
+    }
+    Some(PrepareRenameResponse::Range(symbol_references.range))
+}
+
+pub async fn rename<P: AsRef<Path>>(
+    location: Position,
+    source: &str,
+    local_path: P,
+    new_name: &str,
+) -> Result<Option<WorkspaceEdit>> {
+    let local_path = local_path.as_ref();
+    let Some(symbol_references) = find_references(location, source, local_path).await else {
+        return Ok(None);
+    };
+    if !can_rename(&symbol_references, local_path) {
+        return Err(LspError::invalid_params(format!(
+            "Cannot rename {}, it is not defined in the project",
+            symbol_references.name
+        )));
+    }
+    if !is_valid_name(symbol_references.symbol, new_name) {
+        return Err(LspError::invalid_params(format!(
+            "{new_name} is not a valid name"
+        )));
+    }
+    let mut changes = HashMap::new();
+    for (uri, unit) in symbol_references.references {
+        changes.entry(uri).or_insert_with(Vec::new).push(TextEdit {
+            range: unit.range,
+            new_text: new_name.to_string(),
+        });
+    }
+    Ok(Some(WorkspaceEdit {
+        changes: Some(changes),
+        ..Default::default()
+    }))
+}
+
+#[cfg(test)]
+mod rename_test {
+    use super::*;
+    use crate::references::{ReferenceKind, ReferenceUnit};
+    use tower_lsp::lsp_types::Range;
+
+    #[test]
+    fn tst_valid_name() {
+        assert!(is_valid_name(ReferenceSymbol::Command, "my_helper"));
+        assert!(!is_valid_name(ReferenceSymbol::Command, "my-helper"));
+        assert!(is_valid_name(ReferenceSymbol::Target, "my-lib.core"));
+        assert!(!is_valid_name(ReferenceSymbol::Variable, "${ABCD}"));
+        assert!(!is_valid_name(ReferenceSymbol::Variable, ""));
+    }
+
+    #[test]
+    fn tst_can_rename() {
+        let local_path = Path::new("/tmp/can_rename/CMakeLists.txt");
+        let uri = Url::from_file_path(local_path).unwrap();
+        let range = |start, end| Range {
+            start: Position {
+                line: 0,
+                character: start,
+            },
+            end: Position {
+                line: 0,
+                character: end,
+            },
+        };
+        let mut symbol_references = SymbolReferences {
+            name: "FOO".to_string(),
+            symbol: ReferenceSymbol::Variable,
+            range: range(4, 7),
+            references: vec![(
+                uri,
+                ReferenceUnit {
+                    name: "FOO".to_string(),
+                    range: range(4, 7),
+                    kind: ReferenceKind::VariableDef,
+                },
+            )],
+        };
+        assert!(can_rename(&symbol_references, local_path));
+        assert!(!can_rename(
+            &symbol_references,
+            Path::new("/tmp/can_rename/other.cmake")
+        ));
+        symbol_references.references[0].1.kind = ReferenceKind::VariableArgument;
+        assert!(!can_rename(&symbol_references, local_path));
+        symbol_references.references[0].1.kind = ReferenceKind::VariableDef;
+        symbol_references.name = "CMAKE_CXX_STANDARD".to_string();
+        assert!(!can_rename(&symbol_references, local_path));
+        symbol_references.name = "FOO".to_string();
+        symbol_references.range = range(10, 13);
+        assert!(!can_rename(&symbol_references, local_path));
+    }
+
+    #[tokio::test]
+    async fn tst_rename_variable() {
+        use std::fs::File;
+        use std::io::Write;
+        use tempfile::tempdir;
+
+        let dir = tempdir().unwrap();
+        let top_cmake = dir.path().join("CMakeLists.txt");
+        let source = r#"set(FOO "abcd")
+set(FOO_DIR "${FOO}_DIR")
+if(FOO)
+endif()
+"#;
+        let mut top_file = File::create_new(&top_cmake).unwrap();
+        top_file.write_all(source.as_bytes()).unwrap();
+
+        let edit = rename(
+            Position {
+                line: 0,
+                character: 5,
+            },
+            source,
+            &top_cmake,
+            "BAR",
+        )
+        .await
+        .unwrap()
+        .unwrap();
+        let changes = edit.changes.unwrap();
+        let edits = changes
+            .get(&Url::from_file_path(&top_cmake).unwrap())
+            .unwrap();
+        let ranges: Vec<Range> = edits.iter().map(|edit| edit.range).collect();
+        let range = |line, start, end| Range {
+            start: Position {
+                line,
+                character: start,
+            },
+            end: Position {
+                line,
+                character: end,
+            },
+        };
+        assert_eq!(
+            ranges,
+            vec![range(0, 4, 7), range(1, 15, 18), range(2, 3, 6)]
+        );
+    }
+
+    #[tokio::test]
+    async fn tst_rename_bare_argument() {
+        use std::path::PathBuf;
+        let source = r#"set(FOO main.cpp)
+list(APPEND FOO FOO.cpp)
+math(EXPR FOO "1 + 2")
+message(STATUS "${FOO}" FOO)
+unset(FOO)
+"#;
+        let local_path = PathBuf::from("/tmp/rename_bare_argument/CMakeLists.txt");
+        let edit = rename(
+            Position {
+                line: 4,
+                character: 7,
+            },
+            source,
+            &local_path,
+            "BAR",
+        )
+        .await
+        .unwrap()
+        .unwrap();
+        let changes = edit.changes.unwrap();
+        let edits = changes
+            .get(&Url::from_file_path(&local_path).unwrap())
+            .unwrap();
+        let ranges: Vec<Range> = edits.iter().map(|edit| edit.range).collect();
+        let range = |line, start, end| Range {
+            start: Position {
+                line,
+                character: start,
+            },
+            end: Position {
+                line,
+                character: end,
+            },
+        };
+        assert_eq!(
+            ranges,
+            vec![
+                range(0, 4, 7),
+                range(1, 12, 15),
+                range(2, 10, 13),
+                range(3, 18, 21),
+                range(4, 6, 9)
+            ]
+        );
+        // the source files are not variables
+        assert!(prepare_rename(
+            Position {
+                line: 0,
+                character: 10,
+            },
+            source,
+            &local_path,
+        )
+        .await
+        .is_none());
+    }
+
+    #[tokio::test]
+    async fn tst_rename_buildin_variable() {
+        use std::path::PathBuf;
+        let source = "set(CMAKE_CXX_STANDARD 17)\nmessage(STATUS ${CMAKE_CXX_STANDARD})\n";
+        let local_path = PathBuf::from("/tmp/rename_buildin_variable/CMakeLists.txt");
+        assert!(rename(
+            Position {
+                line: 0,
+                character: 6,
+            },
+            source,
+            &local_path,
+            "MY_STANDARD",
+        )
+        .await
+        .is_err());
+    }
+
+    #[tokio::test]
+    async fn tst_rename_buildin_command() {
+        use std::path::PathBuf;
+        let source = "message(STATUS \"hello\")\n";
+        let local_path = PathBuf::from("/tmp/rename_buildin/CMakeLists.txt");
+        assert!(prepare_rename(
+            Position {
+                line: 0,
+                character: 2,
+            },
+            source,
+            &local_path,
+        )
+        .await
+        .is_none());
+        assert!(rename(
+            Position {
+                line: 0,
+                character: 2,
+            },
+            source,
+            &local_path,
+            "my_message",
+        )
+        .await
+        .is_err());
+    }
+}
@@ -1,18 +1,51 @@
 use tower_lsp::{
-    lsp_types::{SemanticToken, SemanticTokenType, SemanticTokens, SemanticTokensResult},
+    lsp_types::{
+        Range, SemanticToken, SemanticTokenModifier, SemanticTokenType, SemanticTokens,
+        SemanticTokensDelta, SemanticTokensEdit, SemanticTokensFullDeltaResult,
+        SemanticTokensRangeResult, SemanticTokensResult, Url,
+    },
     Client,
 };
 
-use std::sync::LazyLock;
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, LazyLock};
 
-use crate::consts::TREESITTER_CMAKE_LANGUAGE;
+use tokio::sync::Mutex;
 
+use crate::complete::{BUILDIN_COMMAND, BUILDIN_VARIABLE};
+use crate::gammar::condition;
+use crate::genex;
 use crate::CMakeNodeKinds;
 static NUMBERREGEX: LazyLock<regex::Regex> =
     LazyLock::new(|| regex::Regex::new(r"^\d+(?:\.+\d*)?").unwrap());
 
 const BOOL_VAL: &[&str] = &["ON", "OFF", "TRUE", "FALSE"];
-const UNIQUE_KEYWORD: &[&str] = &["AND", "NOT"];
+
+/// the first argument of these commands is where the variable or function is defined
+const DECLARATION_COMMANDS: &[&str] = &["set", "option", "function", "macro"];
+
+/// the commands marked as deprecated in the cmake document
+const DEPRECATED_COMMANDS: &[&str] = &[
+    "build_name",
+    "exec_program",
+    "export_library_dependencies",
+    "install_files",
+    "install_programs",
+    "install_targets",
+    "load_command",
+    "make_directory",
+    "output_required_files",
+    "qt_wrap_cpp",
+    "qt_wrap_ui",
+    "remove",
+    "subdir_depends",
+    "subdirs",
+    "use_mangled_mesa",
+    "utility_source",
+    "variable_requires",
+    "write_file",
+];
 
 pub const LEGEND_TYPE: &[SemanticTokenType] = &[
     SemanticTokenType::FUNCTION,
@@ -24,8 +57,46 @@ pub const LEGEND_TYPE: &[SemanticTokenType] = &[
     SemanticTokenType::KEYWORD,
     SemanticTokenType::OPERATOR,
     SemanticTokenType::PARAMETER,
+    SemanticTokenType::MACRO,
+];
+
+pub const LEGEND_MODIFIER: &[SemanticTokenModifier] = &[
+    SemanticTokenModifier::DECLARATION,
+    SemanticTokenModifier::READONLY,
+    SemanticTokenModifier::DEPRECATED,
+    SemanticTokenModifier::DEFAULT_LIBRARY,
 ];
 
+static BUILDIN_COMMAND_NAMES: LazyLock<HashSet<String>> = LazyLock::new(|| {
+    BUILDIN_COMMAND
+        .as_ref()
+        .map(|commands| {
+            commands
+                .iter()
+                .map(|command| command.label.to_lowercase())
+                .collect()
+        })
+        .unwrap_or_default()
+});
+
+static BUILDIN_VARIABLE_NAMES: LazyLock<HashSet<String>> = LazyLock::new(|| {
+    BUILDIN_VARIABLE
+        .as_ref()
+        .map(|variables| {
+            variables
+                .iter()
+                .map(|variable| variable.label.clone())
+                .collect()
+        })
+        .unwrap_or_default()
+});
+
+/// the last full result of every document, used by the delta request
+static SEMANTIC_TOKEN_CACHE: LazyLock<Arc<Mutex<HashMap<Url, SemanticTokens>>>> =
+    LazyLock::new(|| Arc::new(Mutex::new(HashMap::new())));
+
+static RESULT_ID: AtomicU64 = AtomicU64::new(0);
+
 fn get_token_position(tokentype: SemanticTokenType) -> u32 {
     LEGEND_TYPE
         .iter()
@@ -33,20 +104,155 @@ fn get_token_position(tokentype: SemanticTokenType) -> u32 {
         .unwrap() as u32
 }
 
-pub async fn semantic_token(_client: &Client, context: &str) -> Option<SemanticTokensResult> {
-    let mut parse = tree_sitter::Parser::new();
-    parse.set_language(&TREESITTER_CMAKE_LANGUAGE).unwrap();
-    let thetree = parse.parse(context, None);
-    let tree = thetree?;
-    Some(SemanticTokensResult::Tokens(SemanticTokens {
-        result_id: None,
-        data: sub_tokens(
-            tree.root_node(),
-            &context.lines().collect(),
-            &mut 0,
-            &mut 0,
-            false,
+fn get_modifiers_bitset(modifiers: &[SemanticTokenModifier]) -> u32 {
+    modifiers
+        .iter()
+        .filter_map(|modifier| LEGEND_MODIFIER.iter().position(|data| data == modifier))
+        .fold(0, |bitset, index| bitset | (1 << index))
+}
+
+fn command_modifiers(name: &str) -> u32 {
+    let name = name.to_lowercase();
+    let mut modifiers = vec![];
+    if BUILDIN_COMMAND_NAMES.contains(&name) {
+        modifiers.push(SemanticTokenModifier::DEFAULT_LIBRARY);
+    }
+    if DEPRECATED_COMMANDS.contains(&name.as_str()) {
+        modifiers.push(SemanticTokenModifier::DEPRECATED);
+    }
+    get_modifiers_bitset(&modifiers)
+}
+
+// cache variables and the variables provided by cmake should not be changed by the user
+fn variable_modifiers(variable: tree_sitter::Node, name: &str) -> u32 {
+    let is_cache = variable
+        .parent()
+        .is_some_and(|parent| parent.kind() == CMakeNodeKinds::CACHE_VAR);
+    if is_cache || BUILDIN_VARIABLE_NAMES.contains(name) {
+        get_modifiers_bitset(&[SemanticTokenModifier::READONLY])
+    } else {
+        0
+    }
+}
+
+fn get_tokens(context: &str, tree: &tree_sitter::Tree) -> Vec<SemanticToken> {
+    sub_tokens(
+        tree.root_node(),
+        &context.lines().collect(),
+        &mut 0,
+        &mut 0,
+        false,
+    )
+}
+
+fn next_result_id() -> String {
+    RESULT_ID.fetch_add(1, Ordering::Relaxed).to_string()
+}
+
+pub async fn semantic_token(
+    _client: &Client,
+    uri: &Url,
+    context: &str,
+    tree: &tree_sitter::Tree,
+) -> Option<SemanticTokensResult> {
+    let tokens = SemanticTokens {
+        result_id: Some(next_result_id()),
+        data: get_tokens(context, tree),
+    };
+    let mut cache = SEMANTIC_TOKEN_CACHE.lock().await;
+    cache.insert(uri.clone(), tokens.clone());
+    Some(SemanticTokensResult::Tokens(tokens))
+}
+
+/// only send the changed tokens if the previous result is still cached
+pub async fn semantic_token_delta(
+    uri: &Url,
+    context: &str,
+    tree: &tree_sitter::Tree,
+    previous_result_id: &str,
+) -> Option<SemanticTokensFullDeltaResult> {
+    let tokens = SemanticTokens {
+        result_id: Some(next_result_id()),
+        data: get_tokens(context, tree),
+    };
+    let mut cache = SEMANTIC_TOKEN_CACHE.lock().await;
+    let previous = cache.insert(uri.clone(), tokens.clone());
+    match previous {
+        Some(previous) if previous.result_id.as_deref() == Some(previous_result_id) => Some(
+            SemanticTokensFullDeltaResult::TokensDelta(SemanticTokensDelta {
+                result_id: tokens.result_id,
+                edits: get_token_edits(&previous.data, &tokens.data),
+            }),
         ),
+        _ => Some(SemanticTokensFullDeltaResult::Tokens(tokens)),
+    }
+}
+
+pub async fn remove_cache(uri: &Url) {
+    let mut cache = SEMANTIC_TOKEN_CACHE.lock().await;
+    cache.remove(uri);
+}
+
+// NOTE: the start and the delete count of the edit are counted by u32, five for one token
+fn get_token_edits(
+    previous: &[SemanticToken],
+    current: &[SemanticToken],
+) -> Vec<SemanticTokensEdit> {
+    let prefix = previous
+        .iter()
+        .zip(current)
+        .take_while(|(previous, current)| previous == current)
+        .count();
+    let suffix = previous[prefix..]
+        .iter()
+        .rev()
+        .zip(current[prefix..].iter().rev())
+        .take_while(|(previous, current)| previous == current)
+        .count();
+    let deleted = previous.len() - prefix - suffix;
+    let inserted = &current[prefix..current.len() - suffix];
+    if deleted == 0 && inserted.is_empty() {
+        return vec![];
+    }
+    vec![SemanticTokensEdit {
+        start: prefix as u32 * 5,
+        delete_count: deleted as u32 * 5,
+        data: Some(inserted.to_vec()),
+    }]
+}
+
+/// the tokens in the range, the first one is relative to the start of the document
+pub fn semantic_token_range(
+    context: &str,
+    tree: &tree_sitter::Tree,
+    range: Range,
+) -> Option<SemanticTokensRangeResult> {
+    let mut data = vec![];
+    let (mut line, mut start) = (0, 0);
+    let (mut preline, mut prestart) = (0, 0);
+    for token in get_tokens(context, tree) {
+        line += token.delta_line;
+        if token.delta_line != 0 {
+            start = 0;
+        }
+        start += token.delta_start;
+        if line < range.start.line || line > range.end.line {
+            continue;
+        }
+        if line != preline {
+            prestart = 0;
+        }
+        data.push(SemanticToken {
+            delta_line: line - preline,
+            delta_start: start - prestart,
+            ..token
+        });
+        preline = line;
+        prestart = start;
+    }
+    Some(SemanticTokensRangeResult::Tokens(SemanticTokens {
+        result_id: None,
+        data,
     }))
 }
 
@@ -92,7 +298,7 @@ fn sub_tokens(
                     delta_start: x as u32 - *prestart,
                     length: (y - x) as u32,
                     token_type: get_token_position(SemanticTokenType::VARIABLE),
-                    token_modifiers_bitset: 0,
+                    token_modifiers_bitset: variable_modifiers(child, &source[h][x..y]),
                 });
                 *preline = h as u32;
                 *prestart = x as u32;
@@ -116,7 +322,7 @@ fn sub_tokens(
                     delta_start: x as u32 - *prestart,
                     length: (y - x) as u32,
                     token_type: get_token_position(SemanticTokenType::METHOD),
-                    token_modifiers_bitset: 0,
+                    token_modifiers_bitset: command_modifiers(&source[h][x..y]),
                 });
                 *preline = h as u32;
                 *prestart = x as u32;
@@ -169,6 +375,15 @@ fn sub_tokens(
             CMakeNodeKinds::ARGUMENT_LIST => {
                 let mut argument_course = child.walk();
                 let mut is_first_val = !is_if; // NOTE: if is if, not check it
+                let command_name = input
+                    .child(0)
+                    .map(|id| {
+                        source[id.start_position().row]
+                            [id.start_position().column..id.end_position().column]
+                            .to_lowercase()
+                    })
+                    .unwrap_or_default();
+                let is_declaration = DECLARATION_COMMANDS.contains(&command_name.as_str());
                 for argument in child.children(&mut argument_course) {
                     let h = argument.start_position().row;
                     let x = argument.start_position().column;
@@ -301,6 +516,48 @@ fn sub_tokens(
                         is_first_val = false;
                         continue;
                     }
+                    // NOTE: the generator expressions, the variables inside are still highlighted
+                    if let Some(unquoted_argument) = argument.child(0).filter(|child| {
+                        child.kind() == CMakeNodeKinds::UNQUOTED_ARGUMENT
+                            && h == argument.end_position().row
+                            && source[h][x..y].contains("$<")
+                    }) {
+                        let mut ranges = genex::highlight_ranges(&source[h][x..y])
+                            .into_iter()
+                            .map(|(start, end)| (x + start, x + end))
+                            .peekable();
+                        let mut unquoted_argument_course = unquoted_argument.walk();
+                        let variables = unquoted_argument
+                            .children(&mut unquoted_argument_course)
+                            .map(Some)
+                            .chain(std::iter::once(None));
+                        for variable in variables {
+                            let next_column = variable.map_or(y, |v| v.start_position().column);
+                            while let Some((start, end)) =
+                                ranges.next_if(|(start, _)| *start < next_column)
+                            {
+                                if h as u32 != *preline {
+                                    *prestart = 0;
+                                }
+                                res.push(SemanticToken {
+                                    delta_line: h as u32 - *preline,
+                                    delta_start: start as u32 - *prestart,
+                                    length: (end - start) as u32,
+                                    token_type: get_token_position(SemanticTokenType::MACRO),
+                                    token_modifiers_bitset: 0,
+                                });
+                                *prestart = start as u32;
+                                *preline = h as u32;
+                            }
+                            if let Some(variable) = variable {
+                                res.append(&mut sub_tokens(
+                                    variable, source, preline, prestart, false,
+                                ));
+                            }
+                        }
+                        is_first_val = false;
+                        continue;
+                    }
                     if argument
                         .child(0)
                         .is_some_and(|child| child.child_count() != 0)
@@ -316,6 +573,26 @@ fn sub_tokens(
                         continue;
                     }
                     let name = &source[h][x..y];
+                    if is_first_val && is_declaration {
+                        let token_type = if command_name == "set" || command_name == "option" {
+                            SemanticTokenType::VARIABLE
+                        } else {
+                            SemanticTokenType::FUNCTION
+                        };
+                        res.push(SemanticToken {
+                            delta_line: h as u32 - *preline,
+                            delta_start: x as u32 - *prestart,
+                            length: (y - x) as u32,
+                            token_type: get_token_position(token_type),
+                            token_modifiers_bitset: get_modifiers_bitset(&[
+                                SemanticTokenModifier::DECLARATION,
+                            ]),
+                        });
+                        *prestart = x as u32;
+                        *preline = h as u32;
+                        is_first_val = false;
+                        continue;
+                    }
                     if BOOL_VAL.contains(&name) {
                         res.push(SemanticToken {
                             delta_line: h as u32 - *preline,
@@ -341,7 +618,7 @@ fn sub_tokens(
                         *preline = h as u32;
                         continue;
                     }
-                    if UNIQUE_KEYWORD.contains(&name) {
+                    if condition::is_operator(name) {
                         res.push(SemanticToken {
                             delta_line: h as u32 - *preline,
                             delta_start: x as u32 - *prestart,
@@ -435,6 +712,7 @@ fn test_number() {
 
 #[test]
 fn test_hl() {
+    use crate::consts::TREESITTER_CMAKE_LANGUAGE;
     fn semantic_token_test(context: &str) -> Option<SemanticTokensResult> {
         let mut parse = tree_sitter::Parser::new();
         parse.set_language(&TREESITTER_CMAKE_LANGUAGE).unwrap();
@@ -453,3 +731,54 @@ fn test_hl() {
     }
     semantic_token_test(include_str!("../assert/highlight/bracket_argument.cmake"));
 }
+
+#[test]
+fn test_token_edits() {
+    let token = |delta_line, token_type| SemanticToken {
+        delta_line,
+        delta_start: 0,
+        length: 3,
+        token_type,
+        token_modifiers_bitset: 0,
+    };
+    let previous = vec![token(0, 1), token(1, 2), token(1, 3)];
+    let current = vec![token(0, 1), token(1, 4), token(1, 4), token(1, 3)];
+    assert_eq!(
+        get_token_edits(&previous, &current),
+        vec![SemanticTokensEdit {
+            start: 5,
+            delete_count: 5,
+            data: Some(vec![token(1, 4), token(1, 4)]),
+        }]
+    );
+    assert!(get_token_edits(&current, &current).is_empty());
+    assert_eq!(
+        get_modifiers_bitset(&[
+            SemanticTokenModifier::DECLARATION,
+            SemanticTokenModifier::DEFAULT_LIBRARY
+        ]),
+        0b1001
+    );
+}
+
+#[test]
+fn test_genex_hl() {
+    use crate::consts::TREESITTER_CMAKE_LANGUAGE;
+    let context = "add_custom_target(demo COMMAND $<TARGET_FILE:${NAME}>)\n";
+    let mut parse = tree_sitter::Parser::new();
+    parse.set_language(&TREESITTER_CMAKE_LANGUAGE).unwrap();
+    let tree = parse.parse(context, None).unwrap();
+    let mut start = 0;
+    let tokens: Vec<(u32, u32, u32)> = get_tokens(context, &tree)
+        .iter()
+        .map(|token| {
+            start += token.delta_start;
+            (start, token.length, token.token_type)
+        })
+        .collect();
+    let macro_type = get_token_position(SemanticTokenType::MACRO);
+    let variable_type = get_token_position(SemanticTokenType::VARIABLE);
+    assert!(tokens.contains(&(31, 13, macro_type)));
+    assert!(tokens.contains(&(47, 4, variable_type)));
+    assert!(tokens.contains(&(52, 1, macro_type)));
+}
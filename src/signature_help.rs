@@ -0,0 +1,612 @@
+/// provide signature help for buildin commands and user defined functions or macros
+use std::path::Path;
+use std::sync::LazyLock;
+
+use tower_lsp::lsp_types::{
+    ParameterInformation, ParameterLabel, Position, SignatureHelp, SignatureInformation,
+};
+use tree_sitter::{Node, Tree};
+
+use crate::complete::BUILDIN_SIGNATURE;
+use crate::document;
+use crate::jump::get_cached_defs;
+use crate::utils::treehelper::{command_arguments, node_text};
+use crate::CMakeNodeKinds;
+
+static KEYWORD_REGEX: LazyLock<regex::Regex> =
+    LazyLock::new(|| regex::Regex::new(r"\b[A-Z][A-Z0-9_]+\b").unwrap());
+
+/// the command which the cursor is in, and the arguments before the cursor
+#[derive(Debug, PartialEq, Eq)]
+struct CommandCall {
+    name: String,
+    arguments: Vec<String>,
+    // the argument the cursor is on, it may be empty
+    current: String,
+}
+
+impl CommandCall {
+    fn active_index(&self) -> usize {
+        self.arguments.len()
+    }
+
+    // the arguments till the cursor, include the one which is being typed
+    fn typed_arguments(&self) -> Vec<&str> {
+        let mut arguments: Vec<&str> = self.arguments.iter().map(|arg| arg.as_str()).collect();
+        if !self.current.is_empty() {
+            arguments.push(&self.current);
+        }
+        arguments
+    }
+}
+
+fn skip_bracket<I: Iterator<Item = char>>(
+    chars: &mut std::iter::Peekable<I>,
+    output: &mut String,
+) -> bool {
+    let mut level = 0;
+    while chars.peek() == Some(&'=') {
+        chars.next();
+        output.push('=');
+        level += 1;
+    }
+    if chars.peek() != Some(&'[') {
+        return false;
+    }
+    chars.next();
+    output.push('[');
+    let mut closing: Option<usize> = None;
+    for c in chars.by_ref() {
+        output.push(c);
+        closing = match (c, closing) {
+            (']', Some(count)) if count == level => return true,
+            (']', _) => Some(0),
+            ('=', Some(count)) => Some(count + 1),
+            _ => None,
+        };
+    }
+    true
+}
+
+// NOTE: the code before cursor may be incomplete, so do not use the tree here, lex the source directly
+fn get_command_call(source: &str, location: Position) -> Option<CommandCall> {
+    let before = source.get(..document::position_to_byte(source, location))?;
+
+    let mut name = String::new();
+    let mut name_finished = false;
+    let mut call: Option<CommandCall> = None;
+    let mut depth = 0;
+    let mut chars = before.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '#' => {
+                let mut comment = String::new();
+                let is_bracket_comment = chars.peek() == Some(&'[') && {
+                    chars.next();
+                    skip_bracket(&mut chars, &mut comment)
+                };
+                if !is_bracket_comment {
+                    for c in chars.by_ref() {
+                        if c == '\n' {
+                            break;
+                        }
+                    }
+                }
+                match call {
+                    Some(ref mut call) if !call.current.is_empty() => {
+                        call.arguments.push(std::mem::take(&mut call.current));
+                    }
+                    Some(_) => {}
+                    None => name.clear(),
+                }
+            }
+            '"' => {
+                let Some(CommandCall {
+                    ref mut current, ..
+                }) = call
+                else {
+                    continue;
+                };
+                current.push(c);
+                while let Some(c) = chars.next() {
+                    current.push(c);
+                    match c {
+                        '\\' => {
+                            if let Some(c) = chars.next() {
+                                current.push(c);
+                            }
+                        }
+                        '"' => break,
+                        _ => {}
+                    }
+                }
+            }
+            '[' if call.as_ref().is_some_and(|call| call.current.is_empty()) => {
+                let current = &mut call.as_mut().unwrap().current;
+                current.push(c);
+                skip_bracket(&mut chars, current);
+            }
+            '(' => match call {
+                Some(ref mut call) => {
+                    depth += 1;
+                    if !call.current.is_empty() {
+                        call.arguments.push(std::mem::take(&mut call.current));
+                    }
+                }
+                None if !name.is_empty() => {
+                    depth = 1;
+                    call = Some(CommandCall {
+                        name: std::mem::take(&mut name),
+                        arguments: vec![],
+                        current: String::new(),
+                    });
+                }
+                None => {}
+            },
+            ')' => {
+                let Some(ref mut inner) = call else {
+                    continue;
+                };
+                depth -= 1;
+                if depth == 0 {
+                    call = None;
+                    name.clear();
+                } else if !inner.current.is_empty() {
+                    inner.arguments.push(std::mem::take(&mut inner.current));
+                }
+            }
+            c if c.is_whitespace() => match call {
+                Some(ref mut call) => {
+                    if !call.current.is_empty() {
+                        call.arguments.push(std::mem::take(&mut call.current));
+                    }
+                }
+                None => name_finished = !name.is_empty(),
+            },
+            _ => match call {
+                Some(ref mut call) => {
+                    call.current.push(c);
+                    if c == '\\' {
+                        if let Some(c) = chars.next() {
+                            call.current.push(c);
+                        }
+                    }
+                }
+                None if c.is_ascii_alphanumeric() || c == '_' => {
+                    if name_finished {
+                        name.clear();
+                        name_finished = false;
+                    }
+                    name.push(c);
+                }
+                None => {
+                    name.clear();
+                    name_finished = false;
+                }
+            },
+        }
+    }
+    call
+}
+
+/// split the parameters of the signature, like `add_library(<name> [<type>] <sources>...)`
+/// return the start and end of every parameter in the signature
+fn split_parameters(signature: &str) -> Vec<(usize, usize)> {
+    let Some(start) = signature.find('(') else {
+        return vec![];
+    };
+    let end = signature.rfind(')').unwrap_or(signature.len());
+    let mut parameters = vec![];
+    let mut depth = 0;
+    let mut parameter_start: Option<usize> = None;
+    for (index, c) in signature[start + 1..end].char_indices() {
+        let index = index + start + 1;
+        match c {
+            '<' | '[' | '{' => depth += 1,
+            '>' | ']' | '}' => depth -= 1,
+            c if c.is_whitespace() && depth == 0 => {
+                if let Some(parameter_start) = parameter_start.take() {
+                    parameters.push((parameter_start, index));
+                }
+                continue;
+            }
+            _ => {}
+        }
+        if parameter_start.is_none() {
+            parameter_start = Some(index);
+        }
+    }
+    if let Some(parameter_start) = parameter_start {
+        parameters.push((parameter_start, end));
+    }
+    parameters
+}
+
+fn parameter_keywords(parameter: &str) -> Vec<&str> {
+    KEYWORD_REGEX
+        .find_iter(parameter)
+        .map(|keyword| keyword.as_str())
+        .collect()
+}
+
+// parameter like `[EXCLUDE_FROM_ALL]` or `{FILES | PROGRAMS}`, which does not take a value
+fn is_keyword_only(parameter: &str) -> bool {
+    KEYWORD_REGEX
+        .replace_all(parameter, "")
+        .chars()
+        .all(|c| "[]{}<>|. ".contains(c))
+}
+
+// parameter like `<name>` or `[<source>...]`, which takes the value without a keyword
+fn is_positional(parameter: &str) -> bool {
+    let Some(value_start) = parameter.find('<') else {
+        return false;
+    };
+    KEYWORD_REGEX
+        .find(parameter)
+        .is_none_or(|keyword| keyword.start() > value_start)
+}
+
+fn positional_parameter(parameters: &[&str], after: Option<usize>, index: usize) -> Option<usize> {
+    let positional: Vec<usize> = parameters
+        .iter()
+        .enumerate()
+        .filter(|(parameter_index, parameter)| {
+            after.is_none_or(|after| *parameter_index > after) && is_positional(parameter)
+        })
+        .map(|(parameter_index, _)| parameter_index)
+        .collect();
+    if let Some(parameter_index) = positional.get(index) {
+        return Some(*parameter_index);
+    }
+    positional
+        .last()
+        .filter(|parameter_index| parameters[**parameter_index].contains("..."))
+        .copied()
+}
+
+/// get the parameter of the argument under cursor
+/// the last keyword before the cursor decides the section, otherwise count the positional ones
+fn get_active_parameter(parameters: &[&str], call: &CommandCall) -> Option<usize> {
+    let typed_arguments = call.typed_arguments();
+    let active_index = call.active_index();
+    for (argument_index, argument) in typed_arguments.iter().enumerate().rev() {
+        let Some(parameter_index) = parameters
+            .iter()
+            .position(|parameter| parameter_keywords(parameter).contains(argument))
+        else {
+            continue;
+        };
+        let after_keyword = active_index - argument_index;
+        if after_keyword == 0 || !is_keyword_only(parameters[parameter_index]) {
+            return Some(parameter_index);
+        }
+        return Some(
+            positional_parameter(parameters, Some(parameter_index), after_keyword - 1)
+                .unwrap_or(parameter_index),
+        );
+    }
+    positional_parameter(parameters, None, active_index)
+}
+
+fn to_utf16_offset(label: &str, offset: usize) -> u32 {
+    label[..offset].encode_utf16().count() as u32
+}
+
+// NOTE: parameters of user defined function have no keywords, the arguments are matched in order
+fn get_signature_information(
+    signature: &str,
+    call: &CommandCall,
+    is_buildin: bool,
+) -> SignatureInformation {
+    let offsets = split_parameters(signature);
+    let parameters: Vec<&str> = offsets
+        .iter()
+        .map(|(start, end)| &signature[*start..*end])
+        .collect();
+    let active_parameter = if is_buildin {
+        get_active_parameter(&parameters, call)
+    } else {
+        Some(call.active_index()).filter(|index| *index < parameters.len())
+    };
+    SignatureInformation {
+        label: signature.to_string(),
+        documentation: None,
+        parameters: Some(
+            offsets
+                .iter()
+                .map(|(start, end)| ParameterInformation {
+                    label: ParameterLabel::LabelOffsets([
+                        to_utf16_offset(signature, *start),
+                        to_utf16_offset(signature, *end),
+                    ]),
+                    documentation: None,
+                })
+                .collect(),
+        ),
+        active_parameter: active_parameter.map(|index| index as u32),
+    }
+}
+
+// NOTE: commands like install have many signatures, the first argument decides which one is used
+fn get_active_signature(signatures: &[String], call: &CommandCall) -> u32 {
+    let Some(first_argument) = call.typed_arguments().first().copied() else {
+        return 0;
+    };
+    signatures
+        .iter()
+        .position(|signature| {
+            split_parameters(signature)
+                .first()
+                .is_some_and(|(start, end)| {
+                    parameter_keywords(&signature[*start..*end]).contains(&first_argument)
+                })
+        })
+        .unwrap_or(0) as u32
+}
+
+/// find the function or macro defined in the tree, and generate the signature like `name(arg1 arg2)`
+fn get_defined_signature(input: Node, source: &[&str], name: &str) -> Option<String> {
+    let mut course = input.walk();
+    for child in input.children(&mut course) {
+        match child.kind() {
+            CMakeNodeKinds::FUNCTION_DEF | CMakeNodeKinds::MACRO_DEF => {
+                let Some(command) = child.child(0) else {
+                    continue;
+                };
+                let arguments: Vec<&str> = command_arguments(command)
+                    .iter()
+                    .filter_map(|argument| node_text(source, argument))
+                    .collect();
+                let Some((defined_name, parameters)) = arguments.split_first() else {
+                    continue;
+                };
+                if defined_name.eq_ignore_ascii_case(name) {
+                    return Some(format!("{defined_name}({})", parameters.join(" ")));
+                }
+            }
+            CMakeNodeKinds::IF_CONDITION | CMakeNodeKinds::FOREACH_LOOP | CMakeNodeKinds::BODY => {
+                if let Some(signature) = get_defined_signature(child, source, name) {
+                    return Some(signature);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+async fn get_project_defined_signature<P: AsRef<Path>>(
+    local_path: P,
+    name: &str,
+) -> Option<String> {
+    let location = get_cached_defs(local_path, name).await?;
+    let (context, tree) = match document::get_document(&location.uri).await {
+        Some(opened) => opened,
+        None => {
+            let path = location.uri.to_file_path().ok()?;
+            let context = tokio::fs::read_to_string(&path).await.ok()?;
+            let tree = document::parse(&context, None)?;
+            (context, tree)
+        }
+    };
+    get_defined_signature(
+        tree.root_node(),
+        &context.lines().collect::<Vec<&str>>(),
+        name,
+    )
+}
+
+/// get the signature help of the command under the cursor
+pub async fn get_signature_help<P: AsRef<Path>>(
+    location: Position,
+    source: &str,
+    tree: &Tree,
+    local_path: P,
+) -> Option<SignatureHelp> {
+    let call = get_command_call(source, location)?;
+    let lines: Vec<&str> = source.lines().collect();
+    let defined_signature = match get_defined_signature(tree.root_node(), &lines, &call.name) {
+        Some(signature) => Some(signature),
+        None => get_project_defined_signature(local_path, &call.name).await,
+    };
+    if let Some(signature) = defined_signature {
+        return Some(SignatureHelp {
+            signatures: vec![get_signature_information(&signature, &call, false)],
+            active_signature: Some(0),
+            active_parameter: None,
+        });
+    }
+    let signatures = BUILDIN_SIGNATURE.get(&call.name.to_lowercase())?;
+    Some(SignatureHelp {
+        signatures: signatures
+            .iter()
+            .map(|signature| get_signature_information(signature, &call, true))
+            .collect(),
+        active_signature: Some(get_active_signature(signatures, &call)),
+        active_parameter: None,
+    })
+}
+
+#[cfg(test)]
+mod signature_help_test {
+    use super::*;
+    use crate::consts::TREESITTER_CMAKE_LANGUAGE;
+
+    fn active_parameter(signature: &str, source: &str, is_buildin: bool) -> Option<u32> {
+        let call = get_command_call(
+            source,
+            Position {
+                line: 0,
+                character: source.len() as u32,
+            },
+        )
+        .unwrap();
+        get_signature_information(signature, &call, is_buildin).active_parameter
+    }
+
+    #[test]
+    fn tst_command_call() {
+        let source = r#"set(A "a b(" [[c)]]) # abc(
+add_library (mylib STATIC ${A}"#;
+        assert_eq!(
+            get_command_call(
+                source,
+                Position {
+                    line: 1,
+                    character: 100,
+                },
+            ),
+            Some(CommandCall {
+                name: "add_library".to_string(),
+                arguments: vec!["mylib".to_string(), "STATIC".to_string()],
+                current: "${A}".to_string(),
+            })
+        );
+        assert_eq!(
+            get_command_call(
+                source,
+                Position {
+                    line: 0,
+                    character: 10,
+                },
+            ),
+            Some(CommandCall {
+                name: "set".to_string(),
+                arguments: vec!["A".to_string()],
+                current: "\"a b".to_string(),
+            })
+        );
+        assert_eq!(
+            get_command_call(
+                source,
+                Position {
+                    line: 0,
+                    character: 27,
+                },
+            ),
+            None
+        );
+        // the character is counted by utf16
+        assert_eq!(
+            get_command_call(
+                "message(\"中文\" a",
+                Position {
+                    line: 0,
+                    character: 14,
+                },
+            ),
+            Some(CommandCall {
+                name: "message".to_string(),
+                arguments: vec!["\"中文\"".to_string()],
+                current: "a".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn tst_split_parameters() {
+        let signature = "add_library(<name> [STATIC | SHARED] [EXCLUDE_FROM_ALL] <sources>...)";
+        let parameters: Vec<&str> = split_parameters(signature)
+            .into_iter()
+            .map(|(start, end)| &signature[start..end])
+            .collect();
+        assert_eq!(
+            parameters,
+            vec![
+                "<name>",
+                "[STATIC | SHARED]",
+                "[EXCLUDE_FROM_ALL]",
+                "<sources>..."
+            ]
+        );
+    }
+
+    #[test]
+    fn tst_active_parameter() {
+        let signature = "add_library(<name> [STATIC | SHARED] [EXCLUDE_FROM_ALL] <sources>...)";
+        assert_eq!(active_parameter(signature, "add_library(", true), Some(0));
+        assert_eq!(
+            active_parameter(signature, "add_library(abc ", true),
+            Some(3)
+        );
+        assert_eq!(
+            active_parameter(signature, "add_library(abc STATIC", true),
+            Some(1)
+        );
+        assert_eq!(
+            active_parameter(signature, "add_library(abc STATIC a.cpp b.cpp ", true),
+            Some(3)
+        );
+
+        let signature = "define_property(<GLOBAL | TARGET> PROPERTY <name> [INHERITED] [BRIEF_DOCS <brief-doc> [docs...]])";
+        assert_eq!(
+            active_parameter(signature, "define_property(TARGET PROPERTY ", true),
+            Some(2)
+        );
+        assert_eq!(
+            active_parameter(
+                signature,
+                "define_property(TARGET PROPERTY abc BRIEF_DOCS ",
+                true
+            ),
+            Some(4)
+        );
+
+        assert_eq!(active_parameter("my_fun(a b)", "my_fun(x ", false), Some(1));
+        assert_eq!(active_parameter("my_fun(a b)", "my_fun(x y z", false), None);
+    }
+
+    #[test]
+    fn tst_active_signature() {
+        let signatures = vec![
+            "install(TARGETS <target>... [...])".to_string(),
+            "install({FILES | PROGRAMS} <file>... [...])".to_string(),
+        ];
+        let call = get_command_call(
+            "install(PROGRAMS ",
+            Position {
+                line: 0,
+                character: 17,
+            },
+        )
+        .unwrap();
+        assert_eq!(get_active_signature(&signatures, &call), 1);
+    }
+
+    #[tokio::test]
+    async fn tst_defined_signature() {
+        let source = r#"function(my_fun name type)
+endfunction()
+if(WIN32)
+  macro(my_macro)
+  endmacro()
+endif()
+my_fun(abc "#;
+        let mut parse = tree_sitter::Parser::new();
+        parse.set_language(&TREESITTER_CMAKE_LANGUAGE).unwrap();
+        let tree = parse.parse(source, None).unwrap();
+        let lines: Vec<&str> = source.lines().collect();
+        assert_eq!(
+            get_defined_signature(tree.root_node(), &lines, "my_fun"),
+            Some("my_fun(name type)".to_string())
+        );
+        assert_eq!(
+            get_defined_signature(tree.root_node(), &lines, "MY_MACRO"),
+            Some("my_macro()".to_string())
+        );
+        let help = get_signature_help(
+            Position {
+                line: 6,
+                character: 11,
+            },
+            source,
+            &tree,
+            "/tmp/signature_help/CMakeLists.txt",
+        )
+        .await
+        .unwrap();
+        assert_eq!(help.signatures[0].label, "my_fun(name type)");
+        assert_eq!(help.signatures[0].active_parameter, Some(1));
+    }
+}
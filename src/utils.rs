@@ -151,7 +151,7 @@ pub fn replace_placeholders(template: &str) -> Option<String> {
     replace_placeholders_with_hashmap(template, &values)
 }
 
-fn replace_placeholders_with_hashmap(
+pub fn replace_placeholders_with_hashmap(
     template: &str,
     values: &HashMap<String, String>,
 ) -> Option<String> {
@@ -289,3 +289,41 @@ fn package_name_check_tst() {
         .collect();
     assert_eq!(output, vec!["abc", "def", "ghi"]);
 }
+
+/// the count of the chars to insert, remove or replace to change one word to the other
+pub fn edit_distance(origin: &str, target: &str) -> usize {
+    let target: Vec<char> = target.chars().collect();
+    let mut previous: Vec<usize> = (0..=target.len()).collect();
+    for (i, origin_char) in origin.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, target_char) in target.iter().enumerate() {
+            let replace = previous[j] + usize::from(origin_char != *target_char);
+            current.push(replace.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[target.len()]
+}
+
+/// the most similar word in the candidates, used to find the typo
+pub fn find_similar<'a>(word: &str, candidates: &[&'a str]) -> Option<&'a str> {
+    let max_distance = (word.chars().count() / 3).clamp(1, 3);
+    candidates
+        .iter()
+        .map(|candidate| (edit_distance(word, candidate), *candidate))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+#[test]
+fn similar_word_tst() {
+    assert_eq!(edit_distance("PUBLLIC", "PUBLIC"), 1);
+    assert_eq!(edit_distance("EXIST", "EXISTS"), 1);
+    assert_eq!(edit_distance("abc", "abc"), 0);
+    assert_eq!(
+        find_similar("PUBLLIC", &["PRIVATE", "PUBLIC", "INTERFACE"]),
+        Some("PUBLIC")
+    );
+    assert_eq!(find_similar("SOURCES", &["PRIVATE", "PUBLIC"]), None);
+}
@@ -84,6 +84,31 @@ fn test_change() {
     );
 }
 
+/// the text of the node, None if it takes more than one line
+pub fn node_text<'a>(source: &[&'a str], node: &Node) -> Option<&'a str> {
+    let start = node.start_position();
+    let end = node.end_position();
+    if start.row != end.row {
+        return None;
+    }
+    source.get(start.row)?.get(start.column..end.column)
+}
+
+/// the arguments of the command, without the comments between them
+pub fn command_arguments(command: Node) -> Vec<Node> {
+    command
+        .child(2)
+        .filter(|node| node.kind() == CMakeNodeKinds::ARGUMENT_LIST)
+        .map(|argument_list| {
+            let mut course = argument_list.walk();
+            argument_list
+                .children(&mut course)
+                .filter(|argument| argument.kind() == CMakeNodeKinds::ARGUMENT)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
 /// get the position of the string
 pub fn get_point_string<'a>(location: Point, root: Node, source: &Vec<&'a str>) -> Option<&'a str> {
     let mut course = root.walk();
@@ -0,0 +1,344 @@
+/// Search functions, macros, options, cache variables and targets in the whole project
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::LazyLock;
+
+use tokio::sync::{Mutex, OnceCell};
+use tower_lsp::lsp_types::{Location, Range, SymbolInformation, SymbolKind, Url};
+use tree_sitter::Node;
+
+use crate::consts::TARGET_DEFINE_COMMANDS;
+use crate::document::{self, DOCUMENTS_CACHE};
+use crate::references::get_file_references;
+use crate::scansubs::TREE_MAP;
+use crate::utils::treehelper::{command_arguments, node_text, ToPosition};
+use crate::utils::CACHE_CMAKE_PACKAGES_WITHKEYS;
+use crate::CMakeNodeKinds;
+
+pub type SymbolKV = HashMap<PathBuf, Vec<SymbolInformation>>;
+
+/// NOTE: the symbols defined in every file
+pub static SYMBOL_CACHE: LazyLock<Arc<Mutex<SymbolKV>>> =
+    LazyLock::new(|| Arc::new(Mutex::new(HashMap::new())));
+
+/// the packages and the targets imported by them, they will not change when the server is running
+static PACKAGE_SYMBOLS: OnceCell<Vec<SymbolInformation>> = OnceCell::const_new();
+
+#[allow(deprecated)]
+fn symbol_information(
+    name: &str,
+    kind: SymbolKind,
+    container_name: &str,
+    location: Location,
+) -> SymbolInformation {
+    SymbolInformation {
+        name: name.to_string(),
+        kind,
+        tags: None,
+        deprecated: None,
+        location,
+        container_name: Some(container_name.to_string()),
+    }
+}
+
+// only the name without variables inside can be searched
+fn get_arguments<'a>(source: &[&'a str], command: Node) -> Vec<(&'a str, Range)> {
+    command_arguments(command)
+        .into_iter()
+        .filter_map(|argument| {
+            let unquoted = argument.child(0).filter(|node| {
+                node.kind() == CMakeNodeKinds::UNQUOTED_ARGUMENT && node.child_count() == 0
+            })?;
+            Some((
+                node_text(source, &unquoted)?,
+                Range {
+                    start: unquoted.start_position().to_position(),
+                    end: unquoted.end_position().to_position(),
+                },
+            ))
+        })
+        .collect()
+}
+
+fn scan_symbols_inner(
+    input: Node,
+    source: &[&str],
+    uri: &Url,
+    imported_only: bool,
+    symbols: &mut Vec<SymbolInformation>,
+) {
+    let location = |range: Range| Location {
+        uri: uri.clone(),
+        range,
+    };
+    let mut course = input.walk();
+    for child in input.children(&mut course) {
+        match child.kind() {
+            CMakeNodeKinds::FUNCTION_DEF | CMakeNodeKinds::MACRO_DEF if !imported_only => {
+                let container_name = if child.kind() == CMakeNodeKinds::FUNCTION_DEF {
+                    "function"
+                } else {
+                    "macro"
+                };
+                let Some(command) = child.child(0) else {
+                    continue;
+                };
+                if let Some((name, range)) = get_arguments(source, command).first() {
+                    symbols.push(symbol_information(
+                        name,
+                        SymbolKind::FUNCTION,
+                        container_name,
+                        location(*range),
+                    ));
+                }
+            }
+            CMakeNodeKinds::NORMAL_COMMAND => {
+                let Some(command_name) = child.child(0).and_then(|id| node_text(source, &id))
+                else {
+                    continue;
+                };
+                let command_name = command_name.to_lowercase();
+                let arguments = get_arguments(source, child);
+                let Some((name, range)) = arguments.first() else {
+                    continue;
+                };
+                let is_imported = arguments.iter().any(|(arg, _)| *arg == "IMPORTED");
+                let (kind, container_name) = match command_name.as_str() {
+                    command if TARGET_DEFINE_COMMANDS.contains(&command) && is_imported => {
+                        (SymbolKind::CLASS, "imported target")
+                    }
+                    _ if imported_only => continue,
+                    command if TARGET_DEFINE_COMMANDS.contains(&command) => {
+                        (SymbolKind::CLASS, "target")
+                    }
+                    "option" => (SymbolKind::VARIABLE, "option"),
+                    "set" if arguments.iter().any(|(arg, _)| *arg == "CACHE") => {
+                        (SymbolKind::VARIABLE, "cache variable")
+                    }
+                    _ => continue,
+                };
+                symbols.push(symbol_information(
+                    name,
+                    kind,
+                    container_name,
+                    location(*range),
+                ));
+            }
+            _ => scan_symbols_inner(child, source, uri, imported_only, symbols),
+        }
+    }
+}
+
+fn scan_symbols(
+    root: Node,
+    context: &str,
+    uri: &Url,
+    imported_only: bool,
+) -> Vec<SymbolInformation> {
+    let mut symbols = vec![];
+    scan_symbols_inner(
+        root,
+        &context.lines().collect::<Vec<&str>>(),
+        uri,
+        imported_only,
+        &mut symbols,
+    );
+    symbols
+}
+
+pub async fn update_cache<P: AsRef<Path>>(path: P, context: &str) -> Vec<SymbolInformation> {
+    let path = path.as_ref();
+    let Ok(uri) = Url::from_file_path(path) else {
+        return vec![];
+    };
+    let Some(tree) = document::get_tree(&uri, context).await else {
+        return vec![];
+    };
+    let symbols = scan_symbols(tree.root_node(), context, &uri, false);
+    let mut cache = SYMBOL_CACHE.lock().await;
+    cache.insert(path.to_path_buf(), symbols.clone());
+    symbols
+}
+
+/// the file is scanned again when it is needed, after it is changed outside or closed
+pub async fn remove_cache<P: AsRef<Path>>(path: P) {
+    let mut cache = SYMBOL_CACHE.lock().await;
+    cache.remove(path.as_ref());
+}
+
+async fn get_file_symbols(path: &Path) -> Vec<SymbolInformation> {
+    let cache = SYMBOL_CACHE.lock().await;
+    if let Some(symbols) = cache.get(path) {
+        return symbols.clone();
+    }
+    drop(cache);
+    let buffer_content = match Url::from_file_path(path) {
+        Ok(uri) => document::get_text(&uri).await,
+        Err(_) => None,
+    };
+    let context = match buffer_content {
+        Some(context) => context,
+        None => match tokio::fs::read_to_string(path).await {
+            Ok(context) => context,
+            Err(_) => return vec![],
+        },
+    };
+    update_cache(path, &context).await
+}
+
+// NOTE: all the package files are read, so it runs on the blocking threads
+async fn get_cached_package_symbols() -> &'static [SymbolInformation] {
+    PACKAGE_SYMBOLS
+        .get_or_init(|| async {
+            tokio::task::spawn_blocking(get_package_symbols)
+                .await
+                .unwrap_or_default()
+        })
+        .await
+}
+
+fn get_package_symbols() -> Vec<SymbolInformation> {
+    let mut symbols = vec![];
+    for package in CACHE_CMAKE_PACKAGES_WITHKEYS.values() {
+        let Some(first_file) = package.tojump.first() else {
+            continue;
+        };
+        let Ok(uri) = Url::from_file_path(first_file) else {
+            continue;
+        };
+        symbols.push(symbol_information(
+            &package.name,
+            SymbolKind::PACKAGE,
+            "package",
+            Location {
+                uri,
+                range: Range::default(),
+            },
+        ));
+        for file in package.tojump.iter() {
+            let Ok(context) = std::fs::read_to_string(file) else {
+                continue;
+            };
+            if !context.contains("IMPORTED") {
+                continue;
+            }
+            let Ok(uri) = Url::from_file_path(file) else {
+                continue;
+            };
+            let Some(tree) = document::parse(&context, None) else {
+                continue;
+            };
+            symbols.append(&mut scan_symbols(tree.root_node(), &context, &uri, true));
+        }
+    }
+    symbols
+}
+
+// the files in the subdirectory tree, the opened ones and all the included ones
+async fn get_project_files() -> Vec<PathBuf> {
+    let tree_map = TREE_MAP.lock().await;
+    let mut files: Vec<PathBuf> = tree_map
+        .iter()
+        .flat_map(|(sub, top)| [sub.clone(), top.clone()])
+        .collect();
+    drop(tree_map);
+    let documents = DOCUMENTS_CACHE.lock().await;
+    files.extend(documents.keys().filter_map(|uri| uri.to_file_path().ok()));
+    drop(documents);
+    files.sort();
+    files.dedup();
+
+    let mut index = 0;
+    while index < files.len() {
+        let path = files[index].clone();
+        index += 1;
+        let Some(info) = get_file_references(&path).await else {
+            continue;
+        };
+        for include in info.includes {
+            if !files.contains(&include) {
+                files.push(include);
+            }
+        }
+    }
+    files
+}
+
+fn is_match(name: &str, query: &str) -> bool {
+    name.to_lowercase().contains(query)
+}
+
+/// search the symbols in the project, and the packages installed
+pub async fn get_workspace_symbols(query: &str) -> Option<Vec<SymbolInformation>> {
+    let query = query.to_lowercase();
+    let mut symbols = vec![];
+    for path in get_project_files().await {
+        symbols.extend(
+            get_file_symbols(&path)
+                .await
+                .into_iter()
+                .filter(|symbol| is_match(&symbol.name, &query)),
+        );
+    }
+    symbols.extend(
+        get_cached_package_symbols()
+            .await
+            .iter()
+            .filter(|symbol| is_match(&symbol.name, &query))
+            .cloned(),
+    );
+    if symbols.is_empty() {
+        None
+    } else {
+        Some(symbols)
+    }
+}
+
+#[cfg(test)]
+mod workspace_symbol_test {
+    use super::*;
+
+    #[test]
+    fn tst_scan_symbols() {
+        let uri = Url::from_file_path("/tmp/workspace_symbol/CMakeLists.txt").unwrap();
+        let source = r#"option(BUILD_TESTS "build tests" ON)
+set(ROOT_DIR "/usr" CACHE STRING "root dir")
+set(NORMAL_VAR abcd)
+function(my_helper name)
+  add_library(${name} STATIC)
+endfunction()
+if(BUILD_TESTS)
+  macro(my_macro)
+  endmacro()
+  add_executable(my_test main.cpp)
+endif()
+add_library(Foo::bar SHARED IMPORTED)
+"#;
+        let tree = document::parse(source, None).unwrap();
+        let symbols: Vec<(String, SymbolKind, String)> =
+            scan_symbols(tree.root_node(), source, &uri, false)
+                .into_iter()
+                .map(|symbol| (symbol.name, symbol.kind, symbol.container_name.unwrap()))
+                .collect();
+        let symbol = |name: &str, kind, container_name: &str| {
+            (name.to_string(), kind, container_name.to_string())
+        };
+        assert_eq!(
+            symbols,
+            vec![
+                symbol("BUILD_TESTS", SymbolKind::VARIABLE, "option"),
+                symbol("ROOT_DIR", SymbolKind::VARIABLE, "cache variable"),
+                symbol("my_helper", SymbolKind::FUNCTION, "function"),
+                symbol("my_macro", SymbolKind::FUNCTION, "macro"),
+                symbol("my_test", SymbolKind::CLASS, "target"),
+                symbol("Foo::bar", SymbolKind::CLASS, "imported target"),
+            ]
+        );
+        let imported: Vec<String> = scan_symbols(tree.root_node(), source, &uri, true)
+            .into_iter()
+            .map(|symbol| symbol.name)
+            .collect();
+        assert_eq!(imported, vec!["Foo::bar".to_string()]);
+    }
+}